@@ -0,0 +1,204 @@
+use super::{AgentExecutor, OutputSink};
+use crate::error::AgentError;
+use crate::types::AgentName;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Agents tried in order until one answers (`--agent claude,codex,gemini`)
+///
+/// An agent that isn't installed, times out or returns an invalid response
+/// is skipped for the rest of the run, so repair prompts go straight to the
+/// agent that answered. So is an agent whose messages still fail validation
+/// once the generation loop's repairs run out (see
+/// [`AgentExecutor::fall_back`]). Other failures are returned as-is (and
+/// retried by the generation loop if transient).
+pub struct AgentChain<A> {
+    agents: Vec<A>,
+    /// Index of the agent currently asked first
+    current: AtomicUsize,
+}
+
+impl<A: AgentExecutor> AgentChain<A> {
+    /// Chain `agents` in order of preference
+    ///
+    /// # Panics
+    /// If `agents` is empty.
+    pub fn new(agents: Vec<A>) -> Self {
+        assert!(!agents.is_empty(), "agent chain needs at least one agent");
+        Self {
+            agents,
+            current: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Whether the next agent in the chain should get the prompt
+fn falls_back(error: &AgentError) -> bool {
+    matches!(
+        error,
+        AgentError::NotFound { .. }
+            | AgentError::Timeout { .. }
+            | AgentError::InvalidResponse { .. }
+    )
+}
+
+impl<A: AgentExecutor + Sync> AgentExecutor for AgentChain<A> {
+    async fn execute(&self, prompt: &str) -> Result<String, AgentError> {
+        self.execute_streaming(prompt, &mut |_| {}).await
+    }
+
+    /// Output from an agent that fails is followed by the next agent's output
+    /// from the start
+    async fn execute_streaming(
+        &self,
+        prompt: &str,
+        on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
+        let mut index = self.current.load(Ordering::Relaxed);
+        loop {
+            match self.agents[index]
+                .execute_streaming(prompt, &mut *on_output)
+                .await
+            {
+                Err(error) if falls_back(&error) && index + 1 < self.agents.len() => {
+                    index += 1;
+                    self.current.store(index, Ordering::Relaxed);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn fall_back(&self) -> bool {
+        let index = self.current.load(Ordering::Relaxed);
+        let next = index + 1 < self.agents.len();
+        if next {
+            self.current.store(index + 1, Ordering::Relaxed);
+        }
+        next
+    }
+
+    /// The agent that answered last (the first agent before any call)
+    fn name(&self) -> AgentName {
+        self.agents[self.current.load(Ordering::Relaxed)].name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Agent with a fixed result that counts its calls
+    struct StubAgent {
+        name: AgentName,
+        result: Result<&'static str, AgentError>,
+        calls: AtomicUsize,
+    }
+
+    fn stub(name: AgentName, result: Result<&'static str, AgentError>) -> StubAgent {
+        StubAgent {
+            name,
+            result,
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    impl AgentExecutor for StubAgent {
+        async fn execute(&self, _prompt: &str) -> Result<String, AgentError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            self.result.clone().map(str::to_string)
+        }
+
+        fn name(&self) -> AgentName {
+            self.name.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn falls_back_and_remembers_the_agent_that_answered() {
+        let chain = AgentChain::new(vec![
+            stub(
+                AgentName::Claude,
+                Err(AgentError::NotFound {
+                    agent: AgentName::Claude,
+                }),
+            ),
+            stub(
+                AgentName::Codex,
+                Err(AgentError::Timeout {
+                    agent: AgentName::Codex,
+                    timeout_secs: 1,
+                }),
+            ),
+            stub(AgentName::Gemini, Ok("feat: add chain")),
+        ]);
+        assert_eq!(chain.name(), AgentName::Claude);
+
+        assert_eq!(chain.execute("prompt").await.unwrap(), "feat: add chain");
+        assert_eq!(chain.name(), AgentName::Gemini);
+
+        // Failed agents aren't asked again
+        chain.execute("repair").await.unwrap();
+        let calls: Vec<usize> = chain
+            .agents
+            .iter()
+            .map(|agent| agent.calls.load(Ordering::Relaxed))
+            .collect();
+        assert_eq!(calls, [1, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn execution_failures_do_not_fall_back() {
+        let chain = AgentChain::new(vec![
+            stub(
+                AgentName::Claude,
+                Err(AgentError::ExecutionFailed {
+                    agent: AgentName::Claude,
+                    exit_code: None,
+                    stderr: "rate limited".to_string(),
+                }),
+            ),
+            stub(AgentName::Codex, Ok("feat: x")),
+        ]);
+
+        let result = chain.execute("prompt").await;
+        assert!(matches!(result, Err(AgentError::ExecutionFailed { .. })));
+        assert_eq!(chain.name(), AgentName::Claude);
+    }
+
+    #[tokio::test]
+    async fn returns_the_last_error_when_every_agent_fails() {
+        let chain = AgentChain::new(vec![
+            stub(
+                AgentName::Claude,
+                Err(AgentError::NotFound {
+                    agent: AgentName::Claude,
+                }),
+            ),
+            stub(
+                AgentName::Codex,
+                Err(AgentError::NotFound {
+                    agent: AgentName::Codex,
+                }),
+            ),
+        ]);
+
+        match chain.execute("prompt").await {
+            Err(AgentError::NotFound { agent }) => assert_eq!(agent, AgentName::Codex),
+            other => panic!("expected NotFound, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn fall_back_moves_on_until_the_last_agent() {
+        let chain = AgentChain::new(vec![
+            stub(AgentName::Claude, Ok("bad")),
+            stub(AgentName::Codex, Ok("feat: x")),
+        ]);
+
+        assert!(chain.fall_back());
+        assert_eq!(chain.execute("prompt").await.unwrap(), "feat: x");
+        assert_eq!(chain.name(), AgentName::Codex);
+        assert!(!chain.fall_back());
+        assert_eq!(chain.name(), AgentName::Codex);
+    }
+}
@@ -1,28 +1,54 @@
-use super::{AGENT_TIMEOUT, check_command_exists, run_command_with_stdin};
+use super::{AgentSettings, OutputSink, PromptMode, check_command_exists, run_with_prompt};
 use crate::error::AgentError;
 use crate::types::AgentName;
 
 /// Claude AI agent implementation
 #[derive(Default)]
-pub struct ClaudeAgent;
+pub struct ClaudeAgent {
+    /// Model, extra arguments, environment, working directory and timeout
+    pub settings: AgentSettings,
+    /// How the prompt is handed to the CLI (stdin or argv)
+    pub prompt: PromptMode,
+}
 
 impl ClaudeAgent {
     /// Execute Claude CLI with the given prompt
     ///
     /// Uses `claude --print` to output without interactive confirmation.
-    /// The prompt is passed via stdin.
+    /// The prompt is passed via stdin unless configured as an argument.
     pub async fn execute(&self, prompt: &str) -> Result<String, AgentError> {
+        self.execute_streaming(prompt, &mut |_| {}).await
+    }
+
+    /// Like [`execute`](Self::execute), passing stdout to `on_output` line by line
+    pub async fn execute_streaming(
+        &self,
+        prompt: &str,
+        on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
         let agent = AgentName::Claude;
-        check_command_exists(agent.command_name(), agent).await?;
+        check_command_exists(agent.command_name(), agent.clone()).await?;
 
-        tokio::time::timeout(
-            AGENT_TIMEOUT,
-            run_command_with_stdin(agent.command_name(), &["--print"], prompt, agent),
+        run_with_prompt(
+            agent.command_name(),
+            &self.args(),
+            self.prompt,
+            prompt,
+            &agent,
+            &self.settings,
+            on_output,
         )
         .await
-        .map_err(|_| AgentError::Timeout {
-            agent,
-            timeout_secs: AGENT_TIMEOUT.as_secs(),
-        })?
+    }
+
+    /// Argument template: the model, extra arguments, then the prompt
+    fn args(&self) -> Vec<String> {
+        let mut args = vec!["--print".to_string()];
+        if let Some(model) = &self.settings.model {
+            args.push("--model".to_string());
+            args.push(model.clone());
+        }
+        args.extend(self.settings.args.iter().cloned());
+        args
     }
 }
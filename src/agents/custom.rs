@@ -0,0 +1,271 @@
+use super::{
+    AgentSettings, OutputSink, PromptMode, check_command_exists, extract_json_field,
+    run_with_prompt,
+};
+use crate::error::AgentError;
+use crate::types::AgentName;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Placeholder replaced with the configured model (empty if none)
+const MODEL_PLACEHOLDER: &str = "{model}";
+
+/// How the agent's stdout is turned into the raw response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    /// Use stdout as-is
+    #[default]
+    Text,
+    /// Parse stdout as JSON and read the string at `output_field`
+    Json,
+}
+
+/// Command line for a custom agent, from an `[agents.<name>]` config section
+///
+/// ```toml
+/// [agents.ollama]
+/// command = "ollama"
+/// args = ["run", "llama3"]
+/// prompt = "stdin"
+/// output = "text"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomAgentConfig {
+    /// Executable to run (defaults to the agent name)
+    pub command: Option<String>,
+    /// Argument template; may contain `{prompt}`, `{prompt_file}` or `{model}`
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Prompt delivery mode
+    #[serde(default)]
+    pub prompt: PromptMode,
+    /// Output parsing mode
+    #[serde(default)]
+    pub output: OutputMode,
+    /// Dot-separated path to the message in JSON output (default: `response`)
+    pub output_field: Option<String>,
+    /// Model substituted for `{model}` and shown in the signature
+    pub model: Option<String>,
+    /// Extra environment variables
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Directory the command runs in
+    pub cwd: Option<PathBuf>,
+    /// Timeout for this agent (overrides `timeout_secs`)
+    pub timeout_secs: Option<u64>,
+}
+
+impl CustomAgentConfig {
+    /// Whether the argument template passes on a model (`{model}`)
+    pub fn uses_model(&self) -> bool {
+        self.args.iter().any(|arg| arg.contains(MODEL_PLACEHOLDER))
+    }
+
+    /// One-line summary for `config show`
+    pub fn summary(&self, name: &str) -> String {
+        let mut command = vec![self.command.as_deref().unwrap_or(name).to_string()];
+        command.extend(self.args.iter().cloned());
+        let prompt = self.prompt.as_str();
+        let output = match self.output {
+            OutputMode::Text => "text",
+            OutputMode::Json => "json",
+        };
+        format!(
+            "{} (prompt: {}, output: {})",
+            command.join(" "),
+            prompt,
+            output
+        )
+    }
+}
+
+/// Custom agent that runs any local LLM CLI
+pub struct CustomAgent {
+    /// Agent name (the `--agent` value and config section key)
+    pub name: String,
+    /// Command line configuration
+    pub config: CustomAgentConfig,
+    /// Model, extra arguments, environment, working directory and timeout
+    pub settings: AgentSettings,
+}
+
+impl CustomAgent {
+    /// Create a custom agent with default settings
+    ///
+    /// Without a config section the agent runs the command of the same name
+    /// with the prompt on stdin and uses stdout as the response.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            config: CustomAgentConfig::default(),
+            settings: AgentSettings::default(),
+        }
+    }
+
+    /// Execute the configured command with the given prompt
+    pub async fn execute(&self, prompt: &str) -> Result<String, AgentError> {
+        self.execute_streaming(prompt, &mut |_| {}).await
+    }
+
+    /// Like [`execute`](Self::execute), passing stdout to `on_output` line by line
+    ///
+    /// JSON output is only usable once complete, so it isn't streamed.
+    pub async fn execute_streaming(
+        &self,
+        prompt: &str,
+        on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
+        let agent = AgentName::Custom(self.name.clone());
+        let command = self.config.command.as_deref().unwrap_or(&self.name);
+        check_command_exists(command, agent.clone()).await?;
+
+        match self.config.output {
+            OutputMode::Text => self.run_command(command, prompt, &agent, on_output).await,
+            OutputMode::Json => {
+                let stdout = self
+                    .run_command(command, prompt, &agent, &mut |_| {})
+                    .await?;
+                let field = self.config.output_field.as_deref().unwrap_or("response");
+                extract_json_field(&stdout, field)
+            }
+        }
+    }
+
+    /// Run the command, delivering the prompt according to `PromptMode`
+    ///
+    /// Extra arguments follow the configured ones.
+    async fn run_command(
+        &self,
+        command: &str,
+        prompt: &str,
+        agent: &AgentName,
+        on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
+        let model = self.settings.model.as_deref().unwrap_or_default();
+        let template: Vec<String> = self
+            .config
+            .args
+            .iter()
+            .map(|arg| arg.replace(MODEL_PLACEHOLDER, model))
+            .chain(self.settings.args.iter().cloned())
+            .collect();
+        run_with_prompt(
+            command,
+            &template,
+            self.config.prompt,
+            prompt,
+            agent,
+            &self.settings,
+            on_output,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent_with(config: CustomAgentConfig) -> CustomAgent {
+        CustomAgent {
+            name: "fake".to_string(),
+            config,
+            settings: AgentSettings::default(),
+        }
+    }
+
+    #[test]
+    fn parses_config_section() {
+        let config: CustomAgentConfig = toml::from_str(
+            r#"
+command = "llm"
+args = ["-m", "gpt-4o-mini", "{prompt}"]
+prompt = "argv"
+output = "json"
+output_field = "text"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.command.as_deref(), Some("llm"));
+        assert_eq!(config.prompt, PromptMode::Argv);
+        assert_eq!(config.output, OutputMode::Json);
+        assert_eq!(config.output_field.as_deref(), Some("text"));
+    }
+
+    #[test]
+    fn summary_includes_command_and_modes() {
+        let config = CustomAgentConfig {
+            args: vec!["run".to_string(), "llama3".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.summary("ollama"),
+            "ollama run llama3 (prompt: stdin, output: text)"
+        );
+    }
+
+    #[tokio::test]
+    async fn stdin_mode_pipes_prompt() {
+        let agent = agent_with(CustomAgentConfig {
+            command: Some("cat".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            agent.execute("feat: via stdin").await.unwrap(),
+            "feat: via stdin"
+        );
+    }
+
+    #[tokio::test]
+    async fn argv_mode_passes_prompt_argument() {
+        let agent = agent_with(CustomAgentConfig {
+            command: Some("echo".to_string()),
+            args: vec!["-n".to_string(), "{prompt}".to_string()],
+            prompt: PromptMode::Argv,
+            ..Default::default()
+        });
+        assert_eq!(
+            agent.execute("feat: via argv").await.unwrap(),
+            "feat: via argv"
+        );
+    }
+
+    #[tokio::test]
+    async fn file_mode_passes_prompt_file() {
+        let agent = agent_with(CustomAgentConfig {
+            command: Some("cat".to_string()),
+            prompt: PromptMode::File,
+            ..Default::default()
+        });
+        assert_eq!(
+            agent.execute("feat: via file").await.unwrap(),
+            "feat: via file"
+        );
+    }
+
+    #[tokio::test]
+    async fn json_output_mode_extracts_field() {
+        let agent = agent_with(CustomAgentConfig {
+            command: Some("cat".to_string()),
+            output: OutputMode::Json,
+            ..Default::default()
+        });
+        let result = agent.execute(r#"{"response": "fix: parse json"}"#).await;
+        assert_eq!(result.unwrap(), "fix: parse json");
+    }
+
+    #[tokio::test]
+    async fn missing_command_reports_not_found() {
+        let agent = CustomAgent::new("this-command-definitely-does-not-exist-99999");
+        match agent.execute("prompt").await {
+            Err(AgentError::NotFound { agent }) => assert_eq!(
+                agent,
+                AgentName::Custom("this-command-definitely-does-not-exist-99999".to_string())
+            ),
+            other => panic!("expected NotFound error, got {:?}", other),
+        }
+    }
+}
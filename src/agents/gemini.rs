@@ -1,48 +1,66 @@
-use super::{AGENT_TIMEOUT, check_command_exists};
+use super::{
+    AgentSettings, OutputSink, PROMPT_FILE_PLACEHOLDER, PromptMode, check_command_exists,
+    run_with_prompt,
+};
 use crate::error::AgentError;
 use crate::types::AgentName;
-use tokio::process::Command;
 
 /// Gemini AI agent implementation
 #[derive(Default)]
-pub struct GeminiAgent;
+pub struct GeminiAgent {
+    /// Model, extra arguments, environment, working directory and timeout
+    pub settings: AgentSettings,
+    /// How the prompt is handed to the CLI
+    pub prompt: PromptMode,
+}
 
 impl GeminiAgent {
     /// Execute Gemini CLI with the given prompt
     ///
-    /// Uses `gemini -p "<prompt>"` to pass the prompt as a command-line argument.
-    /// Unlike Claude and Codex, Gemini takes the prompt as an argument, not stdin.
+    /// Gemini answers non-interactively when its stdin isn't a terminal, so
+    /// by default the prompt is piped in. `argv` passes `-p "<prompt>"`, and
+    /// `file` passes `-p @<name>` of a file in the directory Gemini runs in.
     pub async fn execute(&self, prompt: &str) -> Result<String, AgentError> {
+        self.execute_streaming(prompt, &mut |_| {}).await
+    }
+
+    /// Like [`execute`](Self::execute), passing stdout to `on_output` line by line
+    pub async fn execute_streaming(
+        &self,
+        prompt: &str,
+        on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
         let agent = AgentName::Gemini;
-        check_command_exists(agent.command_name(), agent).await?;
+        check_command_exists(agent.command_name(), agent.clone()).await?;
 
-        tokio::time::timeout(AGENT_TIMEOUT, Self::run_command(agent, prompt))
-            .await
-            .map_err(|_| AgentError::Timeout {
-                agent,
-                timeout_secs: AGENT_TIMEOUT.as_secs(),
-            })?
+        run_with_prompt(
+            agent.command_name(),
+            &self.args(),
+            self.prompt,
+            prompt,
+            &agent,
+            &self.settings,
+            on_output,
+        )
+        .await
     }
 
-    /// Run gemini command with prompt as command-line argument
-    async fn run_command(agent: AgentName, prompt: &str) -> Result<String, AgentError> {
-        let output = Command::new(agent.command_name())
-            .arg("-p")
-            .arg(prompt)
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .output()
-            .await
-            .map_err(|e| AgentError::ExecutionFailed {
-                agent,
-                stderr: e.to_string(),
-            })?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            return Err(AgentError::ExecutionFailed { agent, stderr });
+    /// Argument template: the model, extra arguments, then the prompt
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(model) = &self.settings.model {
+            args.push("--model".to_string());
+            args.push(model.clone());
         }
-
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        args.extend(self.settings.args.iter().cloned());
+        match self.prompt {
+            PromptMode::Stdin => {}
+            PromptMode::Argv => args.push("-p".to_string()),
+            PromptMode::File => {
+                args.push("-p".to_string());
+                args.push(format!("@{}", PROMPT_FILE_PLACEHOLDER));
+            }
+        }
+        args
     }
 }
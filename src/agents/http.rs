@@ -0,0 +1,445 @@
+use super::{AGENT_TIMEOUT, MAX_STDOUT_BYTES, OutputSink, extract_json_field};
+use crate::error::AgentError;
+use crate::types::AgentName;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// Wire protocol spoken by an HTTP agent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpApi {
+    /// OpenAI-compatible `POST {base_url}/chat/completions`
+    /// (llama.cpp server, vLLM, LM Studio, OpenAI itself)
+    #[default]
+    Openai,
+    /// Ollama's native `POST {base_url}/api/generate`
+    Ollama,
+}
+
+impl HttpApi {
+    /// Base URL used when the config section doesn't set one
+    pub fn default_base_url(self) -> &'static str {
+        match self {
+            Self::Openai => "http://localhost:8080/v1",
+            Self::Ollama => "http://localhost:11434",
+        }
+    }
+
+    fn endpoint(self) -> &'static str {
+        match self {
+            Self::Openai => "/chat/completions",
+            Self::Ollama => "/api/generate",
+        }
+    }
+
+    /// Path to the message text in the response body
+    fn response_field(self) -> &'static str {
+        match self {
+            Self::Openai => "choices.0.message.content",
+            Self::Ollama => "response",
+        }
+    }
+
+    /// Message text in one line of a streamed response
+    ///
+    /// OpenAI-compatible servers send server-sent events (`data: {...}`),
+    /// Ollama sends one JSON object per line. `None` if the line isn't an
+    /// event; events without text (role announcements) give `Some("")`.
+    fn stream_text(self, line: &str) -> Option<String> {
+        match self {
+            Self::Openai => {
+                let data = line.strip_prefix("data:")?.trim();
+                if data == "[DONE]" {
+                    return Some(String::new());
+                }
+                let event: serde_json::Value = serde_json::from_str(data).ok()?;
+                let text = event.pointer("/choices/0/delta/content");
+                Some(
+                    text.and_then(|t| t.as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                )
+            }
+            Self::Ollama => {
+                let event: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+                Some(event.get("response")?.as_str()?.to_string())
+            }
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Openai => "openai",
+            Self::Ollama => "ollama",
+        }
+    }
+}
+
+/// HTTP endpoint for an agent, from an `[agents.<name>]` config section
+///
+/// Sections with an `api` key are HTTP agents; all others are commands.
+///
+/// ```toml
+/// [agents.local]
+/// api = "openai"
+/// base_url = "http://localhost:8080/v1"
+/// model = "qwen2.5-coder"
+/// temperature = 0.2
+/// max_tokens = 512
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpAgentConfig {
+    /// Wire protocol
+    pub api: HttpApi,
+    /// Server base URL (defaults per protocol)
+    pub base_url: Option<String>,
+    /// Model name sent with each request
+    pub model: Option<String>,
+    /// Sampling temperature
+    pub temperature: Option<f32>,
+    /// Maximum tokens to generate
+    pub max_tokens: Option<u32>,
+    /// Environment variable holding a bearer token, if the server needs one
+    pub api_key_env: Option<String>,
+    /// Timeout for this agent (overrides `timeout_secs`)
+    pub timeout_secs: Option<u64>,
+}
+
+impl HttpAgentConfig {
+    /// Base URL without a trailing slash
+    pub fn base_url(&self) -> &str {
+        self.base_url
+            .as_deref()
+            .unwrap_or(self.api.default_base_url())
+            .trim_end_matches('/')
+    }
+
+    /// Full request URL
+    pub fn url(&self) -> String {
+        format!("{}{}", self.base_url(), self.api.endpoint())
+    }
+
+    /// One-line summary for `config show`
+    pub fn summary(&self) -> String {
+        format!(
+            "{} {} (model: {})",
+            self.api.as_str(),
+            self.url(),
+            self.model.as_deref().unwrap_or("server default")
+        )
+    }
+
+    /// JSON request body for the configured protocol
+    fn request_body(&self, prompt: &str, stream: bool) -> serde_json::Value {
+        match self.api {
+            HttpApi::Openai => {
+                let mut body = json!({
+                    "messages": [{ "role": "user", "content": prompt }],
+                    "stream": stream,
+                });
+                if let Some(model) = &self.model {
+                    body["model"] = json!(model);
+                }
+                if let Some(temperature) = self.temperature {
+                    body["temperature"] = json!(temperature);
+                }
+                if let Some(max_tokens) = self.max_tokens {
+                    body["max_tokens"] = json!(max_tokens);
+                }
+                body
+            }
+            HttpApi::Ollama => {
+                let mut options = serde_json::Map::new();
+                if let Some(temperature) = self.temperature {
+                    options.insert("temperature".to_string(), json!(temperature));
+                }
+                if let Some(max_tokens) = self.max_tokens {
+                    options.insert("num_predict".to_string(), json!(max_tokens));
+                }
+                json!({
+                    "model": self.model.as_deref().unwrap_or_default(),
+                    "prompt": prompt,
+                    "stream": stream,
+                    "options": options,
+                })
+            }
+        }
+    }
+}
+
+/// HTTP agent that talks to an OpenAI-compatible or Ollama server
+pub struct HttpAgent {
+    /// Agent name (the `--agent` value and config section key)
+    pub name: String,
+    /// Endpoint configuration
+    pub config: HttpAgentConfig,
+    /// Maximum time to wait for the server to respond
+    pub timeout: Duration,
+}
+
+impl HttpAgent {
+    /// Create an HTTP agent with the default timeout
+    pub fn new(name: impl Into<String>, config: HttpAgentConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            timeout: AGENT_TIMEOUT,
+        }
+    }
+
+    /// Send the prompt to the configured endpoint
+    pub async fn execute(&self, prompt: &str) -> Result<String, AgentError> {
+        self.with_timeout(self.send(prompt, None)).await
+    }
+
+    /// Like [`execute`](Self::execute), but asks the server to stream its
+    /// answer and passes the text so far to `on_output` as it arrives
+    ///
+    /// A server that ignores the request and answers in one piece still works.
+    pub async fn execute_streaming(
+        &self,
+        prompt: &str,
+        on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
+        self.with_timeout(self.send(prompt, Some(on_output))).await
+    }
+
+    fn agent_name(&self) -> AgentName {
+        AgentName::Custom(self.name.clone())
+    }
+
+    async fn with_timeout(
+        &self,
+        request: impl Future<Output = Result<String, AgentError>>,
+    ) -> Result<String, AgentError> {
+        tokio::time::timeout(self.timeout, request)
+            .await
+            .map_err(|_| AgentError::Timeout {
+                agent: self.agent_name(),
+                timeout_secs: self.timeout.as_secs(),
+            })?
+    }
+
+    async fn send(
+        &self,
+        prompt: &str,
+        on_output: Option<OutputSink<'_>>,
+    ) -> Result<String, AgentError> {
+        let agent = self.agent_name();
+        let failed = |stderr: String| AgentError::ExecutionFailed {
+            agent: agent.clone(),
+            exit_code: None,
+            stderr,
+        };
+
+        let url = self.config.url();
+        let mut request = reqwest::Client::new()
+            .post(&url)
+            .json(&self.config.request_body(prompt, on_output.is_some()));
+
+        if let Some(var) = &self.config.api_key_env {
+            let key = std::env::var(var)
+                .map_err(|_| failed(format!("environment variable {} is not set", var)))?;
+            request = request.bearer_auth(key);
+        }
+
+        let mut response = request
+            .send()
+            .await
+            .map_err(|e| failed(format!("request to {} failed: {}", url, e)))?;
+        let read_failed =
+            |e: reqwest::Error| failed(format!("failed to read response from {}: {}", url, e));
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.map_err(read_failed)?;
+            return Err(failed(format!(
+                "{} returned HTTP {}: {}",
+                url,
+                status,
+                body.trim()
+            )));
+        }
+
+        let Some(on_output) = on_output else {
+            let body = response.text().await.map_err(read_failed)?;
+            return extract_json_field(&body, self.config.api.response_field());
+        };
+
+        let mut body = Vec::new();
+        let mut text = String::new();
+        let mut streamed = false;
+        let mut line_start = 0;
+        loop {
+            let chunk = response.chunk().await.map_err(read_failed)?;
+            if let Some(chunk) = &chunk {
+                body.extend_from_slice(chunk);
+                if body.len() > MAX_STDOUT_BYTES {
+                    return Err(AgentError::InvalidResponse {
+                        reason: format!("agent output exceeds {} bytes", MAX_STDOUT_BYTES),
+                    });
+                }
+            }
+
+            // Whole lines only, plus whatever is left once the body ends
+            let line_end = match chunk {
+                Some(_) => body.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1),
+                None => body.len(),
+            };
+            if line_end > line_start {
+                let lines = String::from_utf8_lossy(&body[line_start..line_end]);
+                let events: Vec<String> = lines
+                    .lines()
+                    .filter_map(|line| self.config.api.stream_text(line))
+                    .collect();
+                if !events.is_empty() {
+                    streamed = true;
+                    text.extend(events);
+                    on_output(&text);
+                }
+                line_start = line_end;
+            }
+            if chunk.is_none() {
+                break;
+            }
+        }
+
+        if streamed {
+            Ok(text)
+        } else {
+            // Answered in one piece after all
+            extract_json_field(
+                &String::from_utf8_lossy(&body),
+                self.config.api.response_field(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_config_section() {
+        let config: HttpAgentConfig = toml::from_str(
+            r#"
+api = "ollama"
+model = "llama3"
+temperature = 0.2
+max_tokens = 256
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.api, HttpApi::Ollama);
+        assert_eq!(config.model.as_deref(), Some("llama3"));
+        assert_eq!(config.max_tokens, Some(256));
+        assert_eq!(config.url(), "http://localhost:11434/api/generate");
+    }
+
+    #[test]
+    fn url_joins_base_url_without_double_slash() {
+        let config = HttpAgentConfig {
+            base_url: Some("http://example.test/v1/".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.url(), "http://example.test/v1/chat/completions");
+    }
+
+    #[test]
+    fn openai_body_includes_sampling_options() {
+        let config = HttpAgentConfig {
+            model: Some("gpt-4o-mini".to_string()),
+            temperature: Some(0.5),
+            max_tokens: Some(100),
+            ..Default::default()
+        };
+        let body = config.request_body("diff", false);
+        assert_eq!(body["model"], "gpt-4o-mini");
+        assert_eq!(body["messages"][0]["content"], "diff");
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["max_tokens"], 100);
+    }
+
+    #[test]
+    fn openai_body_omits_unset_options() {
+        let body = HttpAgentConfig::default().request_body("diff", false);
+        assert!(body.get("model").is_none());
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("max_tokens").is_none());
+    }
+
+    #[test]
+    fn ollama_body_maps_max_tokens_to_num_predict() {
+        let config = HttpAgentConfig {
+            api: HttpApi::Ollama,
+            model: Some("llama3".to_string()),
+            max_tokens: Some(64),
+            ..Default::default()
+        };
+        let body = config.request_body("diff", false);
+        assert_eq!(body["prompt"], "diff");
+        assert_eq!(body["options"]["num_predict"], 64);
+    }
+
+    #[test]
+    fn stream_text_reads_sse_and_ndjson_events() {
+        let openai = HttpApi::Openai;
+        assert_eq!(
+            openai
+                .stream_text(r#"data: {"choices":[{"delta":{"content":"feat"}}]}"#)
+                .as_deref(),
+            Some("feat")
+        );
+        assert_eq!(
+            openai
+                .stream_text(r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#)
+                .as_deref(),
+            Some("")
+        );
+        assert_eq!(openai.stream_text("data: [DONE]").as_deref(), Some(""));
+        assert_eq!(openai.stream_text(r#"{"choices":[]}"#), None);
+
+        let ollama = HttpApi::Ollama;
+        assert_eq!(
+            ollama
+                .stream_text(r#"{"response":"fix: ","done":false}"#)
+                .as_deref(),
+            Some("fix: ")
+        );
+        assert_eq!(ollama.stream_text("  \"response\": \"x\","), None);
+    }
+
+    #[test]
+    fn summary_includes_api_url_and_model() {
+        let config = HttpAgentConfig {
+            model: Some("qwen".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.summary(),
+            "openai http://localhost:8080/v1/chat/completions (model: qwen)"
+        );
+    }
+
+    #[tokio::test]
+    async fn connection_failure_reports_execution_failed() {
+        // Port 9 (discard) is essentially never listening on localhost
+        let agent = HttpAgent::new(
+            "local",
+            HttpAgentConfig {
+                base_url: Some("http://127.0.0.1:9".to_string()),
+                ..Default::default()
+            },
+        );
+        match agent.execute("prompt").await {
+            Err(AgentError::ExecutionFailed { agent, stderr, .. }) => {
+                assert_eq!(agent, AgentName::Custom("local".to_string()));
+                assert!(stderr.contains("127.0.0.1:9"));
+            }
+            other => panic!("expected ExecutionFailed error, got {:?}", other),
+        }
+    }
+}
@@ -1,13 +1,27 @@
+pub mod chain;
 pub mod claude;
 pub mod codex;
+pub mod custom;
 pub mod gemini;
+pub mod http;
+pub mod parallel;
 
+use crate::config::Config;
 use crate::error::AgentError;
+use crate::prompt::CANDIDATE_SEPARATOR;
 use crate::types::AgentName;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::str::FromStr;
+use std::sync::Mutex;
 use std::time::Duration;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio::process::Command;
 
 // Regex patterns for response cleaning (compiled once with Lazy)
@@ -30,9 +44,81 @@ static THINKING_TAGS: Lazy<Regex> =
 static MULTIPLE_NEWLINES: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"\n{3,}").expect("valid regex pattern"));
 
+/// Markers the prompt asks agents to put around the message
+const MESSAGE_START: &str = "<<<COMMIT_MESSAGE_START>>>";
+const MESSAGE_END: &str = "<<<COMMIT_MESSAGE_END>>>";
+
+/// Receives an agent's response so far each time more of it arrives
+///
+/// Always called with the whole response up to now, so a caller rendering it
+/// can simply redraw.
+pub type OutputSink<'a> = &'a mut (dyn FnMut(&str) + Send);
+
+/// Placeholder replaced with the prompt text in `argv` mode
+pub(crate) const PROMPT_PLACEHOLDER: &str = "{prompt}";
+
+/// Placeholder replaced with the prompt file path in `file` mode
+pub(crate) const PROMPT_FILE_PLACEHOLDER: &str = "{prompt_file}";
+
+/// How the prompt is handed to an agent's process
+///
+/// Stdin is the default: arguments are limited in size (`ARG_MAX`) and
+/// visible to other users in `ps`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptMode {
+    /// Write the prompt to the process's stdin
+    #[default]
+    Stdin,
+    /// Pass the prompt as an argument (`{prompt}` placeholder, or appended)
+    Argv,
+    /// Write the prompt to a temp file and pass its path
+    /// (`{prompt_file}` placeholder, or appended)
+    File,
+}
+
+impl PromptMode {
+    /// Config file spelling
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stdin => "stdin",
+            Self::Argv => "argv",
+            Self::File => "file",
+        }
+    }
+}
+
 /// Shared timeout for all agents (120 seconds)
 pub(crate) const AGENT_TIMEOUT: Duration = Duration::from_secs(120);
 
+/// How a command-line agent is run, from its `[agents.<name>]` section and
+/// the `model` and `agent_args` settings (see [`Config::agent_settings`])
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentSettings {
+    /// Model to ask for (`None`: the CLI's default)
+    pub model: Option<String>,
+    /// Extra arguments for the CLI
+    pub args: Vec<String>,
+    /// Extra environment variables for the process
+    pub env: BTreeMap<String, String>,
+    /// Directory the process runs in (`None`: the current directory)
+    pub cwd: Option<PathBuf>,
+    /// Maximum time to wait for the process to exit
+    pub timeout: Duration,
+}
+
+impl Default for AgentSettings {
+    fn default() -> Self {
+        Self {
+            model: None,
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            cwd: None,
+            timeout: AGENT_TIMEOUT,
+        }
+    }
+}
+
 /// Check if a CLI command exists in PATH
 ///
 /// Uses `which` to check for command availability.
@@ -49,70 +135,373 @@ pub(crate) async fn check_command_exists(
     }
 }
 
-/// Run a command with prompt via stdin
+/// Longest stdout accepted from an agent process (1 MiB)
+pub(crate) const MAX_STDOUT_BYTES: usize = 1024 * 1024;
+
+/// Stderr beyond this is dropped from error messages (16 KiB)
+const MAX_STDERR_BYTES: usize = 16 * 1024;
+
+/// Process groups of agent processes that are still running
+static RUNNING_PROCESSES: Lazy<Mutex<HashSet<u32>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Kill every running agent process and its children
+///
+/// Called on Ctrl-C before exiting, so agents don't outlive the hook.
+pub fn kill_running_agents() {
+    let running = RUNNING_PROCESSES
+        .lock()
+        .map(|running| running.clone())
+        .unwrap_or_default();
+    for pid in running {
+        kill_process_group(pid);
+    }
+}
+
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    if let Ok(pid) = libc::pid_t::try_from(pid) {
+        // SAFETY: kill(2) has no memory safety requirements; a negative pid
+        // addresses the process group the child leads
+        unsafe {
+            libc::kill(-pid, libc::SIGKILL);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_pid: u32) {
+    // No process groups: `kill_on_drop` still stops the direct child
+}
+
+/// Kills the agent's process group unless the process finished on its own
+///
+/// Dropped on timeout, when a parallel agent loses, or when generation is
+/// abandoned, so agents (and anything they started) never keep running.
+struct ProcessGuard {
+    pid: Option<u32>,
+}
+
+impl ProcessGuard {
+    fn new(pid: Option<u32>) -> Self {
+        if let (Some(pid), Ok(mut running)) = (pid, RUNNING_PROCESSES.lock()) {
+            running.insert(pid);
+        }
+        Self { pid }
+    }
+
+    /// The process exited: nothing to kill
+    fn finished(mut self) {
+        self.untrack();
+    }
+
+    fn untrack(&mut self) {
+        if let (Some(pid), Ok(mut running)) = (self.pid.take(), RUNNING_PROCESSES.lock()) {
+            running.remove(&pid);
+        }
+    }
+}
+
+impl Drop for ProcessGuard {
+    fn drop(&mut self) {
+        if let Some(pid) = self.pid {
+            kill_process_group(pid);
+        }
+        self.untrack();
+    }
+}
+
+/// Read a pipe to the end, keeping at most `limit` bytes
 ///
-/// Spawns a process with the given command and arguments, writes the prompt
-/// to stdin, and captures stdout. Used by Claude and Codex agents.
+/// Reading continues past the limit so the child never blocks on a full
+/// pipe. Returns the kept bytes and whether anything was dropped.
+async fn read_limited(
+    pipe: Option<impl AsyncRead + Unpin>,
+    limit: usize,
+) -> std::io::Result<(Vec<u8>, bool)> {
+    let mut kept = Vec::new();
+    let mut truncated = false;
+    let Some(mut pipe) = pipe else {
+        return Ok((kept, truncated));
+    };
+    let mut chunk = [0u8; 8192];
+    loop {
+        let read = pipe.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok((kept, truncated));
+        }
+        let room = limit.saturating_sub(kept.len());
+        kept.extend_from_slice(&chunk[..read.min(room)]);
+        truncated |= read > room;
+    }
+}
+
+/// Read an agent's stdout line by line, passing the text so far to `on_output`
+///
+/// Like [`read_limited`], keeps at most `limit` bytes and keeps draining
+/// after that; `on_output` isn't called once output is truncated.
+async fn read_lines(
+    pipe: Option<impl AsyncRead + Unpin>,
+    limit: usize,
+    on_output: OutputSink<'_>,
+) -> std::io::Result<(String, bool)> {
+    let mut text = String::new();
+    let mut partial_line = Vec::new();
+    let mut truncated = false;
+    let Some(mut pipe) = pipe else {
+        return Ok((text, truncated));
+    };
+    let mut chunk = [0u8; 8192];
+    loop {
+        let read = pipe.read(&mut chunk).await?;
+        let room = limit.saturating_sub(text.len() + partial_line.len());
+        partial_line.extend_from_slice(&chunk[..read.min(room)]);
+        truncated |= read > room;
+
+        // Whole lines only, so multi-byte characters are never split
+        let end = if read == 0 {
+            partial_line.len()
+        } else {
+            partial_line
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .map_or(0, |i| i + 1)
+        };
+        if end > 0 {
+            text.push_str(&String::from_utf8_lossy(&partial_line[..end]));
+            partial_line.drain(..end);
+            if !truncated {
+                on_output(&text);
+            }
+        }
+        if read == 0 {
+            return Ok((text, truncated));
+        }
+    }
+}
+
+/// Run an agent process, writing `input` to its stdin
+///
+/// Shared by all command-line agents. Stdin is written while stdout and
+/// stderr are read, so a child that answers before reading all of its input
+/// can't deadlock on a full pipe. The process runs in its own process group,
+/// which is killed on timeout or when the returned future is dropped
+/// (see [`kill_running_agents`] for Ctrl-C). Stdout is passed to
+/// `on_output` as each line arrives.
 ///
 /// # Arguments
 /// * `command` - The command to execute (e.g., "claude", "codex")
 /// * `args` - Command-line arguments for the command
-/// * `prompt` - The prompt text to write to stdin
+/// * `input` - Text written to stdin (empty: stdin is just closed)
 /// * `agent` - The agent name for error reporting
+/// * `settings` - Environment, working directory and timeout (the model and
+///   extra arguments are already part of `args`)
+/// * `on_output` - Called with stdout so far, line by line
 ///
-/// # Returns
-/// The stdout output from the command if successful
-pub(crate) async fn run_command_with_stdin(
+/// # Errors
+/// - `ExecutionFailed` if the process can't be started or exits with an
+///   error (with its exit code and the first 16 KiB of stderr)
+/// - `Timeout` if it doesn't exit within the timeout
+/// - `InvalidResponse` if stdout exceeds [`MAX_STDOUT_BYTES`]
+pub(crate) async fn run_process(
     command: &str,
     args: &[&str],
-    prompt: &str,
+    input: &str,
     agent: AgentName,
+    settings: &AgentSettings,
+    on_output: OutputSink<'_>,
 ) -> Result<String, AgentError> {
-    let mut child = Command::new(command)
+    let timeout = settings.timeout;
+    let failed = |stderr: String| AgentError::ExecutionFailed {
+        agent: agent.clone(),
+        exit_code: None,
+        stderr,
+    };
+
+    let mut command = Command::new(command);
+    if let Some(cwd) = &settings.cwd {
+        command.current_dir(cwd);
+    }
+    command
         .args(args)
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
-        .map_err(|e| AgentError::ExecutionFailed {
-            agent,
-            stderr: e.to_string(),
-        })?;
+        .envs(&settings.env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+    let mut child = command.spawn().map_err(|e| failed(e.to_string()))?;
+    let guard = ProcessGuard::new(child.id());
 
-    // Write prompt to stdin
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin
-            .write_all(prompt.as_bytes())
-            .await
-            .map_err(|e| AgentError::ExecutionFailed {
-                agent,
-                stderr: format!("failed to write to stdin: {}", e),
-            })?;
-        stdin
-            .flush()
+    let stdin = child.stdin.take();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let write_input = async move {
+        if let Some(mut pipe) = stdin {
+            match pipe.write_all(input.as_bytes()).await {
+                // The child stopped reading; its exit status tells the story
+                Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e),
+                _ => {}
+            }
+            // Dropping the pipe closes stdin
+        }
+        Ok(())
+    };
+    let run = async {
+        tokio::join!(
+            write_input,
+            read_lines(stdout, MAX_STDOUT_BYTES, on_output),
+            read_limited(stderr, MAX_STDERR_BYTES),
+            child.wait(),
+        )
+    };
+
+    let (written, stdout, stderr, status) =
+        tokio::time::timeout(timeout, run)
             .await
-            .map_err(|e| AgentError::ExecutionFailed {
-                agent,
-                stderr: format!("failed to flush stdin: {}", e),
+            .map_err(|_| AgentError::Timeout {
+                agent: agent.clone(),
+                timeout_secs: timeout.as_secs(),
             })?;
-    }
+    let status = status.map_err(|e| failed(e.to_string()))?;
+    guard.finished();
+
+    written.map_err(|e| failed(format!("failed to write to stdin: {}", e)))?;
+    let (stdout, stdout_truncated) = stdout.map_err(|e| failed(e.to_string()))?;
+    let (stderr, _) = stderr.map_err(|e| failed(e.to_string()))?;
 
-    // Wait for process to complete
-    let output = child
-        .wait_with_output()
-        .await
-        .map_err(|e| AgentError::ExecutionFailed {
+    if !status.success() {
+        return Err(AgentError::ExecutionFailed {
             agent,
-            stderr: e.to_string(),
+            exit_code: status.code(),
+            stderr: String::from_utf8_lossy(&stderr).trim_end().to_string(),
+        });
+    }
+    if stdout_truncated {
+        return Err(AgentError::InvalidResponse {
+            reason: format!("agent output exceeds {} bytes", MAX_STDOUT_BYTES),
+        });
+    }
+
+    Ok(stdout)
+}
+
+/// Run `command`, delivering the prompt according to `mode`
+///
+/// `template` may contain `{prompt}` (argv mode) or `{prompt_file}` (file
+/// mode); without one the prompt or file path is appended. Shared by the
+/// built-in and custom command agents.
+pub(crate) async fn run_with_prompt(
+    command: &str,
+    template: &[String],
+    mode: PromptMode,
+    prompt: &str,
+    agent: &AgentName,
+    settings: &AgentSettings,
+    on_output: OutputSink<'_>,
+) -> Result<String, AgentError> {
+    match mode {
+        PromptMode::Stdin => {
+            let args: Vec<&str> = template.iter().map(String::as_str).collect();
+            run_process(command, &args, prompt, agent.clone(), settings, on_output).await
+        }
+        PromptMode::Argv => {
+            let args = expand_args(template, PROMPT_PLACEHOLDER, prompt);
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            run_process(command, &args, "", agent.clone(), settings, on_output).await
+        }
+        PromptMode::File => {
+            // Kept alive until the command finishes; deleted on drop
+            let (_file, path) = write_prompt_file(prompt, agent, settings)?;
+            let args = expand_args(template, PROMPT_FILE_PLACEHOLDER, &path);
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            run_process(command, &args, "", agent.clone(), settings, on_output).await
+        }
+    }
+}
+
+/// Substitute `placeholder` in the argument template, appending `value` if absent
+fn expand_args(template: &[String], placeholder: &str, value: &str) -> Vec<String> {
+    let mut args: Vec<String> = template
+        .iter()
+        .map(|arg| arg.replace(placeholder, value))
+        .collect();
+    if !template.iter().any(|arg| arg.contains(placeholder)) {
+        args.push(value.to_string());
+    }
+    args
+}
+
+/// Write the prompt to a private temp file (0600, removed on drop)
+///
+/// Returns the file and the path to pass to the agent. Gemini only resolves
+/// `@<path>` inside its workspace, the directory it runs in, so its file is
+/// written there and passed by name. Other agents get a file in the system
+/// temp directory.
+fn write_prompt_file(
+    prompt: &str,
+    agent: &AgentName,
+    settings: &AgentSettings,
+) -> Result<(tempfile::NamedTempFile, String), AgentError> {
+    let io_err = |e: std::io::Error| AgentError::ExecutionFailed {
+        agent: agent.clone(),
+        exit_code: None,
+        stderr: format!("failed to write prompt file: {}", e),
+    };
+    let mut builder = tempfile::Builder::new();
+    builder.suffix(".txt");
+    let mut file = match agent {
+        AgentName::Gemini => {
+            let workspace = match &settings.cwd {
+                Some(cwd) => cwd.clone(),
+                None => std::env::current_dir().map_err(io_err)?,
+            };
+            builder
+                .prefix(".commitment-prompt-")
+                .tempfile_in(workspace)
+                .map_err(io_err)?
+        }
+        _ => builder
+            .prefix("commitment-prompt-")
+            .tempfile()
+            .map_err(io_err)?,
+    };
+    file.write_all(prompt.as_bytes()).map_err(io_err)?;
+    file.flush().map_err(io_err)?;
+    let path = match agent {
+        AgentName::Gemini => file.path().file_name().unwrap_or_default(),
+        _ => file.path().as_os_str(),
+    };
+    let path = path.to_string_lossy().into_owned();
+    Ok((file, path))
+}
+
+/// Read a string from JSON output by dot-separated path (`choices.0.text`)
+///
+/// Shared by custom agents with JSON output and HTTP agents.
+pub(crate) fn extract_json_field(stdout: &str, field: &str) -> Result<String, AgentError> {
+    let value: serde_json::Value =
+        serde_json::from_str(stdout.trim()).map_err(|e| AgentError::InvalidResponse {
+            reason: format!("expected JSON output: {}", e),
         })?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        return Err(AgentError::ExecutionFailed { agent, stderr });
+    let mut current = &value;
+    for key in field.split('.') {
+        let next = match key.parse::<usize>() {
+            Ok(index) => current.get(index),
+            Err(_) => current.get(key),
+        };
+        current = next.ok_or_else(|| AgentError::InvalidResponse {
+            reason: format!("JSON output has no field `{}`", field),
+        })?;
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    Ok(stdout)
+    current
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| AgentError::InvalidResponse {
+            reason: format!("JSON field `{}` is not a string", field),
+        })
 }
 
 /// Trait for agent execution
@@ -128,11 +517,46 @@ pub trait AgentExecutor {
         prompt: &str,
     ) -> impl std::future::Future<Output = Result<String, AgentError>> + Send;
 
+    /// Execute the agent, passing the response so far to `on_output` as it arrives
+    ///
+    /// Agents that can't stream answer in one piece (the default).
+    fn execute_streaming(
+        &self,
+        prompt: &str,
+        on_output: OutputSink<'_>,
+    ) -> impl std::future::Future<Output = Result<String, AgentError>> + Send {
+        let _ = on_output;
+        self.execute(prompt)
+    }
+
+    /// Execute a prompt that asks for the commit message itself
+    ///
+    /// `judge` tells usable messages apart, so agents asked in parallel can
+    /// pick a response; single agents just stream (the default). Chunk
+    /// summaries and candidate lists go through [`Self::execute`] instead.
+    fn execute_message(
+        &self,
+        prompt: &str,
+        judge: &parallel::ResponseJudge,
+        on_output: OutputSink<'_>,
+    ) -> impl std::future::Future<Output = Result<String, AgentError>> + Send {
+        let _ = judge;
+        self.execute_streaming(prompt, on_output)
+    }
+
+    /// Hand later prompts to the next agent, returning whether there was one
+    ///
+    /// Called by the generation loop once repairs run out for the agent that
+    /// answered. Single agents have nothing to fall back to (the default).
+    fn fall_back(&self) -> bool {
+        false
+    }
+
     /// Get the agent name for signatures and error reporting
     fn name(&self) -> AgentName;
 }
 
-/// Agent enum - closed set of supported AI agents
+/// Agent enum - built-in AI agents plus the generic custom command agent
 ///
 /// Uses enum dispatch (not trait objects) for:
 /// - No heap allocation
@@ -142,6 +566,8 @@ pub enum Agent {
     Claude(claude::ClaudeAgent),
     Codex(codex::CodexAgent),
     Gemini(gemini::GeminiAgent),
+    Custom(custom::CustomAgent),
+    Http(http::HttpAgent),
 }
 
 impl AgentExecutor for Agent {
@@ -150,6 +576,22 @@ impl AgentExecutor for Agent {
             Self::Claude(agent) => agent.execute(prompt).await,
             Self::Codex(agent) => agent.execute(prompt).await,
             Self::Gemini(agent) => agent.execute(prompt).await,
+            Self::Custom(agent) => agent.execute(prompt).await,
+            Self::Http(agent) => agent.execute(prompt).await,
+        }
+    }
+
+    async fn execute_streaming(
+        &self,
+        prompt: &str,
+        on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
+        match self {
+            Self::Claude(agent) => agent.execute_streaming(prompt, on_output).await,
+            Self::Codex(agent) => agent.execute_streaming(prompt, on_output).await,
+            Self::Gemini(agent) => agent.execute_streaming(prompt, on_output).await,
+            Self::Custom(agent) => agent.execute_streaming(prompt, on_output).await,
+            Self::Http(agent) => agent.execute_streaming(prompt, on_output).await,
         }
     }
 
@@ -158,16 +600,316 @@ impl AgentExecutor for Agent {
             Self::Claude(_) => AgentName::Claude,
             Self::Codex(_) => AgentName::Codex,
             Self::Gemini(_) => AgentName::Gemini,
+            Self::Custom(agent) => AgentName::Custom(agent.name.clone()),
+            Self::Http(agent) => AgentName::Custom(agent.name.clone()),
+        }
+    }
+}
+
+impl Agent {
+    /// Create the configured agent with its settings
+    ///
+    /// Agents pick up their `[agents.<name>]` section if present; custom
+    /// sections with an `api` key become HTTP agents. `fallback_agents` are
+    /// ignored (see [`AgentGroup::from_config`]).
+    pub fn from_config(config: &Config) -> Self {
+        let settings = config.agent_settings(&config.agent);
+        let prompt = match config.agents.get(config.agent.command_name()) {
+            Some(AgentConfig::Builtin(builtin)) => builtin.prompt,
+            _ => PromptMode::default(),
+        };
+        match &config.agent {
+            AgentName::Claude => Self::Claude(claude::ClaudeAgent { settings, prompt }),
+            AgentName::Codex => Self::Codex(codex::CodexAgent { settings, prompt }),
+            AgentName::Gemini => Self::Gemini(gemini::GeminiAgent { settings, prompt }),
+            AgentName::Custom(name) => match config.agents.get(name) {
+                Some(AgentConfig::Http(http_config)) => Self::Http(http::HttpAgent {
+                    name: name.clone(),
+                    config: http::HttpAgentConfig {
+                        model: settings.model,
+                        ..http_config.clone()
+                    },
+                    timeout: settings.timeout,
+                }),
+                Some(AgentConfig::Command(command_config)) => Self::Custom(custom::CustomAgent {
+                    name: name.clone(),
+                    config: command_config.clone(),
+                    settings,
+                }),
+                // Built-in sections are only stored under built-in names
+                Some(AgentConfig::Builtin(_)) | None => Self::Custom(custom::CustomAgent {
+                    settings,
+                    ..custom::CustomAgent::new(name.clone())
+                }),
+            },
+        }
+    }
+}
+
+/// How `agent` and its `fallback_agents` are combined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentMode {
+    /// Ask one agent at a time, moving on when one fails (see [`chain::AgentChain`])
+    #[default]
+    Fallback,
+    /// Ask all agents at once and use the first valid message
+    Race,
+    /// Ask all agents at once and use the best valid message
+    Rank,
+}
+
+impl FromStr for AgentMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fallback" => Ok(Self::Fallback),
+            "race" => Ok(Self::Race),
+            "rank" => Ok(Self::Rank),
+            _ => Err(format!(
+                "unknown agent mode `{}` (expected fallback, race or rank)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AgentMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fallback => write!(f, "fallback"),
+            Self::Race => write!(f, "race"),
+            Self::Rank => write!(f, "rank"),
         }
     }
 }
 
+/// The configured agents, combined according to `agent_mode`
+pub enum AgentGroup {
+    Chain(chain::AgentChain<Agent>),
+    Parallel(parallel::ParallelAgents<Agent>),
+}
+
+impl AgentGroup {
+    /// Create `agent` and its `fallback_agents`
+    pub fn from_config(config: &Config) -> Self {
+        let agents: Vec<Agent> = config
+            .agent_chain()
+            .into_iter()
+            .map(|agent| Agent::from_config(&config.single_agent(agent)))
+            .collect();
+        match config.agent_mode {
+            AgentMode::Fallback => Self::Chain(chain::AgentChain::new(agents)),
+            AgentMode::Race => Self::Parallel(parallel::ParallelAgents::race(agents)),
+            AgentMode::Rank => Self::Parallel(parallel::ParallelAgents::rank(agents)),
+        }
+    }
+}
+
+impl AgentExecutor for AgentGroup {
+    async fn execute(&self, prompt: &str) -> Result<String, AgentError> {
+        match self {
+            Self::Chain(agents) => agents.execute(prompt).await,
+            Self::Parallel(agents) => agents.execute(prompt).await,
+        }
+    }
+
+    /// Parallel agents would interleave their output, so only chains stream
+    async fn execute_streaming(
+        &self,
+        prompt: &str,
+        on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
+        match self {
+            Self::Chain(agents) => agents.execute_streaming(prompt, on_output).await,
+            Self::Parallel(agents) => agents.execute(prompt).await,
+        }
+    }
+
+    async fn execute_message(
+        &self,
+        prompt: &str,
+        judge: &parallel::ResponseJudge,
+        on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
+        match self {
+            Self::Chain(agents) => agents.execute_streaming(prompt, on_output).await,
+            Self::Parallel(agents) => agents.execute_message(prompt, judge, on_output).await,
+        }
+    }
+
+    /// Parallel agents were all asked already
+    fn fall_back(&self) -> bool {
+        match self {
+            Self::Chain(agents) => agents.fall_back(),
+            Self::Parallel(_) => false,
+        }
+    }
+
+    fn name(&self) -> AgentName {
+        match self {
+            Self::Chain(agents) => agents.name(),
+            Self::Parallel(agents) => agents.name(),
+        }
+    }
+}
+
+/// Settings for a built-in agent, from an `[agents.claude]`, `[agents.codex]`
+/// or `[agents.gemini]` section
+///
+/// ```toml
+/// [agents.claude]
+/// model = "haiku"
+/// args = ["--max-turns", "1"]
+/// env = { ANTHROPIC_LOG = "error" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuiltinAgentConfig {
+    /// Prompt delivery mode
+    #[serde(default)]
+    pub prompt: PromptMode,
+    /// Model passed with `--model`
+    pub model: Option<String>,
+    /// Extra arguments for the CLI
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Directory the CLI runs in
+    pub cwd: Option<PathBuf>,
+    /// Timeout for this agent (overrides `timeout_secs`)
+    pub timeout_secs: Option<u64>,
+}
+
+impl BuiltinAgentConfig {
+    /// One-line summary for `config show`
+    pub fn summary(&self) -> String {
+        let mut summary = format!("prompt: {}", self.prompt.as_str());
+        if let Some(model) = &self.model {
+            summary.push_str(&format!(", model: {}", model));
+        }
+        if !self.args.is_empty() {
+            summary.push_str(&format!(", args: {}", self.args.join(" ")));
+        }
+        summary
+    }
+}
+
+/// An `[agents.<name>]` config section
+///
+/// Sections named after a built-in agent adjust that agent. Otherwise,
+/// sections with an `api` key describe an HTTP endpoint and everything else
+/// describes a local command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AgentConfig {
+    Builtin(BuiltinAgentConfig),
+    Command(custom::CustomAgentConfig),
+    Http(http::HttpAgentConfig),
+}
+
+impl AgentConfig {
+    /// The section's `timeout_secs`, if set
+    pub fn timeout_secs(&self) -> Option<u64> {
+        match self {
+            Self::Builtin(config) => config.timeout_secs,
+            Self::Command(config) => config.timeout_secs,
+            Self::Http(config) => config.timeout_secs,
+        }
+    }
+
+    /// One-line summary for `config show`
+    pub fn summary(&self, name: &str) -> String {
+        match self {
+            Self::Builtin(config) => config.summary(),
+            Self::Command(config) => config.summary(name),
+            Self::Http(config) => config.summary(),
+        }
+    }
+
+    /// Parse the `[agents.<name>]` section for `name`
+    pub fn from_section(name: &str, table: toml::Table) -> Result<Self, String> {
+        if AgentName::BUILTIN
+            .iter()
+            .any(|agent| agent.command_name() == name)
+        {
+            let config: BuiltinAgentConfig = table
+                .try_into()
+                .map_err(|e| format!("built-in agent `{}`: {}", name, e.message()))?;
+            // File mode passes `@<path>`, which only the Gemini CLI reads
+            if config.prompt == PromptMode::File && name != AgentName::Gemini.command_name() {
+                return Err(format!(
+                    "built-in agent `{}`: prompt = \"file\" is only supported by gemini \
+                     (use stdin or argv)",
+                    name
+                ));
+            }
+            return Ok(Self::Builtin(config));
+        }
+        let config: Self = table.try_into().map_err(|e| e.message().to_string())?;
+        // Ollama has no default model and rejects requests without one
+        if let Self::Http(http) = &config
+            && http.api == http::HttpApi::Ollama
+            && http.model.is_none()
+        {
+            return Err(format!(
+                "agent `{}`: api = \"ollama\" needs a `model`",
+                name
+            ));
+        }
+        Ok(config)
+    }
+}
+
+/// Deserialize the `[agents]` table, picking each section's schema by name
+pub(crate) fn deserialize_agent_sections<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, AgentConfig>, D::Error> {
+    let sections = BTreeMap::<String, toml::Table>::deserialize(deserializer)?;
+    sections
+        .into_iter()
+        .map(|(name, table)| {
+            let config =
+                AgentConfig::from_section(&name, table).map_err(serde::de::Error::custom)?;
+            Ok((name, config))
+        })
+        .collect()
+}
+
+impl<'de> Deserialize<'de> for AgentConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Pick the variant up front so field errors name the right schema
+        let table = toml::Table::deserialize(deserializer)?;
+        let result = if table.contains_key("api") {
+            table.try_into().map(Self::Http)
+        } else {
+            table.try_into().map(Self::Command)
+        };
+        result.map_err(|e| serde::de::Error::custom(e.message()))
+    }
+}
+
+impl From<custom::CustomAgentConfig> for AgentConfig {
+    fn from(config: custom::CustomAgentConfig) -> Self {
+        Self::Command(config)
+    }
+}
+
+impl From<http::HttpAgentConfig> for AgentConfig {
+    fn from(config: http::HttpAgentConfig) -> Self {
+        Self::Http(config)
+    }
+}
+
 impl From<AgentName> for Agent {
     fn from(name: AgentName) -> Self {
         match name {
-            AgentName::Claude => Self::Claude(claude::ClaudeAgent),
-            AgentName::Codex => Self::Codex(codex::CodexAgent),
-            AgentName::Gemini => Self::Gemini(gemini::GeminiAgent),
+            AgentName::Claude => Self::Claude(claude::ClaudeAgent::default()),
+            AgentName::Codex => Self::Codex(codex::CodexAgent::default()),
+            AgentName::Gemini => Self::Gemini(gemini::GeminiAgent::default()),
+            AgentName::Custom(name) => Self::Custom(custom::CustomAgent::new(name)),
         }
     }
 }
@@ -207,10 +949,75 @@ pub fn clean_ai_response(raw: &str) -> String {
     cleaned.trim().to_string()
 }
 
+/// The part of a partial response worth showing while it streams in
+///
+/// Text before `<<<COMMIT_MESSAGE_START>>>` is dropped once the marker
+/// arrives, everything from `<<<COMMIT_MESSAGE_END>>>` on is dropped, and so
+/// is a marker that has only partly arrived. The final response still goes
+/// through [`clean_ai_response`].
+pub fn streamed_message(partial: &str) -> &str {
+    let message = match partial.find(MESSAGE_START) {
+        Some(start) => &partial[start + MESSAGE_START.len()..],
+        None => partial,
+    };
+    let message = match message.find(MESSAGE_END) {
+        Some(end) => &message[..end],
+        None => message,
+    };
+
+    // A trailing `<<<COMMIT_MES` could be the start of either marker
+    let partial_marker = (1..MESSAGE_START.len())
+        .rev()
+        .filter_map(|len| message.get(message.len().checked_sub(len)?..))
+        .find(|tail| MESSAGE_START.starts_with(tail) || MESSAGE_END.starts_with(tail))
+        .map_or(0, str::len);
+    &message[..message.len() - partial_marker]
+}
+
+/// Split a multi-candidate AI response into cleaned messages
+///
+/// Expects the candidates between the usual markers, separated by
+/// [`CANDIDATE_SEPARATOR`] lines. Agents that wrap each candidate in its own
+/// marker pair are handled too. Each piece goes through [`clean_ai_response`];
+/// empty pieces are dropped.
+pub fn clean_ai_candidates(raw: &str) -> Vec<String> {
+    let blocks: Vec<&str> = MARKER_EXTRACT
+        .captures_iter(raw)
+        .filter_map(|captures| captures.get(1))
+        .map(|content| content.as_str())
+        .collect();
+    let blocks = if blocks.is_empty() { vec![raw] } else { blocks };
+
+    blocks
+        .into_iter()
+        .flat_map(|block| block.split(CANDIDATE_SEPARATOR))
+        .map(clean_ai_response)
+        .filter(|candidate| !candidate.is_empty())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn expand_args_replaces_placeholder() {
+        let template = vec!["-p".to_string(), "{prompt}".to_string()];
+        assert_eq!(
+            expand_args(&template, PROMPT_PLACEHOLDER, "hello"),
+            vec!["-p", "hello"]
+        );
+    }
+
+    #[test]
+    fn expand_args_appends_when_placeholder_missing() {
+        let template = vec!["run".to_string(), "llama3".to_string()];
+        assert_eq!(
+            expand_args(&template, PROMPT_PLACEHOLDER, "hello"),
+            vec!["run", "llama3", "hello"]
+        );
+    }
+
     #[test]
     fn clean_ai_response_with_markers() {
         let input = "Some preamble\n<<<COMMIT_MESSAGE_START>>>feat: add feature<<<COMMIT_MESSAGE_END>>>\nSome postamble";
@@ -218,6 +1025,44 @@ mod tests {
         assert_eq!(result, "feat: add feature");
     }
 
+    #[test]
+    fn streamed_message_hides_markers() {
+        assert_eq!(streamed_message("Thinking about it"), "Thinking about it");
+        assert_eq!(
+            streamed_message("Sure:\n<<<COMMIT_MESSAGE_START>>>\nfeat: add x\n<<<COMMIT_MES"),
+            "\nfeat: add x\n"
+        );
+        assert_eq!(
+            streamed_message("<<<COMMIT_MESSAGE_START>>>fix: y<<<COMMIT_MESSAGE_END>>>\nDone!"),
+            "fix: y"
+        );
+        assert_eq!(
+            streamed_message("Here it is <<<COMMIT_MESSAGE_ST"),
+            "Here it is "
+        );
+        assert_eq!(streamed_message("a << b"), "a << b");
+    }
+
+    #[test]
+    fn clean_ai_candidates_splits_on_separator() {
+        let input = "<<<COMMIT_MESSAGE_START>>>\nfeat: add a\n<<<COMMIT_MESSAGE_SEPARATOR>>>\n```\nfeat: add b\n```\n<<<COMMIT_MESSAGE_SEPARATOR>>>\n<<<COMMIT_MESSAGE_END>>>";
+        assert_eq!(clean_ai_candidates(input), ["feat: add a", "feat: add b"]);
+    }
+
+    #[test]
+    fn clean_ai_candidates_accepts_one_marker_pair_per_candidate() {
+        let input = "<<<COMMIT_MESSAGE_START>>>fix: a<<<COMMIT_MESSAGE_END>>>\n<<<COMMIT_MESSAGE_START>>>fix: b<<<COMMIT_MESSAGE_END>>>";
+        assert_eq!(clean_ai_candidates(input), ["fix: a", "fix: b"]);
+    }
+
+    #[test]
+    fn clean_ai_candidates_without_markers_is_single_candidate() {
+        assert_eq!(
+            clean_ai_candidates("docs: update readme"),
+            ["docs: update readme"]
+        );
+    }
+
     #[test]
     fn clean_ai_response_with_code_blocks() {
         let input = "```\nfeat: add feature\n```";
@@ -359,19 +1204,19 @@ This implements JWT-based authentication.
 
     #[test]
     fn agent_name_returns_correct_variant_claude() {
-        let agent = Agent::Claude(claude::ClaudeAgent);
+        let agent = Agent::Claude(claude::ClaudeAgent::default());
         assert_eq!(agent.name(), AgentName::Claude);
     }
 
     #[test]
     fn agent_name_returns_correct_variant_codex() {
-        let agent = Agent::Codex(codex::CodexAgent);
+        let agent = Agent::Codex(codex::CodexAgent::default());
         assert_eq!(agent.name(), AgentName::Codex);
     }
 
     #[test]
     fn agent_name_returns_correct_variant_gemini() {
-        let agent = Agent::Gemini(gemini::GeminiAgent);
+        let agent = Agent::Gemini(gemini::GeminiAgent::default());
         assert_eq!(agent.name(), AgentName::Gemini);
     }
 
@@ -393,6 +1238,233 @@ This implements JWT-based authentication.
         assert_eq!(agent.name(), AgentName::Gemini);
     }
 
+    #[test]
+    fn agent_from_config_uses_configured_timeout() {
+        let config = Config {
+            agent: AgentName::Codex,
+            timeout_secs: 30,
+            ..Config::default()
+        };
+        match Agent::from_config(&config) {
+            Agent::Codex(agent) => assert_eq!(agent.settings.timeout, Duration::from_secs(30)),
+            _ => panic!("expected Codex agent"),
+        }
+    }
+
+    #[test]
+    fn agent_from_config_uses_custom_agent_section() {
+        let mut config = Config {
+            agent: AgentName::Custom("ollama".to_string()),
+            ..Config::default()
+        };
+        config.agents.insert(
+            "ollama".to_string(),
+            custom::CustomAgentConfig {
+                args: vec!["run".to_string(), "llama3".to_string()],
+                ..Default::default()
+            }
+            .into(),
+        );
+
+        let agent = Agent::from_config(&config);
+        assert_eq!(agent.name(), AgentName::Custom("ollama".to_string()));
+        match agent {
+            Agent::Custom(agent) => assert_eq!(agent.config.args, vec!["run", "llama3"]),
+            _ => panic!("expected Custom agent"),
+        }
+    }
+
+    #[test]
+    fn agent_group_follows_agent_mode() {
+        let config = Config {
+            agent: AgentName::Codex,
+            fallback_agents: vec![AgentName::Codex, AgentName::Gemini],
+            ..Config::default()
+        };
+        let group = AgentGroup::from_config(&config);
+        assert!(matches!(group, AgentGroup::Chain(_)));
+        assert_eq!(group.name(), AgentName::Codex);
+
+        let config = Config {
+            agent_mode: AgentMode::Race,
+            ..config
+        };
+        let group = AgentGroup::from_config(&config);
+        assert!(matches!(group, AgentGroup::Parallel(_)));
+    }
+
+    #[test]
+    fn builtin_agent_section_sets_prompt_mode() {
+        let layer = crate::config::ConfigLayer::from_toml(
+            "[agents.gemini]\nprompt = \"file\"\n",
+            std::path::Path::new("t.toml"),
+        )
+        .unwrap();
+        assert_eq!(
+            layer.agents["gemini"],
+            AgentConfig::Builtin(BuiltinAgentConfig {
+                prompt: PromptMode::File,
+                ..Default::default()
+            })
+        );
+
+        let config = Config {
+            agent: AgentName::Gemini,
+            agents: layer.agents,
+            ..Config::default()
+        };
+        match Agent::from_config(&config) {
+            Agent::Gemini(agent) => assert_eq!(agent.prompt, PromptMode::File),
+            _ => panic!("expected Gemini agent"),
+        }
+        match Agent::from_config(&config.single_agent(AgentName::Claude)) {
+            Agent::Claude(agent) => assert_eq!(agent.prompt, PromptMode::Stdin),
+            _ => panic!("expected Claude agent"),
+        }
+    }
+
+    #[test]
+    fn ollama_agents_need_a_model() {
+        let error = crate::config::ConfigLayer::from_toml(
+            "[agents.local]\napi = \"ollama\"\n",
+            std::path::Path::new("t.toml"),
+        )
+        .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("agent `local`: api = \"ollama\" needs a `model`"),
+            "{}",
+            error
+        );
+
+        // OpenAI-compatible servers fall back to their loaded model
+        assert!(
+            crate::config::ConfigLayer::from_toml(
+                "[agents.local]\napi = \"openai\"\n",
+                std::path::Path::new("t.toml"),
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn file_prompt_mode_is_only_for_gemini() {
+        for name in ["claude", "codex"] {
+            let error = crate::config::ConfigLayer::from_toml(
+                &format!("[agents.{}]\nprompt = \"file\"\n", name),
+                std::path::Path::new("t.toml"),
+            )
+            .unwrap_err();
+            assert!(
+                error
+                    .to_string()
+                    .contains("prompt = \"file\" is only supported by gemini"),
+                "{}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn gemini_prompt_files_go_in_its_workspace() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let settings = AgentSettings {
+            cwd: Some(workspace.path().to_path_buf()),
+            ..AgentSettings::default()
+        };
+
+        // Gemini rejects `@` paths outside the directory it runs in
+        let (file, path) = write_prompt_file("prompt", &AgentName::Gemini, &settings).unwrap();
+        assert!(!path.contains('/'), "{}", path);
+        assert!(path.starts_with(".commitment-prompt-"));
+        assert_eq!(file.path(), workspace.path().join(&path));
+
+        let custom = AgentName::Custom("llm".to_string());
+        let (file, path) = write_prompt_file("prompt", &custom, &settings).unwrap();
+        assert!(std::path::Path::new(&path).is_absolute());
+        assert!(!file.path().starts_with(workspace.path()));
+    }
+
+    #[test]
+    fn agent_mode_round_trips() {
+        for mode in [AgentMode::Fallback, AgentMode::Race, AgentMode::Rank] {
+            assert_eq!(mode.to_string().parse::<AgentMode>(), Ok(mode));
+        }
+        assert!("fastest".parse::<AgentMode>().is_err());
+    }
+
+    #[test]
+    fn agent_from_custom_agent_name() {
+        let agent = Agent::from(AgentName::Custom("llm".to_string()));
+        assert_eq!(agent.name(), AgentName::Custom("llm".to_string()));
+    }
+
+    #[test]
+    fn extract_json_field_reads_nested_path() {
+        let json = r#"{"choices": [{"text": "feat: add thing"}]}"#;
+        assert_eq!(
+            extract_json_field(json, "choices.0.text").unwrap(),
+            "feat: add thing"
+        );
+    }
+
+    #[test]
+    fn extract_json_field_rejects_missing_field() {
+        let result = extract_json_field(r#"{"other": "x"}"#, "response");
+        assert!(matches!(result, Err(AgentError::InvalidResponse { .. })));
+    }
+
+    #[test]
+    fn extract_json_field_rejects_invalid_json() {
+        let result = extract_json_field("not json", "response");
+        assert!(matches!(result, Err(AgentError::InvalidResponse { .. })));
+    }
+
+    #[test]
+    fn agent_config_with_api_key_is_http() {
+        let config: AgentConfig = toml::from_str("api = \"ollama\"\nmodel = \"llama3\"").unwrap();
+        assert!(matches!(config, AgentConfig::Http(_)));
+    }
+
+    #[test]
+    fn agent_config_without_api_key_is_command() {
+        let config: AgentConfig = toml::from_str("command = \"llm\"").unwrap();
+        assert!(matches!(config, AgentConfig::Command(_)));
+    }
+
+    #[test]
+    fn agent_config_reports_unknown_http_field() {
+        let result = toml::from_str::<AgentConfig>("api = \"openai\"\nargs = []");
+        assert!(result.unwrap_err().message().contains("args"));
+    }
+
+    #[test]
+    fn agent_from_config_uses_http_agent_section() {
+        let mut config = Config {
+            agent: AgentName::Custom("local".to_string()),
+            timeout_secs: 10,
+            ..Config::default()
+        };
+        config.agents.insert(
+            "local".to_string(),
+            http::HttpAgentConfig {
+                model: Some("qwen".to_string()),
+                ..Default::default()
+            }
+            .into(),
+        );
+
+        match Agent::from_config(&config) {
+            Agent::Http(agent) => {
+                assert_eq!(agent.name, "local");
+                assert_eq!(agent.config.model.as_deref(), Some("qwen"));
+                assert_eq!(agent.timeout, Duration::from_secs(10));
+            }
+            _ => panic!("expected Http agent"),
+        }
+    }
+
     #[test]
     fn agent_timeout_constant() {
         // Verify timeout is 120 seconds as specified
@@ -422,47 +1494,152 @@ This implements JWT-based authentication.
     }
 
     #[tokio::test]
-    async fn run_command_with_stdin_success() {
-        // Test with echo command which will succeed
-        let result = run_command_with_stdin("cat", &[], "test input", AgentName::Claude).await;
-        assert!(result.is_ok());
+    async fn run_process_success() {
+        // cat echoes stdin back
+        let result = run_process(
+            "cat",
+            &[],
+            "test input",
+            AgentName::Claude,
+            &AgentSettings::default(),
+            &mut |_| {},
+        )
+        .await;
         assert_eq!(result.unwrap(), "test input");
     }
 
     #[tokio::test]
-    async fn run_command_with_stdin_command_not_found() {
-        // Test with nonexistent command
-        let result = run_command_with_stdin(
+    async fn run_process_streams_stdout_line_by_line() {
+        let mut seen = Vec::new();
+        let result = run_process(
+            "sh",
+            &["-c", "echo one; sleep 0.1; printf 'two\\nthree'"],
+            "",
+            AgentName::Claude,
+            &AgentSettings::default(),
+            &mut |text| seen.push(text.to_string()),
+        )
+        .await;
+        assert_eq!(result.unwrap(), "one\ntwo\nthree");
+        assert_eq!(seen.first().map(String::as_str), Some("one\n"));
+        assert_eq!(seen.last().map(String::as_str), Some("one\ntwo\nthree"));
+    }
+
+    #[tokio::test]
+    async fn run_process_command_not_found() {
+        let result = run_process(
             "this-command-does-not-exist-12345",
             &[],
             "test",
             AgentName::Gemini,
+            &AgentSettings::default(),
+            &mut |_| {},
         )
         .await;
-        assert!(result.is_err());
         match result.unwrap_err() {
-            AgentError::ExecutionFailed { agent, .. } => assert_eq!(agent, AgentName::Gemini),
+            AgentError::ExecutionFailed {
+                agent, exit_code, ..
+            } => {
+                assert_eq!(agent, AgentName::Gemini);
+                assert_eq!(exit_code, None);
+            }
             _ => panic!("expected ExecutionFailed error"),
         }
     }
 
     #[tokio::test]
-    async fn run_command_with_stdin_command_failure() {
-        // Test with command that exits with error (ls on nonexistent directory)
-        let result = run_command_with_stdin(
-            "ls",
-            &["/this/path/does/not/exist/surely"],
+    async fn run_process_command_failure() {
+        let result = run_process(
+            "sh",
+            &["-c", "echo broken >&2; exit 3"],
             "",
             AgentName::Claude,
+            &AgentSettings::default(),
+            &mut |_| {},
         )
         .await;
-        assert!(result.is_err());
         match result.unwrap_err() {
-            AgentError::ExecutionFailed { agent, stderr } => {
+            AgentError::ExecutionFailed {
+                agent,
+                exit_code,
+                stderr,
+            } => {
                 assert_eq!(agent, AgentName::Claude);
-                assert!(!stderr.is_empty());
+                assert_eq!(exit_code, Some(3));
+                assert_eq!(stderr, "broken");
             }
             _ => panic!("expected ExecutionFailed error"),
         }
     }
+
+    #[tokio::test]
+    async fn run_process_does_not_deadlock_on_full_pipes() {
+        // Writes more than a pipe buffer before reading any of its input
+        let script = "head -c 300000 /dev/zero | tr '\\0' x; cat > /dev/null";
+        let input = "y".repeat(1024 * 1024);
+        let result = run_process(
+            "sh",
+            &["-c", script],
+            &input,
+            AgentName::Claude,
+            &AgentSettings {
+                timeout: Duration::from_secs(10),
+                ..Default::default()
+            },
+            &mut |_| {},
+        )
+        .await;
+        assert_eq!(result.unwrap().len(), 300_000);
+    }
+
+    #[tokio::test]
+    async fn run_process_rejects_oversized_output() {
+        let script = format!("head -c {} /dev/zero", MAX_STDOUT_BYTES + 1);
+        let result = run_process(
+            "sh",
+            &["-c", &script],
+            "",
+            AgentName::Claude,
+            &AgentSettings::default(),
+            &mut |_| {},
+        )
+        .await;
+        assert!(matches!(result, Err(AgentError::InvalidResponse { .. })));
+    }
+
+    // Reads /proc to see whether the grandchild is still running
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn run_process_kills_the_process_group_on_timeout() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pid_file = dir.path().join("pid");
+        let script = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+
+        let result = run_process(
+            "sh",
+            &["-c", &script],
+            "",
+            AgentName::Codex,
+            &AgentSettings {
+                timeout: Duration::from_millis(500),
+                ..Default::default()
+            },
+            &mut |_| {},
+        )
+        .await;
+        assert!(matches!(result, Err(AgentError::Timeout { .. })));
+
+        // The grandchild is gone (or a zombie awaiting its new parent)
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let stat = std::path::Path::new("/proc").join(pid.trim()).join("stat");
+        for _ in 0..50 {
+            match std::fs::read_to_string(&stat) {
+                Ok(stat) if !stat.contains(") Z ") => {
+                    tokio::time::sleep(Duration::from_millis(20)).await
+                }
+                _ => return,
+            }
+        }
+        panic!("sleep outlived its timed-out parent");
+    }
 }
@@ -0,0 +1,452 @@
+use super::{AgentExecutor, OutputSink, clean_ai_response};
+use crate::diff::name_status_path;
+use crate::error::AgentError;
+use crate::format::{FormatOptions, format_message};
+use crate::types::{AgentName, CommitRules, ConventionalCommit, StagedDiff};
+use futures_util::future::join_all;
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Headers up to this length aren't penalized when ranking
+const IDEAL_HEADER_LENGTH: usize = 50;
+
+/// Points for a scope that is allowed in this repository
+const SCOPE_POINTS: i64 = 10;
+
+/// Points per changed file the message mentions (up to `MAX_MENTIONED_FILES`)
+const FILE_POINTS: i64 = 5;
+
+/// Mentioned files beyond this don't add points
+const MAX_MENTIONED_FILES: usize = 3;
+
+/// Decides which agent responses are usable commit messages, and how good
+pub struct ResponseJudge {
+    rules: CommitRules,
+    format: FormatOptions,
+    /// File names and stems of the staged files, lowercased
+    changed_files: Vec<(String, String)>,
+}
+
+impl ResponseJudge {
+    /// Judge responses against `rules` for a commit of `diff`
+    ///
+    /// Responses are cleaned and tidied with `format` first, as generation
+    /// does before validating.
+    pub fn new(rules: CommitRules, format: FormatOptions, diff: &StagedDiff) -> Self {
+        let changed_files = diff
+            .name_status
+            .lines()
+            .filter_map(name_status_path)
+            .filter_map(|path| {
+                let path = Path::new(path);
+                let name = path.file_name()?.to_str()?.to_lowercase();
+                let stem = path.file_stem()?.to_str()?.to_lowercase();
+                Some((name, stem))
+            })
+            .collect();
+        Self {
+            rules,
+            format,
+            changed_files,
+        }
+    }
+
+    /// Parse a raw response as a commit message (`None` if it doesn't validate)
+    pub fn accept(&self, response: &str) -> Option<ConventionalCommit> {
+        let message = format_message(&clean_ai_response(response), &self.format);
+        ConventionalCommit::validate_with(&message, &self.rules).ok()
+    }
+
+    /// Rank a valid message: higher is better
+    ///
+    /// Headers over 50 characters lose a point per extra character, an
+    /// allowed scope earns points, and so does mentioning changed files by
+    /// name (`config.rs`, or `config` for stems of three or more letters).
+    pub fn score(&self, commit: &ConventionalCommit) -> i64 {
+        let overlong = commit
+            .header()
+            .chars()
+            .count()
+            .saturating_sub(IDEAL_HEADER_LENGTH);
+        let mut score = -(overlong as i64);
+
+        if let Some(scope) = commit.scope()
+            && (self.rules.scopes.is_empty() || self.rules.scopes.iter().any(|s| s == scope))
+        {
+            score += SCOPE_POINTS;
+        }
+
+        let text = commit.as_str().to_lowercase();
+        let mentioned = self
+            .changed_files
+            .iter()
+            .filter(|(name, stem)| {
+                text.contains(name.as_str()) || (stem.len() >= 3 && text.contains(stem.as_str()))
+            })
+            .count()
+            .min(MAX_MENTIONED_FILES);
+        score + mentioned as i64 * FILE_POINTS
+    }
+}
+
+/// Agents asked the same prompt at the same time (`agent_mode = "race"` or `"rank"`)
+///
+/// For commit message prompts ([`AgentExecutor::execute_message`]), racing
+/// returns the first response the judge accepts; ranking waits for every
+/// agent and returns the best accepted response by [`ResponseJudge::score`],
+/// earlier agents winning ties. Agents still running when a race is decided
+/// are dropped, which kills their processes.
+///
+/// Other prompts (chunk summaries, candidate lists) take the first response
+/// to arrive in either mode, as do message prompts nothing accepts once every
+/// agent has answered. If every agent fails, the first agent's error is
+/// returned.
+pub struct ParallelAgents<A> {
+    agents: Vec<A>,
+    /// Wait for every agent and rank, instead of racing
+    rank: bool,
+    /// Index of the agent whose response was used last
+    winner: AtomicUsize,
+}
+
+impl<A: AgentExecutor> ParallelAgents<A> {
+    /// Use the first valid response from `agents`
+    ///
+    /// # Panics
+    /// If `agents` is empty.
+    pub fn race(agents: Vec<A>) -> Self {
+        Self::new(agents, false)
+    }
+
+    /// Use the best valid response from `agents`
+    ///
+    /// # Panics
+    /// If `agents` is empty.
+    pub fn rank(agents: Vec<A>) -> Self {
+        Self::new(agents, true)
+    }
+
+    fn new(agents: Vec<A>, rank: bool) -> Self {
+        assert!(
+            !agents.is_empty(),
+            "parallel agents need at least one agent"
+        );
+        Self {
+            agents,
+            rank,
+            winner: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<A: AgentExecutor + Sync> ParallelAgents<A> {
+    /// First response `judge` accepts, or the first to arrive without a judge
+    async fn race_responses(
+        &self,
+        prompt: &str,
+        judge: Option<&ResponseJudge>,
+    ) -> Result<(usize, String), AgentError> {
+        let mut pending: FuturesUnordered<_> = self
+            .agents
+            .iter()
+            .enumerate()
+            .map(|(index, agent)| async move {
+                let result = match judge {
+                    Some(judge) => agent.execute_message(prompt, judge, &mut |_| {}).await,
+                    None => agent.execute(prompt).await,
+                };
+                (index, result)
+            })
+            .collect();
+
+        let mut results = Vec::new();
+        while let Some((index, result)) = pending.next().await {
+            match result {
+                // Dropping `pending` stops the agents still running
+                Ok(response) if judge.is_none_or(|judge| judge.accept(&response).is_some()) => {
+                    return Ok((index, response));
+                }
+                result => results.push((index, result)),
+            }
+        }
+        Self::fallback(results)
+    }
+
+    async fn rank_responses(
+        &self,
+        prompt: &str,
+        judge: &ResponseJudge,
+    ) -> Result<(usize, String), AgentError> {
+        let results =
+            join_all(self.agents.iter().map(|agent| async move {
+                agent.execute_message(prompt, judge, &mut |_| {}).await
+            }))
+            .await;
+
+        let best = results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, result)| {
+                let commit = judge.accept(result.as_ref().ok()?)?;
+                Some((index, judge.score(&commit)))
+            })
+            // Highest score; earlier agents win ties
+            .min_by_key(|&(index, score)| (-score, index));
+
+        match best {
+            Some((index, _)) => {
+                let response = results[index].as_ref().cloned().unwrap_or_default();
+                Ok((index, response))
+            }
+            None => Self::fallback(results.into_iter().enumerate().collect()),
+        }
+    }
+
+    /// No valid response: the first successful one, else the first agent's error
+    fn fallback(
+        results: Vec<(usize, Result<String, AgentError>)>,
+    ) -> Result<(usize, String), AgentError> {
+        let mut first_error: Option<(usize, AgentError)> = None;
+        for (index, result) in results {
+            match result {
+                Ok(response) => return Ok((index, response)),
+                Err(error) => {
+                    if first_error.as_ref().is_none_or(|(first, _)| index < *first) {
+                        first_error = Some((index, error));
+                    }
+                }
+            }
+        }
+        let (_, error) = first_error.expect("parallel agents need at least one agent");
+        Err(error)
+    }
+
+    fn answered(&self, (index, response): (usize, String)) -> String {
+        self.winner.store(index, Ordering::Relaxed);
+        response
+    }
+}
+
+impl<A: AgentExecutor + Sync> AgentExecutor for ParallelAgents<A> {
+    async fn execute(&self, prompt: &str) -> Result<String, AgentError> {
+        Ok(self.answered(self.race_responses(prompt, None).await?))
+    }
+
+    /// Parallel agents would interleave their output, so nothing is streamed
+    async fn execute_message(
+        &self,
+        prompt: &str,
+        judge: &ResponseJudge,
+        _on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
+        let answer = if self.rank {
+            self.rank_responses(prompt, judge).await?
+        } else {
+            self.race_responses(prompt, Some(judge)).await?
+        };
+        Ok(self.answered(answer))
+    }
+
+    /// The agent whose response was used last (the first agent before any call)
+    fn name(&self) -> AgentName {
+        self.agents[self.winner.load(Ordering::Relaxed)].name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::time::Duration;
+
+    /// Agent that answers after a delay, noting whether it got to finish
+    struct DelayedAgent {
+        name: AgentName,
+        delay: Duration,
+        result: Result<&'static str, AgentError>,
+        finished: AtomicBool,
+    }
+
+    fn delayed(
+        name: AgentName,
+        millis: u64,
+        result: Result<&'static str, AgentError>,
+    ) -> DelayedAgent {
+        DelayedAgent {
+            name,
+            delay: Duration::from_millis(millis),
+            result,
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    impl AgentExecutor for DelayedAgent {
+        async fn execute(&self, _prompt: &str) -> Result<String, AgentError> {
+            tokio::time::sleep(self.delay).await;
+            self.finished.store(true, Ordering::Relaxed);
+            self.result.clone().map(str::to_string)
+        }
+
+        fn name(&self) -> AgentName {
+            self.name.clone()
+        }
+    }
+
+    fn judge(name_status: &str, scopes: &[&str]) -> ResponseJudge {
+        let rules = CommitRules {
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            ..CommitRules::default()
+        };
+        let diff = StagedDiff {
+            name_status: name_status.to_string(),
+            ..StagedDiff::default()
+        };
+        ResponseJudge::new(rules, FormatOptions::default(), &diff)
+    }
+
+    async fn message(
+        agents: &ParallelAgents<DelayedAgent>,
+        judge: &ResponseJudge,
+    ) -> Result<String, AgentError> {
+        agents.execute_message("prompt", judge, &mut |_| {}).await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn race_takes_the_first_valid_response_and_drops_the_rest() {
+        let agents = ParallelAgents::race(vec![
+            delayed(AgentName::Claude, 300, Ok("feat: slow")),
+            delayed(AgentName::Codex, 100, Ok("not a commit message")),
+            delayed(AgentName::Gemini, 200, Ok("feat: fast enough")),
+        ]);
+
+        assert_eq!(
+            message(&agents, &judge("", &[])).await.unwrap(),
+            "feat: fast enough"
+        );
+        assert_eq!(agents.name(), AgentName::Gemini);
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(!agents.agents[0].finished.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn race_without_valid_responses_uses_the_earliest() {
+        let agents = ParallelAgents::race(vec![
+            delayed(
+                AgentName::Claude,
+                10,
+                Err(AgentError::NotFound {
+                    agent: AgentName::Claude,
+                }),
+            ),
+            delayed(AgentName::Codex, 200, Ok("- summary b")),
+            delayed(AgentName::Gemini, 100, Ok("- summary a")),
+        ]);
+
+        assert_eq!(
+            message(&agents, &judge("", &[])).await.unwrap(),
+            "- summary a"
+        );
+        assert_eq!(agents.name(), AgentName::Gemini);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn other_prompts_take_the_first_answer_in_either_mode() {
+        for agents in [
+            ParallelAgents::race(vec![
+                delayed(AgentName::Claude, 300, Ok("feat: valid but slow")),
+                delayed(AgentName::Codex, 100, Ok("- summary")),
+            ]),
+            ParallelAgents::rank(vec![
+                delayed(AgentName::Claude, 300, Ok("feat: valid but slow")),
+                delayed(AgentName::Codex, 100, Ok("- summary")),
+            ]),
+        ] {
+            assert_eq!(agents.execute("summarize").await.unwrap(), "- summary");
+            assert_eq!(agents.name(), AgentName::Codex);
+
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            assert!(!agents.agents[0].finished.load(Ordering::Relaxed));
+        }
+    }
+
+    #[test]
+    fn judges_responses_as_generation_formats_them() {
+        let judge = ResponseJudge::new(
+            CommitRules {
+                max_header_length: Some(20),
+                ..CommitRules::default()
+            },
+            FormatOptions {
+                lowercase_subject: true,
+                ..FormatOptions::default()
+            },
+            &StagedDiff::default(),
+        );
+
+        let commit = judge.accept("```\nfeat: Add login\n```").unwrap();
+        assert_eq!(commit.header(), "feat: add login");
+        assert!(judge.accept("feat: add a much longer header").is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rank_picks_the_best_scoring_response() {
+        let agents = ParallelAgents::rank(vec![
+            delayed(AgentName::Claude, 10, Ok("feat: add things")),
+            delayed(
+                AgentName::Codex,
+                100,
+                Ok("feat(config): load config.rs layers"),
+            ),
+            delayed(AgentName::Gemini, 50, Ok("oops")),
+        ]);
+
+        assert_eq!(
+            message(&agents, &judge("M\tsrc/config.rs", &["config"]))
+                .await
+                .unwrap(),
+            "feat(config): load config.rs layers"
+        );
+        assert_eq!(agents.name(), AgentName::Codex);
+    }
+
+    #[tokio::test]
+    async fn returns_the_first_agents_error_when_all_fail() {
+        let agents = ParallelAgents::rank(vec![
+            delayed(
+                AgentName::Claude,
+                0,
+                Err(AgentError::NotFound {
+                    agent: AgentName::Claude,
+                }),
+            ),
+            delayed(
+                AgentName::Codex,
+                0,
+                Err(AgentError::NotFound {
+                    agent: AgentName::Codex,
+                }),
+            ),
+        ]);
+
+        match message(&agents, &judge("", &[])).await {
+            Err(AgentError::NotFound { agent }) => assert_eq!(agent, AgentName::Claude),
+            other => panic!("expected NotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn scores_length_scope_and_mentioned_files() {
+        let judge = judge("M\tsrc/config.rs\nA\tsrc/agents/parallel.rs", &["agents"]);
+        let score = |message: &str| judge.score(&judge.accept(message).unwrap());
+
+        assert_eq!(score("feat: add x"), 0);
+        assert_eq!(score("feat(agents): add x"), SCOPE_POINTS);
+        assert_eq!(
+            score("feat: race parallel agents\n\nReads config.rs"),
+            2 * FILE_POINTS
+        );
+        assert_eq!(score(&format!("feat: {}", "x".repeat(50))), -6);
+    }
+}
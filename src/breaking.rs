@@ -0,0 +1,476 @@
+use crate::diff::split_files;
+use crate::types::StagedDiff;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Public Rust items and JS/TS exports: the item kind and its name
+static PUBLIC_ITEM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(?:pub(?:\s+(?:const|async|unsafe|extern))*\s+(fn|struct|enum|trait|type|const|static|mod|union)|export\s+(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?(function\*?|class|const|let|var|interface|type|enum))\s+([A-Za-z_$][A-Za-z0-9_$]*)",
+    )
+    .expect("valid regex pattern")
+});
+
+/// Explicit long flag names: clap's `long = "name"` and `"--name"` literals
+static FLAG_LITERAL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:\blong\s*=\s*"([a-z0-9][a-z0-9-]*)"|"--([a-z0-9][a-z0-9-]*)")"#)
+        .expect("valid regex pattern")
+});
+
+/// Clap attribute with a bare `long`, naming the flag after the field below it
+static CLAP_LONG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"#\[(?:arg|clap)\(.*\blong\b[^=]*(?:,|\)\])").expect("valid regex pattern")
+});
+
+/// Struct field (`pub dry_run: bool,`)
+static FIELD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:pub(?:\([a-z]+\))?\s+)?([a-z_][a-z0-9_]*)\s*:")
+        .expect("valid regex pattern")
+});
+
+/// Package version in Cargo.toml, pyproject.toml or package.json
+static MANIFEST_VERSION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s*(?:version\s*=\s*|"version"\s*:\s*)"v?([0-9]+)\.([0-9]+)"#)
+        .expect("valid regex pattern")
+});
+
+/// TOML table header (`[package]`, `[[bin]]`)
+static TOML_TABLE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*\[+\s*([^\]]*?)\s*\]").expect("valid regex pattern"));
+
+/// Start lines of a hunk in the old and new file
+static HUNK_HEADER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^@@ -([0-9]+)(?:,[0-9]+)? \+([0-9]+)").expect("valid regex pattern"));
+
+/// Manifests whose version bump is checked
+const MANIFESTS: &[&str] = &["Cargo.toml", "package.json", "pyproject.toml"];
+
+/// `(major, minor)` of a version
+type Version = (u64, u64);
+
+/// TOML tables holding the package's own version (`""` is the top level)
+const PACKAGE_TABLES: &[&str] = &["", "package", "project", "workspace.package"];
+
+/// Whether staged changes are checked for breaking changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BreakingCheck {
+    /// Tell the agent, and reject messages that don't mark a flagged change
+    Require,
+    /// Tell the agent, but accept unmarked messages
+    #[default]
+    Suggest,
+    /// Don't look for breaking changes
+    Off,
+}
+
+impl FromStr for BreakingCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "require" => Ok(Self::Require),
+            "suggest" => Ok(Self::Suggest),
+            "off" => Ok(Self::Off),
+            _ => Err(format!(
+                "unknown breaking change check `{}` (expected require, suggest or off)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for BreakingCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Require => write!(f, "require"),
+            Self::Suggest => write!(f, "suggest"),
+            Self::Off => write!(f, "off"),
+        }
+    }
+}
+
+/// A change that probably breaks users of the code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakingChange {
+    /// Public item or export deleted (and not re-added elsewhere)
+    RemovedSymbol {
+        path: String,
+        kind: String,
+        name: String,
+    },
+    /// Public function re-declared with a different signature
+    ChangedSignature { path: String, name: String },
+    /// Command-line flag deleted
+    RemovedFlag { path: String, flag: String },
+    /// Package version bumped to a new major (or 0.x minor) release
+    MajorVersionBump {
+        path: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RemovedSymbol { path, kind, name } => {
+                write!(f, "removed public {} `{}` in {}", kind, name, path)
+            }
+            Self::ChangedSignature { path, name } => {
+                write!(f, "changed signature of public fn `{}` in {}", name, path)
+            }
+            Self::RemovedFlag { path, flag } => {
+                write!(f, "removed command-line flag `--{}` in {}", flag, path)
+            }
+            Self::MajorVersionBump { path, from, to } => {
+                write!(f, "bumped version {} → {} in {}", from, to, path)
+            }
+        }
+    }
+}
+
+/// Look for likely breaking changes in a staged diff
+///
+/// Line-based heuristics, so both false positives (a `pub fn` in a binary
+/// crate) and misses (a changed return type spread over several lines) are
+/// possible:
+/// - public Rust items and JS/TS exports that are removed, unless the same
+///   name is added anywhere in the diff (moved code)
+/// - public functions whose declaration line changed
+/// - removed `--flags` (clap `long` fields, `long = "..."` and `"--..."` literals)
+/// - major version bumps (or 0.x minor bumps) of the package in Cargo.toml,
+///   package.json or pyproject.toml (not dependency versions)
+pub fn detect_breaking_changes(diff: &StagedDiff) -> Vec<BreakingChange> {
+    let files: Vec<_> = split_files(&diff.diff)
+        .into_iter()
+        .map(|file| {
+            let path = file.path.unwrap_or("diff");
+            (path, file.text, changed_lines(file.text))
+        })
+        .collect();
+
+    // Names declared or flags defined on any added line (moves across files)
+    let mut added_items: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut added_flags: Vec<String> = Vec::new();
+    for (_, _, (_, added)) in &files {
+        for line in added {
+            if let Some((_, name)) = public_item(line) {
+                added_items.entry(name).or_default().push(line.trim());
+            }
+        }
+        added_flags.extend(flags(added));
+    }
+
+    let mut changes = Vec::new();
+    for (path, text, (removed, _)) in &files {
+        for line in removed {
+            let Some((kind, name)) = public_item(line) else {
+                continue;
+            };
+            match added_items.get(name) {
+                None => changes.push(BreakingChange::RemovedSymbol {
+                    path: path.to_string(),
+                    kind: kind.to_string(),
+                    name: name.to_string(),
+                }),
+                Some(declarations) if kind == "fn" && !declarations.contains(&line.trim()) => {
+                    changes.push(BreakingChange::ChangedSignature {
+                        path: path.to_string(),
+                        name: name.to_string(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+
+        for flag in flags(removed) {
+            if !added_flags.contains(&flag) {
+                changes.push(BreakingChange::RemovedFlag {
+                    path: path.to_string(),
+                    flag,
+                });
+            }
+        }
+
+        let manifest = path.rsplit('/').next().unwrap_or_default();
+        if MANIFESTS.contains(&manifest)
+            && let (Some(from), Some(to)) = manifest_versions(text, manifest.ends_with(".json"))
+            && (to.0 > from.0 || (from.0 == 0 && to.0 == 0 && to.1 > from.1))
+        {
+            changes.push(BreakingChange::MajorVersionBump {
+                path: path.to_string(),
+                from: format!("{}.{}", from.0, from.1),
+                to: format!("{}.{}", to.0, to.1),
+            });
+        }
+    }
+
+    let mut unique: Vec<BreakingChange> = Vec::new();
+    for change in changes {
+        if !unique.contains(&change) {
+            unique.push(change);
+        }
+    }
+    unique
+}
+
+/// Removed and added lines of one file's diff, without their `-`/`+`
+fn changed_lines(text: &str) -> (Vec<&str>, Vec<&str>) {
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for line in text.lines() {
+        if line.starts_with("---") || line.starts_with("+++") {
+            continue;
+        }
+        if let Some(line) = line.strip_prefix('-') {
+            removed.push(line);
+        } else if let Some(line) = line.strip_prefix('+') {
+            added.push(line);
+        }
+    }
+    (removed, added)
+}
+
+/// Kind and name of a public item declared on `line`
+fn public_item(line: &str) -> Option<(&str, &str)> {
+    let captures = PUBLIC_ITEM.captures(line)?;
+    let kind = captures.get(1).or_else(|| captures.get(2))?.as_str();
+    let kind = match kind.trim_end_matches('*') {
+        "function" => "fn",
+        kind => kind,
+    };
+    Some((kind, captures.get(3)?.as_str()))
+}
+
+/// Long flag names defined on consecutive lines
+fn flags(lines: &[&str]) -> Vec<String> {
+    let mut flags = Vec::new();
+    let mut pending_long = false;
+    for line in lines {
+        for captures in FLAG_LITERAL.captures_iter(line) {
+            if let Some(flag) = captures.get(1).or_else(|| captures.get(2)) {
+                flags.push(flag.as_str().to_string());
+            }
+        }
+        if CLAP_LONG.is_match(line) {
+            pending_long = true;
+        } else if pending_long && !line.trim_start().starts_with("#[") {
+            if let Some(field) = FIELD.captures(line).and_then(|c| c.get(1)) {
+                flags.push(field.as_str().replace('_', "-"));
+            }
+            pending_long = false;
+        }
+    }
+    flags
+}
+
+/// `(major, minor)` of the package version before and after a manifest change
+///
+/// Only the package's own version counts: a `version` key at the top level
+/// or in a [`PACKAGE_TABLES`] table in TOML, and the top-level `"version"`
+/// in package.json. Version lines whose table or object the hunk doesn't
+/// show are skipped.
+fn manifest_versions(text: &str, json: bool) -> (Option<Version>, Option<Version>) {
+    let mut old = ManifestSide::default();
+    let mut new = ManifestSide::default();
+    let mut in_hunk = false;
+    for line in text.lines() {
+        if let Some(captures) = HUNK_HEADER.captures(line) {
+            old.start_hunk(&captures[1]);
+            new.start_hunk(&captures[2]);
+            in_hunk = true;
+            continue;
+        }
+        if !in_hunk {
+            continue;
+        }
+        match line.split_at_checked(1) {
+            Some((" ", line)) => {
+                old.read(line, json);
+                new.read(line, json);
+            }
+            Some(("-", line)) => old.read(line, json),
+            Some(("+", line)) => new.read(line, json),
+            _ => {}
+        }
+    }
+    (old.version, new.version)
+}
+
+/// Position in one side (old or new) of a manifest while reading a diff
+#[derive(Default)]
+struct ManifestSide {
+    /// Current TOML table (`None` while unknown)
+    table: Option<String>,
+    /// Current JSON object depth (`None` while unknown)
+    depth: Option<usize>,
+    version: Option<Version>,
+}
+
+impl ManifestSide {
+    /// A hunk starting at line `start` is only placed if it starts the file
+    fn start_hunk(&mut self, start: &str) {
+        let at_top = matches!(start, "0" | "1");
+        self.table = at_top.then(String::new);
+        self.depth = at_top.then_some(0);
+    }
+
+    fn read(&mut self, line: &str, json: bool) {
+        let is_package = if json {
+            self.depth == Some(1)
+        } else {
+            self.table
+                .as_deref()
+                .is_some_and(|table| PACKAGE_TABLES.contains(&table))
+        };
+        if is_package
+            && self.version.is_none()
+            && let Some(captures) = MANIFEST_VERSION.captures(line)
+        {
+            self.version = captures[1].parse().ok().zip(captures[2].parse().ok());
+        }
+
+        if json {
+            self.depth = self.depth.map(|depth| json_depth(depth, line));
+        } else if let Some(captures) = TOML_TABLE.captures(line) {
+            self.table = Some(captures[1].to_string());
+        }
+    }
+}
+
+/// Object depth after a JSON line, ignoring braces in strings
+fn json_depth(mut depth: usize, line: &str) -> usize {
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in line.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn staged(diff: &str) -> StagedDiff {
+        StagedDiff {
+            stat: String::new(),
+            name_status: String::new(),
+            diff: diff.to_string(),
+            omitted: Vec::new(),
+        }
+    }
+
+    fn detect(diff: &str) -> Vec<String> {
+        detect_breaking_changes(&staged(diff))
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn flags_removed_public_items() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,4 +1,2 @@\n-pub fn parse(input: &str) -> Ast {\n-pub(crate) fn helper() {}\n-pub struct Token;\n fn private() {}\ndiff --git a/web/api.ts b/web/api.ts\n@@ -1 +0,0 @@\n-export async function fetchUser(id: string) {\n";
+
+        assert_eq!(
+            detect(diff),
+            [
+                "removed public fn `parse` in src/lib.rs",
+                "removed public struct `Token` in src/lib.rs",
+                "removed public fn `fetchUser` in web/api.ts",
+            ]
+        );
+    }
+
+    #[test]
+    fn moved_items_are_not_flagged_but_changed_signatures_are() {
+        let diff = "diff --git a/src/a.rs b/src/a.rs\n@@ -1,2 +1 @@\n-pub struct Moved;\n-pub fn run(x: u8) {\n+pub fn run(x: u8, y: u8) {\ndiff --git a/src/b.rs b/src/b.rs\n@@ -0,0 +1 @@\n+pub struct Moved;\n";
+
+        assert_eq!(
+            detect(diff),
+            ["changed signature of public fn `run` in src/a.rs"]
+        );
+
+        let unchanged = "diff --git a/src/a.rs b/src/a.rs\n@@ -1 +1 @@\n-pub fn run(x: u8) {\n+    pub fn run(x: u8) {\n";
+        assert!(detect(unchanged).is_empty());
+    }
+
+    #[test]
+    fn flags_removed_cli_flags() {
+        let diff = "diff --git a/src/cli.rs b/src/cli.rs\n@@ -1,6 +1,3 @@\n     /// Preview only\n-    #[arg(long, global = true)]\n-    pub dry_run: bool,\n-    #[arg(long = \"out-dir\")]\n-    out: PathBuf,\n     #[arg(long)]\n+    pub message_only: bool,\ndiff --git a/cli.py b/cli.py\n@@ -1 +1 @@\n-parser.add_argument(\"--verbose\")\n+parser.add_argument(\"--verbose\", action=\"store_true\")\n";
+
+        assert_eq!(
+            detect(diff),
+            [
+                "removed command-line flag `--dry-run` in src/cli.rs",
+                "removed command-line flag `--out-dir` in src/cli.rs",
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_major_manifest_bumps() {
+        let cargo = "diff --git a/Cargo.toml b/Cargo.toml\n@@ -1,4 +1,4 @@\n [package]\n name = \"app\"\n-version = \"1.4.2\"\n+version = \"2.0.0\"\n-serde = { version = \"1\" }\n+serde = { version = \"2\" }\n";
+        assert_eq!(detect(cargo), ["bumped version 1.4 → 2.0 in Cargo.toml"]);
+
+        let zero = "diff --git a/app/package.json b/app/package.json\n@@ -1,3 +1,3 @@\n {\n   \"name\": \"app\",\n-  \"version\": \"0.3.1\",\n+  \"version\": \"0.4.0\",\n";
+        assert_eq!(
+            detect(zero),
+            ["bumped version 0.3 → 0.4 in app/package.json"]
+        );
+
+        let minor = "diff --git a/Cargo.toml b/Cargo.toml\n@@ -1,2 +1,2 @@\n [package]\n-version = \"1.4.2\"\n+version = \"1.5.0\"\n";
+        assert!(detect(minor).is_empty());
+    }
+
+    #[test]
+    fn ignores_versions_outside_the_package() {
+        let dependency = "diff --git a/Cargo.toml b/Cargo.toml\n@@ -10,3 +10,3 @@\n [dependencies.serde]\n-version = \"1.0\"\n+version = \"2.0\"\n";
+        assert!(detect(dependency).is_empty());
+
+        // The hunk doesn't show which table the line is in
+        let unplaced = "diff --git a/pyproject.toml b/pyproject.toml\n@@ -12 +12 @@\n-version = \"1.0\"\n+version = \"2.0\"\n";
+        assert!(detect(unplaced).is_empty());
+
+        let project = "diff --git a/pyproject.toml b/pyproject.toml\n@@ -8,3 +8,3 @@\n [project]\n-version = \"1.0\"\n+version = \"2.0\"\n";
+        assert_eq!(
+            detect(project),
+            ["bumped version 1.0 → 2.0 in pyproject.toml"]
+        );
+
+        let nested = "diff --git a/package.json b/package.json\n@@ -1,4 +1,4 @@\n {\n   \"engines\": {\n-    \"version\": \"1.0.0\"\n+    \"version\": \"2.0.0\"\n";
+        assert!(detect(nested).is_empty());
+    }
+
+    #[test]
+    fn reports_each_change_once() {
+        let diff = "diff --git a/src/cli.rs b/src/cli.rs\n@@ -1,4 +1 @@\n-    #[arg(long = \"out-dir\")]\n-    #[arg(long = \"verbose\")]\n     out: PathBuf,\n-    // alias: \"--out-dir\"\n";
+
+        assert_eq!(
+            detect(diff),
+            [
+                "removed command-line flag `--out-dir` in src/cli.rs",
+                "removed command-line flag `--verbose` in src/cli.rs",
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_check_mode() {
+        assert_eq!("Suggest".parse(), Ok(BreakingCheck::Suggest));
+        assert_eq!(BreakingCheck::default().to_string(), "suggest");
+        assert!("maybe".parse::<BreakingCheck>().is_err());
+    }
+}
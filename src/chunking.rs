@@ -0,0 +1,515 @@
+use crate::agents::{AgentExecutor, clean_ai_response};
+use crate::config::Config;
+use crate::diff::{FileDiff, split_files};
+use crate::error::AgentError;
+use crate::prompt::{budget_diff, build_prompt_overview, estimate_tokens, fair_shares};
+use crate::retry;
+use crate::types::{AgentName, ConventionalCommit, StagedDiff};
+use futures_util::stream::{self, StreamExt};
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Default number of chunk summaries requested at once
+pub(crate) const SUMMARY_CONCURRENCY: usize = 4;
+
+/// How a diff larger than the agent's token budget is handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffStrategy {
+    /// Drop hunks to fit the budget (see [`budget_diff`])
+    #[default]
+    Truncate,
+    /// Summarize chunks of the diff separately, then write the message from
+    /// the summaries
+    Summarize,
+}
+
+impl FromStr for DiffStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "truncate" => Ok(Self::Truncate),
+            "summarize" => Ok(Self::Summarize),
+            _ => Err(format!(
+                "unknown diff strategy `{}` (expected truncate or summarize)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DiffStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncate => write!(f, "truncate"),
+            Self::Summarize => write!(f, "summarize"),
+        }
+    }
+}
+
+/// Part of a diff summarized in one agent call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    /// Files or directories covered, e.g. `src/agents/` or `README.md`
+    pub label: String,
+    /// Diff text for those files
+    pub diff: String,
+}
+
+/// A chunk's summary, as returned by the agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkSummary {
+    pub label: String,
+    pub summary: String,
+}
+
+/// Whether `diff` should go through map-reduce summarization for `agent`
+///
+/// Only with the summarize strategy, and only when the diff is over the
+/// agent's budget; smaller diffs are sent whole as usual.
+pub fn should_summarize(diff: &StagedDiff, config: &Config, agent: &AgentName) -> bool {
+    config.strategy == DiffStrategy::Summarize
+        && estimate_tokens(&diff.diff) > config.diff_token_budget(agent)
+}
+
+/// Split a diff into chunks of at most roughly `budget` tokens
+///
+/// Files in the same directory are kept together when they fit; larger
+/// directories are split per file, and a file over the budget is cut down
+/// with [`budget_diff`]. Adjacent small pieces are then packed into one
+/// chunk to save agent calls.
+pub fn chunk_diff(diff: &str, budget: usize) -> Vec<Chunk> {
+    // Git lists paths in sorted order, so a directory's files are adjacent
+    let mut groups: Vec<(String, Vec<FileDiff>)> = Vec::new();
+    for file in split_files(diff) {
+        let dir = file.path.map(parent_dir).unwrap_or_default().to_string();
+        match groups.last_mut() {
+            Some((last, files)) if *last == dir => files.push(file),
+            _ => groups.push((dir, vec![file])),
+        }
+    }
+
+    let mut pieces: Vec<Chunk> = Vec::new();
+    for (dir, files) in groups {
+        let text: String = files.iter().map(|file| file.text).collect();
+        if files.len() > 1 && estimate_tokens(&text) <= budget {
+            let label = if dir.is_empty() {
+                "./".to_string()
+            } else {
+                format!("{}/", dir)
+            };
+            pieces.push(Chunk { label, diff: text });
+            continue;
+        }
+        for file in files {
+            pieces.push(Chunk {
+                label: file.path.unwrap_or("diff").to_string(),
+                diff: budget_diff(file.text, budget),
+            });
+        }
+    }
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+    for piece in pieces {
+        match chunks.last_mut() {
+            Some(last) if estimate_tokens(&last.diff) + estimate_tokens(&piece.diff) <= budget => {
+                last.label.push_str(", ");
+                last.label.push_str(&piece.label);
+                last.diff.push_str(&piece.diff);
+            }
+            _ => chunks.push(piece),
+        }
+    }
+    chunks
+}
+
+/// Directory part of a repo-relative path (`""` at the root)
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+/// Prompt asking for a short summary of one chunk (the map step)
+pub fn build_chunk_prompt(chunk: &Chunk) -> String {
+    let mut prompt = String::new();
+
+    prompt.push_str("You are summarizing one part of a large change so that a commit message\n");
+    prompt.push_str("can be written for the whole change later.\n\n");
+    prompt.push_str("Requirements:\n");
+    prompt.push_str("1. Write 1-4 short bullet points (use - prefix) describing what changed\n");
+    prompt.push_str("2. Focus on behavior and intent, not line-by-line edits\n");
+    prompt.push_str("3. Mention renamed, added or removed public items by name\n");
+    prompt.push_str("4. DO NOT write a commit message or a title\n\n");
+
+    prompt.push_str("Return ONLY the summary between these markers:\n");
+    prompt.push_str("<<<COMMIT_MESSAGE_START>>>\n");
+    prompt.push_str("(summary goes here)\n");
+    prompt.push_str("<<<COMMIT_MESSAGE_END>>>\n\n");
+
+    prompt.push_str("=== FILES ===\n");
+    prompt.push_str(&chunk.label);
+    prompt.push_str("\n\n");
+
+    prompt.push_str("=== DIFF ===\n");
+    prompt.push_str(&chunk.diff);
+    if !chunk.diff.ends_with('\n') {
+        prompt.push('\n');
+    }
+
+    prompt
+}
+
+/// Final prompt writing the commit message from chunk summaries (the reduce step)
+///
+/// Same instructions, examples and change overview as the regular prompt,
+/// with the summaries in place of the diff. Together the summaries get
+/// `agent`'s diff budget, shared fairly between them: many chunks can add
+/// up to more than the diff that was too large to send.
+pub fn build_reduce_prompt(
+    diff: &StagedDiff,
+    config: &Config,
+    agent: &AgentName,
+    examples: &[ConventionalCommit],
+    summaries: &[ChunkSummary],
+) -> String {
+    let mut prompt = build_prompt_overview(diff, config, examples);
+
+    prompt.push_str("=== CHANGE SUMMARIES ===\n");
+    prompt.push_str("The full diff is too large to include. Each part of it was summarized\n");
+    prompt.push_str("separately; base the commit message on these summaries.\n\n");
+
+    let labels: usize = summaries
+        .iter()
+        .map(|summary| estimate_tokens(&summary.label) + 1)
+        .sum();
+    let needs: Vec<usize> = summaries
+        .iter()
+        .map(|summary| estimate_tokens(&summary.summary))
+        .collect();
+    let shares = fair_shares(
+        &needs,
+        config.diff_token_budget(agent).saturating_sub(labels),
+    );
+    for (summary, share) in summaries.iter().zip(shares) {
+        prompt.push_str("### ");
+        prompt.push_str(&summary.label);
+        prompt.push('\n');
+        prompt.push_str(&budget_diff(&summary.summary, share));
+        prompt.push_str("\n\n");
+    }
+
+    prompt
+}
+
+/// Summarize every chunk of `diff`, up to `config.summary_concurrency` at a time
+///
+/// Summaries come back in diff order. `on_progress(done, total)` is called as
+/// each chunk finishes. Transient failures are retried with backoff up to
+/// `config.max_attempts` times per chunk.
+///
+/// # Errors
+///
+/// Returns the agent error for the first chunk that can't be summarized;
+/// an empty summary is reported as `AgentError::InvalidResponse`.
+pub async fn summarize_chunks(
+    agent: &impl AgentExecutor,
+    diff: &StagedDiff,
+    config: &Config,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<Vec<ChunkSummary>, AgentError> {
+    let chunks = chunk_diff(&diff.diff, config.diff_token_budget(&agent.name()));
+    let total = chunks.len();
+    on_progress(0, total);
+
+    let mut results = stream::iter(chunks)
+        .map(|chunk| summarize_chunk(agent, chunk, config))
+        .buffered(config.summary_concurrency.max(1));
+
+    let mut summaries = Vec::with_capacity(total);
+    while let Some(summary) = results.next().await {
+        summaries.push(summary?);
+        on_progress(summaries.len(), total);
+    }
+    Ok(summaries)
+}
+
+async fn summarize_chunk(
+    agent: &impl AgentExecutor,
+    chunk: Chunk,
+    config: &Config,
+) -> Result<ChunkSummary, AgentError> {
+    let prompt = build_chunk_prompt(&chunk);
+    let max_attempts = config.max_attempts.max(1);
+    let mut number = 1;
+
+    let raw = loop {
+        match agent.execute(&prompt).await {
+            Ok(raw) => break raw,
+            Err(error)
+                if number < max_attempts && retry::is_retryable(&error, config.retry_timeouts) =>
+            {
+                tokio::time::sleep(retry::backoff_delay(config.retry_backoff(), number)).await;
+                number += 1;
+            }
+            Err(error) => return Err(error),
+        }
+    };
+
+    let summary = clean_ai_response(&raw);
+    if summary.is_empty() {
+        return Err(AgentError::InvalidResponse {
+            reason: format!("empty summary for {}", chunk.label),
+        });
+    }
+    Ok(ChunkSummary {
+        label: chunk.label,
+        summary,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn file(path: &str, lines: usize) -> String {
+        let mut text = format!(
+            "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n@@ -0,0 +1,{lines} @@\n"
+        );
+        for i in 0..lines {
+            text.push_str(&format!("+{:<38}\n", format!("line {}", i)));
+        }
+        text
+    }
+
+    fn staged(diff: String) -> StagedDiff {
+        StagedDiff {
+            stat: String::new(),
+            name_status: String::new(),
+            diff,
+            omitted: Vec::new(),
+        }
+    }
+
+    /// Agent that echoes the chunk label back and tracks how many calls overlap
+    struct SummaryAgent {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+        prompts: Mutex<Vec<String>>,
+    }
+
+    impl SummaryAgent {
+        fn new() -> Self {
+            Self {
+                in_flight: AtomicUsize::new(0),
+                max_in_flight: AtomicUsize::new(0),
+                prompts: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl AgentExecutor for SummaryAgent {
+        async fn execute(&self, prompt: &str) -> Result<String, AgentError> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let label = prompt
+                .split("=== FILES ===\n")
+                .nth(1)
+                .and_then(|rest| rest.lines().next())
+                .unwrap_or_default();
+            Ok(format!(
+                "<<<COMMIT_MESSAGE_START>>>\n- changed {}\n<<<COMMIT_MESSAGE_END>>>",
+                label
+            ))
+        }
+
+        fn name(&self) -> AgentName {
+            AgentName::Custom("llm".to_string())
+        }
+    }
+
+    #[test]
+    fn parses_strategy() {
+        assert_eq!(
+            "Summarize".parse::<DiffStrategy>(),
+            Ok(DiffStrategy::Summarize)
+        );
+        assert_eq!(DiffStrategy::default().to_string(), "truncate");
+        assert!("map-reduce".parse::<DiffStrategy>().is_err());
+    }
+
+    #[test]
+    fn chunks_keep_directories_together() {
+        let diff = file("src/a.rs", 10) + &file("src/b.rs", 10) + &file("tests/t.rs", 10);
+
+        let chunks = chunk_diff(&diff, 250);
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].label, "src/");
+        assert_eq!(chunks[0].diff, file("src/a.rs", 10) + &file("src/b.rs", 10));
+        assert_eq!(chunks[1].label, "tests/t.rs");
+    }
+
+    #[test]
+    fn large_directories_split_per_file() {
+        let diff = file("src/a.rs", 30) + &file("src/b.rs", 30);
+
+        let chunks = chunk_diff(&diff, 400);
+
+        let labels: Vec<&str> = chunks.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, ["src/a.rs", "src/b.rs"]);
+    }
+
+    #[test]
+    fn oversized_files_are_budgeted() {
+        let chunks = chunk_diff(&file("src/huge.rs", 200), 300);
+
+        assert_eq!(chunks.len(), 1);
+        assert!(estimate_tokens(&chunks[0].diff) <= 300);
+        assert!(chunks[0].diff.contains("hunk truncated"));
+    }
+
+    #[test]
+    fn small_pieces_are_packed_together() {
+        let diff = file("README.md", 2) + &file("docs/x.md", 2) + &file("src/a.rs", 2);
+
+        let chunks = chunk_diff(&diff, 1000);
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].label, "README.md, docs/x.md, src/a.rs");
+        assert_eq!(chunks[0].diff, diff);
+    }
+
+    #[test]
+    fn reduce_prompt_lists_summaries_instead_of_diff() {
+        let prompt = build_reduce_prompt(
+            &staged(String::new()),
+            &Config::default(),
+            &AgentName::Claude,
+            &[],
+            &[ChunkSummary {
+                label: "src/".to_string(),
+                summary: "- add parser".to_string(),
+            }],
+        );
+
+        assert!(prompt.contains("<<<COMMIT_MESSAGE_START>>>"));
+        assert!(prompt.contains("=== CHANGE SUMMARIES ===\n"));
+        assert!(prompt.contains("### src/\n- add parser\n"));
+        assert!(!prompt.contains("=== FULL DIFF ==="));
+    }
+
+    #[test]
+    fn reduce_prompt_fits_summaries_in_the_budget() {
+        let config = Config {
+            max_diff_tokens: Some(100),
+            ..Config::default()
+        };
+        let summaries: Vec<ChunkSummary> = (0..4)
+            .map(|i| ChunkSummary {
+                label: format!("src/{}.rs", i),
+                summary: format!("- {}", "x".repeat(if i == 0 { 20 } else { 400 })),
+            })
+            .collect();
+
+        let prompt = build_reduce_prompt(
+            &staged(String::new()),
+            &config,
+            &AgentName::Claude,
+            &[],
+            &summaries,
+        );
+        let listed = &prompt[prompt.find("### ").unwrap()..];
+        assert!(estimate_tokens(listed) <= 100 + 4 * 10);
+        // Short summaries are kept whole, long ones cut
+        assert!(listed.contains(&summaries[0].summary));
+        assert_eq!(listed.matches("### ").count(), 4);
+        assert_eq!(listed.matches("(diff truncated)").count(), 3);
+    }
+
+    #[test]
+    fn should_summarize_only_when_opted_in_and_over_budget() {
+        let diff = staged(file("src/a.rs", 100));
+        let agent = AgentName::Custom("llm".to_string());
+        let summarize = Config {
+            strategy: DiffStrategy::Summarize,
+            max_diff_tokens: Some(100),
+            ..Config::default()
+        };
+
+        assert!(should_summarize(&diff, &summarize, &agent));
+        assert!(!should_summarize(
+            &diff,
+            &Config {
+                strategy: DiffStrategy::Truncate,
+                ..summarize.clone()
+            },
+            &agent
+        ));
+        assert!(!should_summarize(
+            &staged(file("src/a.rs", 2)),
+            &summarize,
+            &agent
+        ));
+    }
+
+    #[tokio::test]
+    async fn summarizes_chunks_in_order_under_concurrency_limit() {
+        let diff: String = (0..6).map(|i| file(&format!("d{}/f.rs", i), 30)).collect();
+        let config = Config {
+            max_diff_tokens: Some(400),
+            summary_concurrency: 2,
+            ..Config::default()
+        };
+        let agent = SummaryAgent::new();
+        let mut progress = Vec::new();
+
+        let summaries = summarize_chunks(&agent, &staged(diff), &config, |done, total| {
+            progress.push((done, total))
+        })
+        .await
+        .unwrap();
+
+        let labels: Vec<&str> = summaries.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            [
+                "d0/f.rs", "d1/f.rs", "d2/f.rs", "d3/f.rs", "d4/f.rs", "d5/f.rs"
+            ]
+        );
+        assert_eq!(summaries[2].summary, "- changed d2/f.rs");
+        assert_eq!(agent.max_in_flight.load(Ordering::SeqCst), 2);
+        assert_eq!(progress.first(), Some(&(0, 6)));
+        assert_eq!(progress.last(), Some(&(6, 6)));
+    }
+
+    #[tokio::test]
+    async fn empty_summary_is_an_error() {
+        struct EmptyAgent;
+        impl AgentExecutor for EmptyAgent {
+            async fn execute(&self, _prompt: &str) -> Result<String, AgentError> {
+                Ok("   ".to_string())
+            }
+            fn name(&self) -> AgentName {
+                AgentName::Claude
+            }
+        }
+
+        let result = summarize_chunks(
+            &EmptyAgent,
+            &staged(file("src/a.rs", 2)),
+            &Config::default(),
+            |_, _| {},
+        )
+        .await;
+
+        assert!(matches!(result, Err(AgentError::InvalidResponse { .. })));
+    }
+}
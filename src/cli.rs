@@ -1,12 +1,20 @@
-use crate::agents::Agent;
+use crate::agents::{AgentExecutor, AgentGroup, AgentMode, streamed_message};
+use crate::chunking::DiffStrategy;
+use crate::config::{Config, ConfigLayer, LoadedConfig, load_config};
 use crate::error::{AgentError, GeneratorError, GitError};
 use crate::git::{GitProvider, RealGitProvider};
-use crate::hooks::{HookManager, detect_hook_manager, install_hook};
-use crate::types::{AgentName, commit_types_display};
+use crate::hooks::{HookManager, detect_hook_manager, install_hook, install_lint_hook};
+use crate::lint::{LintReport, Severity, lint_message_with_comment_char};
+use crate::redact::{RedactionMode, RedactionReport};
+use crate::retry::Progress;
+use crate::review::{GuidedAgent, ReviewAction, edit_message, editor_command};
+use crate::scope::ScopeMap;
+use crate::types::{AgentName, CommitRules, ConventionalCommit, commit_types_display};
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use console::style;
+use console::{Term, style};
 use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
 /// AI-powered commit message generator
@@ -17,9 +25,44 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Command>,
 
-    /// AI agent to use for generation (claude, codex, gemini)
-    #[arg(long, default_value_t = AgentName::default_agent(), global = true)]
-    pub agent: AgentName,
+    /// AI agent to use for generation (claude, codex, gemini, or a custom agent) [default: claude]
+    ///
+    /// A comma-separated list is tried in order: the next agent is asked if
+    /// one isn't installed, times out, or returns an invalid response.
+    #[arg(long, value_name = "AGENTS", value_delimiter = ',', global = true)]
+    pub agent: Vec<AgentName>,
+
+    /// How a list of agents is used: fallback (in turn), race (first valid message), or rank (best message) [default: fallback]
+    #[arg(long, value_name = "MODE", global = true)]
+    pub agent_mode: Option<AgentMode>,
+
+    /// Agent timeout in seconds [default: 120]
+    #[arg(long, value_name = "SECS", global = true)]
+    pub timeout: Option<u64>,
+
+    /// Model for the agent (e.g. `haiku`) [default: the agent's own default]
+    #[arg(long, value_name = "MODEL", global = true)]
+    pub model: Option<String>,
+
+    /// Extra argument for the agent's command; repeat for more (`--agent-arg=--max-turns --agent-arg=1`)
+    #[arg(long, value_name = "ARG", allow_hyphen_values = true, global = true)]
+    pub agent_arg: Vec<String>,
+
+    /// Maximum agent calls per generation, including repairs and retries [default: 3]
+    #[arg(long, value_name = "N", global = true)]
+    pub max_attempts: Option<u32>,
+
+    /// Secrets in the staged diff: mask, strict (refuse to generate), or off [default: mask]
+    #[arg(long, value_name = "MODE", global = true)]
+    pub redaction: Option<RedactionMode>,
+
+    /// Summarize large diffs chunk by chunk instead of truncating them
+    #[arg(long, global = true)]
+    pub summarize: bool,
+
+    /// Don't append the agent signature to the message
+    #[arg(long, global = true)]
+    pub no_signature: bool,
 
     /// Generate message without committing
     #[arg(long, global = true)]
@@ -29,6 +72,33 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub message_only: bool,
 
+    /// Review the message before committing: accept, edit, regenerate, or switch agent
+    #[arg(short, long, global = true, conflicts_with = "message_only")]
+    pub interactive: bool,
+
+    /// Generate N alternative messages and pick one from a numbered list
+    #[arg(
+        long,
+        value_name = "N",
+        global = true,
+        conflicts_with_all = ["interactive", "message_only"]
+    )]
+    pub candidates: Option<usize>,
+
+    /// Agents to ask for candidates, comma-separated [default: --agent]
+    #[arg(
+        long,
+        value_name = "AGENTS",
+        value_delimiter = ',',
+        global = true,
+        requires = "candidates"
+    )]
+    pub candidates_from: Vec<AgentName>,
+
+    /// Print the candidates as a JSON array instead of picking one
+    #[arg(long, global = true, requires = "candidates")]
+    pub json: bool,
+
     /// Suppress progress output
     #[arg(short, long, global = true)]
     pub quiet: bool,
@@ -50,26 +120,88 @@ pub enum Command {
         #[arg(long)]
         hook_manager: Option<String>,
 
-        /// Default agent for hooks (claude, codex, gemini)
-        #[arg(long, default_value_t = AgentName::default_agent())]
-        agent: AgentName,
+        /// Default agent for hooks (claude, codex, gemini, or a custom agent) [default: claude]
+        #[arg(long)]
+        agent: Option<AgentName>,
+
+        /// Install a commit-msg hook that lints messages instead of generating them
+        #[arg(long)]
+        lint: bool,
+    },
+
+    /// Check commit messages against the Conventional Commits format
+    Lint {
+        /// Commit message file (e.g. .git/COMMIT_EDITMSG), or `-` for stdin
+        #[arg(required_unless_present = "range", conflicts_with = "range")]
+        file: Option<PathBuf>,
+
+        /// Lint every commit in a git revision range (e.g. main..HEAD)
+        #[arg(long)]
+        range: Option<String>,
+    },
+
+    /// Inspect configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the merged configuration and where each value came from
+    Show,
+}
+
 impl Cli {
     /// Run the CLI application
     pub async fn run(self) -> Result<()> {
+        let cli_layer = self.config_layer();
+
         match self.command {
             Some(Command::Init {
                 hook_manager,
                 agent,
-            }) => run_init(hook_manager, agent).await,
+                lint,
+            }) => {
+                if lint {
+                    return run_init_lint(hook_manager);
+                }
+                let cli_layer = ConfigLayer {
+                    agent: agent.or(cli_layer.agent),
+                    ..cli_layer
+                };
+                let loaded = load_config(&self.cwd, cli_layer).context("Failed to load config")?;
+                run_init(hook_manager, &loaded.config).await
+            }
+            Some(Command::Lint { file, range }) => {
+                let loaded = load_config(&self.cwd, cli_layer).context("Failed to load config")?;
+                run_lint(LintArgs {
+                    config: loaded.config,
+                    file,
+                    range,
+                    quiet: self.quiet,
+                    cwd: self.cwd,
+                })
+            }
+            Some(Command::Config {
+                command: ConfigCommand::Show,
+            }) => {
+                let loaded = load_config(&self.cwd, cli_layer).context("Failed to load config")?;
+                run_config_show(&loaded);
+                Ok(())
+            }
             None => {
                 // Default command: generate
+                let loaded = load_config(&self.cwd, cli_layer).context("Failed to load config")?;
                 run_generate(GenerateArgs {
-                    agent: self.agent,
+                    config: loaded.config,
                     dry_run: self.dry_run,
                     message_only: self.message_only,
+                    interactive: self.interactive,
+                    candidates: self.candidates,
+                    candidates_from: self.candidates_from,
+                    json: self.json,
                     quiet: self.quiet,
                     verbose: self.verbose,
                     cwd: self.cwd,
@@ -78,14 +210,43 @@ impl Cli {
             }
         }
     }
+
+    /// Config values set by command-line flags (highest precedence layer)
+    fn config_layer(&self) -> ConfigLayer {
+        ConfigLayer {
+            agent: self.agent.first().cloned(),
+            // A single `--agent` (as installed hooks pass) keeps the configured
+            // fallbacks; a list replaces them
+            fallback_agents: self
+                .agent
+                .get(1..)
+                .filter(|rest| !rest.is_empty())
+                .map(<[AgentName]>::to_vec),
+            agent_mode: self.agent_mode,
+            timeout_secs: self.timeout,
+            model: self.model.clone(),
+            agent_args: (!self.agent_arg.is_empty()).then(|| self.agent_arg.clone()),
+            max_attempts: self.max_attempts,
+            redaction: self.redaction,
+            strategy: self.summarize.then_some(DiffStrategy::Summarize),
+            signature: self.no_signature.then_some(false),
+            ..ConfigLayer::default()
+        }
+    }
 }
 
 /// Arguments for generate command
 #[derive(Debug)]
 pub struct GenerateArgs {
-    pub agent: AgentName,
+    pub config: Config,
     pub dry_run: bool,
     pub message_only: bool,
+    pub interactive: bool,
+    /// Number of alternative messages to generate (`--candidates`)
+    pub candidates: Option<usize>,
+    /// Agents asked for candidates (empty: the configured agent)
+    pub candidates_from: Vec<AgentName>,
+    pub json: bool,
     pub quiet: bool,
     pub verbose: bool,
     pub cwd: PathBuf,
@@ -93,10 +254,33 @@ pub struct GenerateArgs {
 
 /// Run the generate command
 pub async fn run_generate(args: GenerateArgs) -> Result<()> {
-    let agent_name = args.agent;
+    let agent_name = args.config.agent.clone();
 
     if args.verbose {
-        eprintln!("{} Using agent: {}", style("debug:").cyan(), agent_name);
+        let agents: Vec<String> = args
+            .config
+            .agent_chain()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let agents = match args.config.agent_mode {
+            AgentMode::Fallback => agents.join(" → "),
+            mode => format!("{} ({})", agents.join(", "), mode),
+        };
+        eprintln!("{} Using agent: {}", style("debug:").cyan(), agents);
+        if let Some(model) = args.config.agent_model(&agent_name) {
+            eprintln!("{} Model: {}", style("debug:").cyan(), model);
+        }
+        eprintln!(
+            "{} Timeout: {}s",
+            style("debug:").cyan(),
+            args.config.timeout_secs
+        );
+        eprintln!(
+            "{} Max attempts: {}",
+            style("debug:").cyan(),
+            args.config.max_attempts
+        );
         eprintln!(
             "{} Working directory: {}",
             style("debug:").cyan(),
@@ -104,14 +288,31 @@ pub async fn run_generate(args: GenerateArgs) -> Result<()> {
         );
     }
 
+    // The review loop and candidate picker read from the terminal
+    let is_terminal = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    if args.interactive && !is_terminal {
+        anyhow::bail!("--interactive needs a terminal");
+    }
+    if args.candidates.is_some() && !args.json && !is_terminal {
+        anyhow::bail!("--candidates needs a terminal to pick from (use --json to print them)");
+    }
+    if args.candidates == Some(0) {
+        anyhow::bail!("--candidates must be at least 1");
+    }
+    for name in &args.candidates_from {
+        if let Err(reason) = args.config.check_agent(name) {
+            anyhow::bail!("--candidates-from {}: {}", name, reason);
+        }
+    }
+
     // Create git provider
     let git = RealGitProvider::new(args.cwd.clone());
 
     // Create agent
-    let agent = Agent::from(agent_name);
+    let agent = AgentGroup::from_config(&args.config);
 
-    // Generate default signature based on agent
-    let signature = agent_name.commit_signature();
+    // Render the signature for this agent (unless disabled)
+    let signature = args.config.signature_for(&agent_name);
 
     // Get staged diff for display
     let diff = git.get_staged_diff().map_err(|e| match e {
@@ -121,85 +322,451 @@ pub async fn run_generate(args: GenerateArgs) -> Result<()> {
         other => anyhow::anyhow!("Failed to get staged diff: {}", other),
     })?;
 
-    // Display staged files before spinner (respecting flags)
+    // Display staged files before spinner (respecting flags); masked
+    // secrets are reported by generation
     if !args.quiet && !args.message_only {
         display_staged_files(&diff.name_status);
     }
 
-    // Show spinner unless quiet or message-only mode
-    let spinner = if !args.quiet && !args.message_only {
-        let pb = ProgressBar::new_spinner();
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ")
-                .template("{spinner:.cyan} {msg}")
-                .expect("valid template"),
-        );
-        pb.set_message("Generating commit message...");
-        pb.enable_steady_tick(std::time::Duration::from_millis(80));
-        Some(pb)
+    if let Some(count) = args.candidates {
+        return run_candidates(&git, count, &args).await;
+    }
+
+    let commit = match generate_with_spinner(
+        &git,
+        &agent,
+        signature.as_deref(),
+        &args.config,
+        &args,
+    )
+    .await
+    {
+        Ok(commit) => commit,
+        Err(e) => {
+            format_error(&e, &args);
+            return Err(anyhow::anyhow!("generation failed"));
+        }
+    };
+
+    // Let the user accept, edit, or regenerate before anything is committed
+    let commit = if args.interactive {
+        match review_message(&git, commit, &args).await? {
+            Some(commit) => commit,
+            None => {
+                if !args.quiet {
+                    eprintln!("{} Aborted, nothing committed", style("→").blue());
+                }
+                anyhow::bail!("aborted");
+            }
+        }
     } else {
-        None
+        commit
     };
 
-    // Generate commit message
-    let result = crate::generate_commit_message(&git, &agent, Some(&signature)).await;
+    output_commit(&git, &commit, &args)
+}
+
+/// Print or commit the final message according to the output flags
+fn output_commit(
+    git: &RealGitProvider,
+    commit: &ConventionalCommit,
+    args: &GenerateArgs,
+) -> Result<()> {
+    if args.message_only {
+        // Just print raw message for piping
+        println!("{}", commit.as_str());
+    } else if args.dry_run {
+        // Print formatted message without committing
+        if !args.quiet {
+            eprintln!("{} Generated commit message:", style("✓").green().bold());
+            eprintln!();
+        }
+        println!("{}", commit.as_str());
+        if !args.quiet {
+            eprintln!();
+            eprintln!("{} Run without --dry-run to commit", style("→").blue());
+        }
+    } else {
+        // Commit with generated message
+        git.commit(commit.as_str())
+            .context("Failed to create commit")?;
+
+        if !args.quiet {
+            eprintln!("{} Commit created successfully", style("✓").green().bold());
+            eprintln!();
+            println!("{}", commit.as_str());
+        }
+    }
+    Ok(())
+}
+
+/// Start a progress spinner unless quiet or message-only mode
+fn start_spinner(args: &GenerateArgs, message: String) -> Option<ProgressBar> {
+    if args.quiet || args.message_only {
+        return None;
+    }
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::default_spinner()
+            .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ")
+            .template("{spinner:.cyan} {msg}")
+            .expect("valid template"),
+    );
+    pb.set_message(message);
+    pb.enable_steady_tick(std::time::Duration::from_millis(80));
+    Some(pb)
+}
+
+/// Lines of the forming message shown under the spinner
+const PREVIEW_LINES: usize = 8;
+
+/// Spinner text while the agent's response streams in: the last lines of
+/// the message so far, cut to the terminal width
+fn streaming_preview(base: &str, response: &str) -> String {
+    let width = usize::from(Term::stderr().size().1)
+        .saturating_sub(4)
+        .max(20);
+    let lines: Vec<&str> = streamed_message(response).trim().lines().collect();
+    let mut preview = base.to_string();
+    for line in &lines[lines.len().saturating_sub(PREVIEW_LINES)..] {
+        preview.push_str("\n  ");
+        preview.push_str(&console::truncate_str(line, width, "…"));
+    }
+    preview
+}
+
+/// Generate a message behind a spinner, reporting retries in verbose mode
+async fn generate_with_spinner(
+    git: &RealGitProvider,
+    agent: &impl AgentExecutor,
+    signature: Option<&str>,
+    config: &Config,
+    args: &GenerateArgs,
+) -> Result<ConventionalCommit, GeneratorError> {
+    const GENERATING: &str = "Generating commit message...";
+    let spinner = start_spinner(args, GENERATING.to_string());
+
+    // Generate commit message, showing chunk progress and the message as it
+    // forms on the spinner, and reporting retries and repairs in verbose mode
+    let result =
+        crate::generate_commit_message_with_progress(git, agent, signature, config, |progress| {
+            if let Some(pb) = &spinner {
+                match &progress {
+                    Progress::Summarized { done, total } => pb.set_message(if done == total {
+                        "Generating commit message from summaries...".to_string()
+                    } else {
+                        format!("Summarizing changes ({}/{} chunks)...", done, total)
+                    }),
+                    Progress::Redacted(report) => pb.suspend(|| display_redactions(report)),
+                    Progress::Output(response) => {
+                        pb.set_message(streaming_preview(GENERATING, response))
+                    }
+                    // The next response streams in from the start
+                    Progress::Attempt(_) => pb.set_message(GENERATING),
+                }
+            }
+            let shown = match progress {
+                Progress::Output(_) => true,
+                Progress::Redacted(_) => spinner.is_some(),
+                _ => false,
+            };
+            if args.verbose && !shown {
+                let report = || eprintln!("{} {}", style("debug:").cyan(), progress);
+                match &spinner {
+                    Some(pb) => pb.suspend(report),
+                    None => report(),
+                }
+            }
+        })
+        .await;
 
     // Stop spinner
     if let Some(pb) = spinner {
         pb.finish_and_clear();
     }
 
-    // Handle result
-    match result {
-        Ok(commit) => {
-            if args.message_only {
-                // Just print raw message for piping
-                println!("{}", commit.as_str());
-            } else if args.dry_run {
-                // Print formatted message without committing
-                if !args.quiet {
-                    eprintln!("{} Generated commit message:", style("✓").green().bold());
-                    eprintln!();
+    let answered = agent.name();
+    if args.verbose && answered != config.agent {
+        let model = config
+            .agent_model(&answered)
+            .map(|model| format!(" ({})", model))
+            .unwrap_or_default();
+        eprintln!(
+            "{} Message from: {}{}",
+            style("debug:").cyan(),
+            answered,
+            model
+        );
+    }
+    result
+}
+
+/// Run `--candidates`: collect alternatives from each agent, then pick one
+///
+/// With `--json` the candidates are printed as an array and nothing is
+/// committed. An agent that fails is reported and skipped.
+async fn run_candidates(git: &RealGitProvider, count: usize, args: &GenerateArgs) -> Result<()> {
+    let agents = if args.candidates_from.is_empty() {
+        vec![args.config.agent.clone()]
+    } else {
+        args.candidates_from.clone()
+    };
+
+    let mut candidates: Vec<(AgentName, ConventionalCommit)> = Vec::new();
+    for (index, name) in agents.iter().enumerate() {
+        // Fallbacks only apply when asking the configured agent
+        let config = if args.candidates_from.is_empty() {
+            args.config.clone()
+        } else {
+            args.config.single_agent(name.clone())
+        };
+        let agent = AgentGroup::from_config(&config);
+        let signature = config.signature_for(name);
+
+        let spinner = start_spinner(
+            args,
+            format!("Generating {} candidate(s) with {}...", count, name),
+        );
+        // Every agent sees the same masked diff; report it once
+        let first = index == 0;
+        let result = crate::generate_candidates_with_progress(
+            git,
+            &agent,
+            signature.as_deref(),
+            &config,
+            count,
+            |progress| {
+                if let (Some(pb), Progress::Redacted(report)) = (&spinner, &progress)
+                    && first
+                {
+                    pb.suspend(|| display_redactions(report));
                 }
-                println!("{}", commit.as_str());
+            },
+        )
+        .await;
+        if let Some(pb) = spinner {
+            pb.finish_and_clear();
+        }
+
+        let answered = agent.name();
+        match result {
+            Ok(commits) => {
+                candidates.extend(commits.into_iter().map(|commit| (answered.clone(), commit)))
+            }
+            Err(e) => format_error(&e, args),
+        }
+    }
+
+    // Different agents often agree; show each header once
+    let candidates = crate::dedup_candidates(candidates, |(_, commit)| commit);
+    if candidates.is_empty() {
+        anyhow::bail!("generation failed");
+    }
+
+    if args.json {
+        let json: Vec<serde_json::Value> = candidates
+            .iter()
+            .map(|(agent, commit)| {
+                serde_json::json!({
+                    "agent": agent.to_string(),
+                    "header": commit.header(),
+                    "message": commit.as_str(),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json)?);
+        return Ok(());
+    }
+
+    display_candidates(&candidates, agents.len() > 1);
+    let term = Term::stderr();
+    let index = loop {
+        eprint!("Pick a message [1-{}], or q to abort: ", candidates.len());
+        let line = term.read_line().context("Failed to read from terminal")?;
+        match parse_pick(&line, candidates.len()) {
+            Some(Pick::Index(index)) => break index,
+            Some(Pick::Abort) => {
                 if !args.quiet {
-                    eprintln!();
-                    eprintln!("{} Run without --dry-run to commit", style("→").blue());
+                    eprintln!("{} Aborted, nothing committed", style("→").blue());
                 }
-            } else {
-                // Commit with generated message
-                git.commit(commit.as_str())
-                    .context("Failed to create commit")?;
+                anyhow::bail!("aborted");
+            }
+            None => continue,
+        }
+    };
 
-                if !args.quiet {
-                    eprintln!("{} Commit created successfully", style("✓").green().bold());
-                    eprintln!();
-                    println!("{}", commit.as_str());
+    output_commit(git, &candidates[index].1, args)
+}
+
+/// Print numbered candidates: header, then the body dimmed and indented
+fn display_candidates(candidates: &[(AgentName, ConventionalCommit)], show_agent: bool) {
+    for (i, (agent, commit)) in candidates.iter().enumerate() {
+        let number = style(format!("{:>2}.", i + 1)).cyan().bold();
+        if show_agent {
+            eprintln!("{} {}  {}", number, commit.header(), style(agent).dim());
+        } else {
+            eprintln!("{} {}", number, commit.header());
+        }
+        for line in commit.as_str().lines().skip(1) {
+            if !line.is_empty() {
+                eprintln!("    {}", style(line).dim());
+            }
+        }
+        eprintln!();
+    }
+}
+
+/// A choice typed at the candidate picker
+#[derive(Debug, PartialEq, Eq)]
+enum Pick {
+    /// 0-based index into the candidates
+    Index(usize),
+    Abort,
+}
+
+/// Parse picker input: a 1-based number in range, or `q` (`None` if invalid)
+fn parse_pick(input: &str, count: usize) -> Option<Pick> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("q") {
+        return Some(Pick::Abort);
+    }
+    match input.parse::<usize>() {
+        Ok(n) if (1..=count).contains(&n) => Some(Pick::Index(n - 1)),
+        _ => None,
+    }
+}
+
+/// Interactive review loop for `--interactive`
+///
+/// Shows the message and reads single-key actions until the user accepts or
+/// aborts. Returns `None` on abort. Whatever text is accepted, generated or
+/// hand-edited, is re-validated before it's returned for committing.
+async fn review_message(
+    git: &RealGitProvider,
+    commit: ConventionalCommit,
+    args: &GenerateArgs,
+) -> Result<Option<ConventionalCommit>> {
+    let term = Term::stderr();
+    let mut config = args.config.clone();
+    let mut guidance: Vec<String> = Vec::new();
+    let mut draft = commit.as_str().to_string();
+    // Edits must pass the same checks as generated messages
+    let rules = crate::staged_commit_rules(git, &config)?;
+
+    loop {
+        display_draft(&draft, &rules);
+        eprint!("{} ", style(ReviewAction::MENU).bold());
+        let key = term.read_char().context("Failed to read from terminal")?;
+        eprintln!();
+
+        let Some(action) = ReviewAction::from_key(key) else {
+            continue;
+        };
+        match action {
+            ReviewAction::Accept => match ConventionalCommit::validate_with(&draft, &rules) {
+                Ok(commit) => return Ok(Some(commit)),
+                Err(e) => {
+                    eprintln!("{} {}", style("error:").red().bold(), e);
+                    eprintln!("  Edit or regenerate the message before accepting");
+                }
+            },
+            ReviewAction::Edit => {
+                match edit_message(&draft, &editor_command()).context("Failed to run editor")? {
+                    Some(edited) => draft = edited,
+                    None => eprintln!(
+                        "{} Empty message, keeping the previous one",
+                        style("→").blue()
+                    ),
+                }
+            }
+            ReviewAction::Regenerate => {
+                eprint!("Guidance for the agent (optional): ");
+                let line = term.read_line().context("Failed to read from terminal")?;
+                if !line.trim().is_empty() {
+                    guidance.push(line.trim().to_string());
+                }
+                if let Some(commit) = regenerate(git, &config, &guidance, args).await {
+                    draft = commit.as_str().to_string();
+                }
+            }
+            ReviewAction::SwitchAgent => {
+                eprint!("Agent (current: {}): ", config.agent);
+                let line = term.read_line().context("Failed to read from terminal")?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let agent = line.trim().parse::<AgentName>().map_err(|e| e.to_string());
+                match agent.and_then(|agent| config.check_agent(&agent).map(|()| agent)) {
+                    Ok(agent) => {
+                        config = config.single_agent(agent);
+                        if let Some(commit) = regenerate(git, &config, &guidance, args).await {
+                            draft = commit.as_str().to_string();
+                        }
+                    }
+                    Err(e) => eprintln!("{} {}", style("error:").red().bold(), e),
                 }
             }
-            Ok(())
+            ReviewAction::Abort => return Ok(None),
         }
+    }
+}
+
+/// Generate a fresh message for the review loop
+///
+/// Failures are reported but not fatal, so the user can keep the previous
+/// message or try another agent.
+async fn regenerate(
+    git: &RealGitProvider,
+    config: &Config,
+    guidance: &[String],
+    args: &GenerateArgs,
+) -> Option<ConventionalCommit> {
+    let agent = AgentGroup::from_config(config);
+    let signature = config.signature_for(&config.agent);
+
+    match generate_with_spinner(
+        git,
+        &GuidedAgent::new(&agent, guidance),
+        signature.as_deref(),
+        config,
+        args,
+    )
+    .await
+    {
+        Ok(commit) => Some(commit),
         Err(e) => {
-            format_error(&e, &args);
-            Err(anyhow::anyhow!("generation failed"))
+            format_error(&e, args);
+            None
         }
     }
 }
 
+/// Print the message under review, flagging it if it doesn't validate
+fn display_draft(draft: &str, rules: &CommitRules) {
+    eprintln!();
+    eprintln!("{}", style("Commit message:").bold());
+    eprintln!();
+    for line in draft.lines() {
+        eprintln!("  {}", line);
+    }
+    eprintln!();
+    if let Err(e) = ConventionalCommit::validate_with(draft, rules) {
+        // First line only; the caret diagram is shown on accept
+        let reason = e.to_string();
+        eprintln!(
+            "{} {}",
+            style("warning:").yellow().bold(),
+            reason.lines().next().unwrap_or_default()
+        );
+        eprintln!();
+    }
+}
+
 /// Run the init command
-pub async fn run_init(hook_manager: Option<String>, agent: AgentName) -> Result<()> {
-    // Determine hook manager (detect or use specified)
-    let manager = if let Some(manager_str) = hook_manager {
-        // User specified a manager
-        manager_str
-            .parse::<HookManager>()
-            .context(format!("Invalid hook manager '{}'", manager_str))?
-    } else {
-        // Auto-detect hook manager
-        let cwd = std::env::current_dir().context("Failed to get current directory")?;
-        detect_hook_manager(&cwd).unwrap_or(HookManager::PlainGit)
-    };
+pub async fn run_init(hook_manager: Option<String>, config: &Config) -> Result<()> {
+    let agent = &config.agent;
+
+    let manager = resolve_hook_manager(hook_manager)?;
 
     eprintln!(
         "{} Installing {} hook for agent {}...",
@@ -210,7 +777,7 @@ pub async fn run_init(hook_manager: Option<String>, agent: AgentName) -> Result<
 
     // Install hook
     let cwd = std::env::current_dir().context("Failed to get current directory")?;
-    install_hook(manager, &cwd, &agent).context("Failed to install hook")?;
+    install_hook(manager, &cwd, config).context("Failed to install hook")?;
 
     eprintln!("{} Hook installed successfully", style("✓").green().bold());
     eprintln!();
@@ -225,6 +792,201 @@ pub async fn run_init(hook_manager: Option<String>, agent: AgentName) -> Result<
     Ok(())
 }
 
+/// Determine hook manager (use specified, or detect in the current directory)
+fn resolve_hook_manager(hook_manager: Option<String>) -> Result<HookManager> {
+    if let Some(manager_str) = hook_manager {
+        // User specified a manager
+        manager_str
+            .parse::<HookManager>()
+            .context(format!("Invalid hook manager '{}'", manager_str))
+    } else {
+        // Auto-detect hook manager
+        let cwd = std::env::current_dir().context("Failed to get current directory")?;
+        Ok(detect_hook_manager(&cwd).unwrap_or(HookManager::PlainGit))
+    }
+}
+
+/// Run `init --lint`: install the commit-msg lint hook
+pub fn run_init_lint(hook_manager: Option<String>) -> Result<()> {
+    let manager = resolve_hook_manager(hook_manager)?;
+
+    eprintln!(
+        "{} Installing {} commit-msg lint hook...",
+        style("→").blue(),
+        manager
+    );
+
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    install_lint_hook(manager, &cwd).context("Failed to install hook")?;
+
+    eprintln!("{} Hook installed successfully", style("✓").green().bold());
+    eprintln!();
+    eprintln!("  Manager: {}", manager);
+    eprintln!();
+    eprintln!(
+        "{} Commits with non-conventional messages will now be rejected",
+        style("→").blue()
+    );
+
+    Ok(())
+}
+
+/// Arguments for the lint command
+#[derive(Debug)]
+pub struct LintArgs {
+    /// Used for the allowed scopes
+    pub config: Config,
+    pub file: Option<PathBuf>,
+    pub range: Option<String>,
+    pub quiet: bool,
+    pub cwd: PathBuf,
+}
+
+/// Run the lint command
+///
+/// Prints one line per rule violation and fails if any message has errors.
+/// Scopes are checked against the workspace layout (see [`ScopeMap`]).
+pub fn run_lint(args: LintArgs) -> Result<()> {
+    // Scopes are opt-in for hand-written messages, and old commits in a range
+    // aren't judged by today's workspace layout
+    let scopes = if args.config.lint_scopes && args.range.is_none() {
+        ScopeMap::for_repo(&RealGitProvider::new(args.cwd.clone()), &args.config)
+    } else {
+        ScopeMap::default()
+    };
+    let rules = CommitRules {
+        max_header_length: (args.config.subject_max_length > 0)
+            .then_some(args.config.subject_max_length),
+        ..args.config.commit_rules(&scopes)
+    };
+    let comment_char = RealGitProvider::new(args.cwd.clone()).comment_char();
+    let lint = |message: &str| lint_message_with_comment_char(message, &rules, &comment_char);
+    // (label, report) for each linted message
+    let reports: Vec<(String, LintReport)> = match (&args.range, &args.file) {
+        (Some(range), _) => {
+            let git = RealGitProvider::new(args.cwd.clone());
+            let commits = git
+                .commit_messages(range)
+                .context(format!("Failed to read commits in '{}'", range))?;
+            commits
+                .into_iter()
+                .map(|(sha, message)| {
+                    let header = message.lines().next().unwrap_or_default();
+                    let label = format!("{} {}", &sha[..sha.len().min(7)], header);
+                    (label, lint(&message))
+                })
+                .collect()
+        }
+        (None, Some(file)) => {
+            let (label, message) = if file.as_os_str() == "-" {
+                let message =
+                    std::io::read_to_string(std::io::stdin()).context("Failed to read stdin")?;
+                ("<stdin>".to_string(), message)
+            } else {
+                let message = std::fs::read_to_string(args.cwd.join(file))
+                    .context(format!("Failed to read {}", file.display()))?;
+                (file.display().to_string(), message)
+            };
+            vec![(label, lint(&message))]
+        }
+        (None, None) => anyhow::bail!("lint needs a message file or --range"),
+    };
+
+    let mut errors = 0;
+    let mut warnings = 0;
+    for (label, report) in &reports {
+        errors += report.error_count();
+        warnings += report.warning_count();
+        // Clean messages only get a line in range mode, where they're a checklist
+        if report.diagnostics.is_empty() && (args.quiet || args.range.is_none()) {
+            continue;
+        }
+        display_lint_report(label, report);
+    }
+
+    if errors > 0 {
+        eprintln!();
+        eprintln!(
+            "{} {} error(s), {} warning(s) in {} message(s)",
+            style("error:").red().bold(),
+            errors,
+            warnings,
+            reports.len()
+        );
+        eprintln!("  Expected format: <type>(<scope>): <description>");
+        eprintln!("  Valid types: {}", commit_types_display(&rules.types));
+        anyhow::bail!("lint failed");
+    }
+
+    if args.range.is_some() && !args.quiet {
+        eprintln!();
+        eprintln!(
+            "{} {} message(s) checked, {} warning(s)",
+            style("✓").green().bold(),
+            reports.len(),
+            warnings
+        );
+    }
+    Ok(())
+}
+
+/// Print a lint report as a heading plus one aligned line per diagnostic
+///
+/// Output format:
+/// ```text
+/// ✗ a1b2c3d feat add thing
+///   1:5   error    header-format        expected ':' after the type or scope
+/// ```
+fn display_lint_report(label: &str, report: &LintReport) {
+    let marker = if report.skipped {
+        style("-").dim()
+    } else if report.is_ok() {
+        style("✓").green().bold()
+    } else {
+        style("✗").red().bold()
+    };
+    eprintln!("{} {}", marker, label);
+
+    for diagnostic in &report.diagnostics {
+        // Pad before styling so ANSI codes don't break alignment
+        let severity = format!("{:<8}", diagnostic.severity);
+        let severity = match diagnostic.severity {
+            Severity::Error => style(severity).red(),
+            Severity::Warning => style(severity).yellow(),
+        };
+        eprintln!(
+            "  {:<5} {} {:<21} {}",
+            format!("{}:{}", diagnostic.line, diagnostic.column),
+            severity,
+            diagnostic.rule,
+            diagnostic.message
+        );
+    }
+}
+
+/// Run the `config show` command
+///
+/// Prints one `key = value` line per setting, annotated with its source.
+pub fn run_config_show(loaded: &LoadedConfig) {
+    let entries = loaded.entries();
+    let width = entries
+        .iter()
+        .map(|(key, value, _)| key.len() + value.len())
+        .max()
+        .unwrap_or(0);
+
+    for (key, value, source) in entries {
+        let padding = width - key.len() - value.len();
+        println!(
+            "{} = {}{}  {}",
+            key,
+            value,
+            " ".repeat(padding),
+            style(format!("({})", source)).dim()
+        );
+    }
+}
+
 /// Display staged files list
 ///
 /// Formats and prints the name-status output from git diff.
@@ -248,6 +1010,36 @@ fn display_staged_files(name_status: &str) {
     eprintln!();
 }
 
+/// Display what was masked before the diff went to the agent
+///
+/// Output format:
+/// ```text
+/// ⚠ Masked 3 secret(s) before sending the diff:
+///   aws-access-key  src/config.rs (2)
+///   env-file        .env
+/// ```
+fn display_redactions(report: &RedactionReport) {
+    if report.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "{} Masked {} secret(s) before sending the diff:",
+        style("⚠").yellow().bold(),
+        report.findings.len()
+    );
+    let groups = report.grouped();
+    let width = groups.iter().map(|(d, _, _)| d.len()).max().unwrap_or(0);
+    for (detector, file, count) in groups {
+        let mut line = format!("  {:<width$}  {}", detector, file.unwrap_or("(diff)"));
+        if count > 1 {
+            line.push_str(&format!(" ({})", count));
+        }
+        eprintln!("{}", line);
+    }
+    eprintln!();
+}
+
 /// Format error messages with helpful hints
 fn format_error(error: &GeneratorError, args: &GenerateArgs) {
     eprintln!("{} {}", style("error:").red().bold(), error);
@@ -256,13 +1048,31 @@ fn format_error(error: &GeneratorError, args: &GenerateArgs) {
     // Add context-specific hints
     match error {
         GeneratorError::Agent(agent_err) => match agent_err {
-            AgentError::NotFound { agent } => {
+            AgentError::NotFound { agent } if agent.is_builtin() => {
                 eprintln!("{} Installation instructions:", style("hint:").yellow());
                 eprintln!("  {} CLI: {}", agent.display_name(), agent.install_url());
             }
-            AgentError::ExecutionFailed { agent, stderr } => {
+            AgentError::NotFound { agent } => {
+                eprintln!(
+                    "{} Custom agent command not found:",
+                    style("hint:").yellow()
+                );
+                eprintln!(
+                    "  Install `{}` or set `command` in the [agents.{}] config section",
+                    agent, agent
+                );
+                eprintln!("  Docs: {}", agent.install_url());
+            }
+            AgentError::ExecutionFailed {
+                agent,
+                exit_code,
+                stderr,
+            } => {
                 eprintln!("{} Agent execution details:", style("hint:").yellow());
                 eprintln!("  Agent: {}", agent);
+                if let Some(code) = exit_code {
+                    eprintln!("  Exit code: {}", code);
+                }
                 eprintln!("  Error: {}", stderr);
                 if args.verbose {
                     eprintln!();
@@ -326,6 +1136,12 @@ fn format_error(error: &GeneratorError, args: &GenerateArgs) {
                 eprintln!("  This usually means you're not in a git repository.");
                 eprintln!("  Make sure you're running this command in a git repository.");
             }
+            GitError::InvalidIgnorePattern { path, .. } => {
+                eprintln!("{} Fix the pattern in {}:", style("hint:").yellow(), path);
+                eprintln!(
+                    "  Patterns use .gitignore syntax (e.g. `*.snap`, `vendor/`, `!Cargo.lock`)"
+                );
+            }
             GitError::Io(io_err) => {
                 eprintln!("{} I/O error:", style("hint:").yellow());
                 eprintln!("  {}", io_err);
@@ -339,14 +1155,32 @@ fn format_error(error: &GeneratorError, args: &GenerateArgs) {
                 }
             }
         },
+        GeneratorError::SecretsDetected { summary } => {
+            eprintln!("{} Secrets are staged:", style("hint:").yellow());
+            eprintln!("  {}", summary);
+            eprintln!();
+            eprintln!("  Redaction is in strict mode, so nothing was sent to the agent.");
+            eprintln!("  Unstage the files (git restore --staged <file>) or remove the secrets.");
+        }
+        GeneratorError::Config(config_err) => {
+            eprintln!("{} Check your configuration:", style("hint:").yellow());
+            eprintln!("  {}", config_err);
+            eprintln!("  Run `commitment config show` to see where each value comes from.");
+        }
         GeneratorError::Validation(reason) => {
             eprintln!("{} Commit validation failed:", style("hint:").yellow());
             eprintln!("  {}", reason);
             eprintln!();
-            eprintln!("  The AI generated an invalid commit message.");
+            eprintln!(
+                "  The AI generated an invalid commit message ({} attempt(s)).",
+                args.config.max_attempts.max(1)
+            );
             eprintln!("  Expected format: <type>(<scope>): <description>");
             eprintln!();
-            eprintln!("  Valid types: {}", commit_types_display());
+            eprintln!(
+                "  Valid types: {}",
+                commit_types_display(&args.config.commit_types)
+            );
             if args.verbose {
                 eprintln!();
                 eprintln!("  This is unusual - the AI should generate valid messages.");
@@ -364,9 +1198,12 @@ mod tests {
     fn cli_parses_with_defaults() {
         let cli = Cli::parse_from(["commitment"]);
         assert!(cli.command.is_none());
-        assert_eq!(cli.agent, AgentName::Claude);
+        assert!(cli.agent.is_empty());
+        assert_eq!(cli.timeout, None);
+        assert_eq!(cli.max_attempts, None);
         assert!(!cli.dry_run);
         assert!(!cli.message_only);
+        assert!(!cli.interactive);
         assert!(!cli.quiet);
         assert!(!cli.verbose);
         assert_eq!(cli.cwd, PathBuf::from("."));
@@ -375,7 +1212,119 @@ mod tests {
     #[test]
     fn cli_parses_agent_flag() {
         let cli = Cli::parse_from(["commitment", "--agent", "codex"]);
-        assert_eq!(cli.agent, AgentName::Codex);
+        assert_eq!(cli.agent, [AgentName::Codex]);
+    }
+
+    #[test]
+    fn cli_model_and_agent_args_become_config_layer() {
+        let cli = Cli::parse_from([
+            "commitment",
+            "--model",
+            "haiku",
+            "--agent-arg",
+            "--max-turns",
+            "--agent-arg=1",
+        ]);
+        let layer = cli.config_layer();
+        assert_eq!(layer.model.as_deref(), Some("haiku"));
+        assert_eq!(
+            layer.agent_args,
+            Some(vec!["--max-turns".to_string(), "1".to_string()])
+        );
+        assert_eq!(
+            Cli::parse_from(["commitment"]).config_layer().agent_args,
+            None
+        );
+    }
+
+    #[test]
+    fn cli_agent_list_becomes_fallback_chain() {
+        let cli = Cli::parse_from(["commitment", "--agent", "claude,codex,gemini"]);
+        let layer = cli.config_layer();
+        assert_eq!(layer.agent, Some(AgentName::Claude));
+        assert_eq!(
+            layer.fallback_agents,
+            Some(vec![AgentName::Codex, AgentName::Gemini])
+        );
+
+        // A single agent leaves the configured fallbacks alone
+        let layer = Cli::parse_from(["commitment", "--agent", "codex"]).config_layer();
+        assert_eq!(layer.fallback_agents, None);
+        assert_eq!(
+            Cli::parse_from(["commitment"])
+                .config_layer()
+                .fallback_agents,
+            None
+        );
+    }
+
+    #[test]
+    fn single_cli_agent_keeps_configured_fallbacks() {
+        let repo = ConfigLayer::from_toml(
+            "agent = \"codex\"\nfallback_agents = [\"gemini\"]\n",
+            std::path::Path::new(".commitment.toml"),
+        )
+        .unwrap();
+        let cli = Cli::parse_from(["commitment", "--agent", "claude", "--message-only"]);
+        let loaded = LoadedConfig::resolve([
+            (
+                crate::config::ConfigSource::Repo(PathBuf::from(".commitment.toml")),
+                repo,
+            ),
+            (crate::config::ConfigSource::Cli, cli.config_layer()),
+        ]);
+        assert_eq!(
+            loaded.config.agent_chain(),
+            vec![AgentName::Claude, AgentName::Gemini]
+        );
+    }
+
+    #[test]
+    fn cli_parses_custom_agent_flag() {
+        let cli = Cli::parse_from(["commitment", "--agent", "ollama"]);
+        assert_eq!(cli.agent, [AgentName::Custom("ollama".to_string())]);
+    }
+
+    #[test]
+    fn cli_parses_timeout_flag() {
+        let cli = Cli::parse_from(["commitment", "--timeout", "30"]);
+        assert_eq!(cli.timeout, Some(30));
+    }
+
+    #[test]
+    fn cli_flags_become_config_layer() {
+        let cli = Cli::parse_from([
+            "commitment",
+            "--agent",
+            "gemini",
+            "--timeout",
+            "15",
+            "--max-attempts",
+            "5",
+        ]);
+        let layer = cli.config_layer();
+        assert_eq!(layer.agent, Some(AgentName::Gemini));
+        assert_eq!(layer.timeout_secs, Some(15));
+        assert_eq!(layer.max_attempts, Some(5));
+        assert_eq!(layer.max_diff_length, None);
+        assert_eq!(layer.strategy, None);
+    }
+
+    #[test]
+    fn cli_summarize_sets_strategy() {
+        let cli = Cli::parse_from(["commitment", "--summarize"]);
+        assert_eq!(cli.config_layer().strategy, Some(DiffStrategy::Summarize));
+    }
+
+    #[test]
+    fn cli_parses_config_show_command() {
+        let cli = Cli::parse_from(["commitment", "config", "show"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Config {
+                command: ConfigCommand::Show
+            })
+        ));
     }
 
     #[test]
@@ -390,6 +1339,51 @@ mod tests {
         assert!(cli.message_only);
     }
 
+    #[test]
+    fn cli_parses_interactive_flag() {
+        let cli = Cli::parse_from(["commitment", "-i"]);
+        assert!(cli.interactive);
+        let cli = Cli::parse_from(["commitment", "--interactive", "--dry-run"]);
+        assert!(cli.interactive && cli.dry_run);
+    }
+
+    #[test]
+    fn cli_interactive_conflicts_with_message_only() {
+        assert!(Cli::try_parse_from(["commitment", "-i", "--message-only"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_candidates_flags() {
+        let cli = Cli::parse_from([
+            "commitment",
+            "--candidates",
+            "3",
+            "--candidates-from",
+            "claude,codex",
+            "--json",
+        ]);
+        assert_eq!(cli.candidates, Some(3));
+        assert_eq!(cli.candidates_from, [AgentName::Claude, AgentName::Codex]);
+        assert!(cli.json);
+    }
+
+    #[test]
+    fn cli_candidate_options_require_candidates() {
+        assert!(Cli::try_parse_from(["commitment", "--json"]).is_err());
+        assert!(Cli::try_parse_from(["commitment", "--candidates-from", "codex"]).is_err());
+        assert!(Cli::try_parse_from(["commitment", "--candidates", "2", "-i"]).is_err());
+    }
+
+    #[test]
+    fn parse_pick_accepts_numbers_in_range_and_q() {
+        assert_eq!(parse_pick("1\n", 3), Some(Pick::Index(0)));
+        assert_eq!(parse_pick(" 3 ", 3), Some(Pick::Index(2)));
+        assert_eq!(parse_pick("Q", 3), Some(Pick::Abort));
+        assert_eq!(parse_pick("0", 3), None);
+        assert_eq!(parse_pick("4", 3), None);
+        assert_eq!(parse_pick("two", 3), None);
+    }
+
     #[test]
     fn cli_parses_quiet_flag() {
         let cli = Cli::parse_from(["commitment", "-q"]);
@@ -430,12 +1424,114 @@ mod tests {
         let cli = Cli::parse_from(["commitment", "init", "--agent", "gemini"]);
         match cli.command {
             Some(Command::Init { agent, .. }) => {
-                assert_eq!(agent, AgentName::Gemini);
+                assert_eq!(agent, Some(AgentName::Gemini));
             }
             _ => panic!("Expected Init command"),
         }
     }
 
+    #[test]
+    fn cli_parses_init_with_lint() {
+        let cli = Cli::parse_from(["commitment", "init", "--lint"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Init { lint: true, .. })
+        ));
+    }
+
+    #[test]
+    fn cli_parses_lint_file() {
+        let cli = Cli::parse_from(["commitment", "lint", ".git/COMMIT_EDITMSG"]);
+        match cli.command {
+            Some(Command::Lint { file, range }) => {
+                assert_eq!(file, Some(PathBuf::from(".git/COMMIT_EDITMSG")));
+                assert_eq!(range, None);
+            }
+            _ => panic!("Expected Lint command"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_lint_range() {
+        let cli = Cli::parse_from(["commitment", "lint", "--range", "main..HEAD"]);
+        match cli.command {
+            Some(Command::Lint { file, range }) => {
+                assert_eq!(file, None);
+                assert_eq!(range.as_deref(), Some("main..HEAD"));
+            }
+            _ => panic!("Expected Lint command"),
+        }
+    }
+
+    #[test]
+    fn cli_lint_requires_file_or_range() {
+        assert!(Cli::try_parse_from(["commitment", "lint"]).is_err());
+        assert!(Cli::try_parse_from(["commitment", "lint", "f", "--range", "a..b"]).is_err());
+    }
+
+    #[test]
+    fn run_lint_fails_on_invalid_message_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("MSG"), "added stuff\n# comment\n").unwrap();
+
+        let result = run_lint(LintArgs {
+            config: Config::default(),
+            file: Some(PathBuf::from("MSG")),
+            range: None,
+            quiet: true,
+            cwd: dir.path().to_path_buf(),
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn run_lint_checks_scopes_only_when_enabled() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("MSG"), "docs(readme): fix typo\n").unwrap();
+        let lint = |config: Config| {
+            run_lint(LintArgs {
+                config,
+                file: Some(PathBuf::from("MSG")),
+                range: None,
+                quiet: true,
+                cwd: dir.path().to_path_buf(),
+            })
+        };
+        let config = Config {
+            scopes: std::collections::BTreeMap::from([("web".to_string(), "web".to_string())]),
+            ..Config::default()
+        };
+
+        assert!(lint(config.clone()).is_ok());
+        let enforced = Config {
+            lint_scopes: true,
+            ..config
+        };
+        assert!(lint(enforced.clone()).is_err());
+        assert!(
+            lint(Config {
+                extra_scopes: vec!["readme".to_string()],
+                ..enforced
+            })
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn run_lint_passes_valid_message_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("MSG"), "fix: handle eof\n").unwrap();
+
+        let result = run_lint(LintArgs {
+            config: Config::default(),
+            file: Some(PathBuf::from("MSG")),
+            range: None,
+            quiet: true,
+            cwd: dir.path().to_path_buf(),
+        });
+        assert!(result.is_ok());
+    }
+
     #[test]
     fn cli_parses_combined_flags() {
         let cli = Cli::parse_from([
@@ -447,7 +1543,7 @@ mod tests {
             "--cwd",
             "/tmp",
         ]);
-        assert_eq!(cli.agent, AgentName::Codex);
+        assert_eq!(cli.agent, [AgentName::Codex]);
         assert!(cli.dry_run);
         assert!(cli.quiet);
         assert_eq!(cli.cwd, PathBuf::from("/tmp"));
@@ -456,14 +1552,18 @@ mod tests {
     #[test]
     fn generate_args_construction() {
         let args = GenerateArgs {
-            agent: AgentName::Claude,
+            config: Config::default(),
             dry_run: true,
             message_only: false,
+            interactive: false,
+            candidates: None,
+            candidates_from: Vec::new(),
+            json: false,
             quiet: false,
             verbose: true,
             cwd: PathBuf::from("."),
         };
-        assert_eq!(args.agent, AgentName::Claude);
+        assert_eq!(args.config.agent, AgentName::Claude);
         assert!(args.dry_run);
         assert!(!args.message_only);
         assert!(!args.quiet);
@@ -0,0 +1,1984 @@
+use crate::agents::AGENT_TIMEOUT;
+use crate::agents::{AgentConfig, AgentMode, AgentSettings, deserialize_agent_sections};
+use crate::breaking::{BreakingCheck, detect_breaking_changes};
+use crate::chunking::{DiffStrategy, SUMMARY_CONCURRENCY};
+use crate::error::ConfigError;
+use crate::format::{FormatOptions, SUBJECT_MAX_LENGTH, WRAP_COLUMN};
+use crate::history::HISTORY_EXAMPLES;
+use crate::prompt::BYTES_PER_TOKEN;
+use crate::redact::{RedactionMode, compile_pattern};
+use crate::retry::{MAX_ATTEMPTS, RETRY_BACKOFF};
+use crate::scope::ScopeMap;
+use crate::signature::{
+    DEFAULT_MODEL_SIGNATURE_TEMPLATE, DEFAULT_SIGNATURE_TEMPLATE, SignatureVars, check_template,
+    render_signature,
+};
+use crate::ticket::TicketPlacement;
+use crate::types::{
+    AgentName, CommitRules, CommitType, StagedDiff, commit_types_display, default_commit_types,
+};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Per-repository config file name, looked up at the repository root
+pub const REPO_CONFIG_FILE: &str = ".commitment.toml";
+
+/// Prefix for environment variable overrides (e.g. `COMMITMENT_AGENT`)
+pub const ENV_PREFIX: &str = "COMMITMENT_";
+
+/// Fully resolved configuration
+///
+/// Every field has a concrete value: defaults are applied first, then each
+/// layer (global file, repo file, environment, CLI flags) overrides the
+/// fields it sets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Agent used for generation and written into installed hooks
+    pub agent: AgentName,
+    /// Agents tried in order when `agent` is missing, times out or answers nonsense
+    pub fallback_agents: Vec<AgentName>,
+    /// Whether fallback agents are asked in turn or all at once
+    pub agent_mode: AgentMode,
+    /// Agent execution timeout in seconds
+    pub timeout_secs: u64,
+    /// Whether `timeout_secs` came from `--timeout`, which also beats the
+    /// `timeout_secs` of `[agents.<name>]` sections
+    pub timeout_overrides_agents: bool,
+    /// Model for `agent` (overrides its section's `model`)
+    pub model: Option<String>,
+    /// Extra arguments for `agent`, after its section's `args`
+    pub agent_args: Vec<String>,
+    /// Token budget for the diff section (`None`: the agent's default)
+    pub max_diff_tokens: Option<usize>,
+    /// What to do with diffs over the token budget
+    pub strategy: DiffStrategy,
+    /// Chunk summaries requested at once with the summarize strategy
+    pub summary_concurrency: usize,
+    /// Whether to append the agent signature to generated messages
+    pub signature: bool,
+    /// Signature text with `{agent}`, `{model}`, `{version}` and `{date}` variables
+    pub signature_template: String,
+    /// Column generated bodies are wrapped at (0: don't wrap)
+    pub wrap_column: usize,
+    /// Longest header accepted from the agent (0: any length)
+    pub subject_max_length: usize,
+    /// Lowercase the first word of generated descriptions
+    pub lowercase_subject: bool,
+    /// Total agent calls per generation, including repairs and retries
+    pub max_attempts: u32,
+    /// Delay before the first retry of a failed agent call (doubles each retry)
+    pub retry_backoff_ms: u64,
+    /// Retry agent calls that timed out (off: a hung agent would block for
+    /// `max_attempts` timeouts)
+    pub retry_timeouts: bool,
+    /// What to do with secrets found in the staged diff
+    pub redaction: RedactionMode,
+    /// Extra regexes whose matches are masked (only the `secret` group, if present)
+    pub redact_patterns: Vec<String>,
+    /// Recent commits shown to the agent as style examples (0 disables)
+    pub history_examples: usize,
+    /// Authors (name or email) whose commits are never used as examples
+    pub history_exclude_authors: Vec<String>,
+    /// Whether to read allowed scopes from workspace manifests
+    pub infer_scopes: bool,
+    /// Explicit directory → scope map from the `[scopes]` table (overrides manifests)
+    pub scopes: BTreeMap<String, String>,
+    /// Scopes allowed without a directory (e.g. `ci`, `deps`, `release`)
+    pub extra_scopes: Vec<String>,
+    /// Whether `lint` and the commit-msg hook enforce the allowed scopes
+    pub lint_scopes: bool,
+    /// Allowed commit types with their descriptions, in prompt order
+    pub commit_types: Vec<CommitType>,
+    /// Whether likely breaking changes are pointed out, and whether they must be marked
+    pub breaking_changes: BreakingCheck,
+    /// Jira or Linear project keys whose issue IDs are taken from the branch name
+    pub ticket_keys: Vec<String>,
+    /// Regexes finding other ticket IDs in the branch name (the `ticket` group, if present)
+    pub ticket_patterns: Vec<String>,
+    /// Where ticket references from the branch name are added
+    pub ticket_placement: TicketPlacement,
+    /// Agent settings and custom command and HTTP agent definitions from
+    /// `[agents.<name>]` sections
+    pub agents: BTreeMap<String, AgentConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            agent: AgentName::default_agent(),
+            fallback_agents: Vec::new(),
+            agent_mode: AgentMode::default(),
+            timeout_secs: AGENT_TIMEOUT.as_secs(),
+            timeout_overrides_agents: false,
+            model: None,
+            agent_args: Vec::new(),
+            max_diff_tokens: None,
+            strategy: DiffStrategy::default(),
+            summary_concurrency: SUMMARY_CONCURRENCY,
+            signature: true,
+            signature_template: DEFAULT_SIGNATURE_TEMPLATE.to_string(),
+            wrap_column: WRAP_COLUMN,
+            subject_max_length: SUBJECT_MAX_LENGTH,
+            lowercase_subject: false,
+            max_attempts: MAX_ATTEMPTS,
+            retry_backoff_ms: RETRY_BACKOFF.as_millis() as u64,
+            retry_timeouts: false,
+            redaction: RedactionMode::default(),
+            redact_patterns: Vec::new(),
+            history_examples: HISTORY_EXAMPLES,
+            history_exclude_authors: Vec::new(),
+            infer_scopes: true,
+            scopes: BTreeMap::new(),
+            extra_scopes: Vec::new(),
+            lint_scopes: false,
+            commit_types: default_commit_types(),
+            breaking_changes: BreakingCheck::default(),
+            ticket_keys: Vec::new(),
+            ticket_patterns: Vec::new(),
+            ticket_placement: TicketPlacement::default(),
+            agents: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Agent timeout as a `Duration`
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.timeout_secs)
+    }
+
+    /// Retry backoff as a `Duration`
+    pub fn retry_backoff(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.retry_backoff_ms)
+    }
+
+    /// `agent` followed by its fallbacks, without repeats
+    pub fn agent_chain(&self) -> Vec<AgentName> {
+        let mut chain = vec![self.agent.clone()];
+        for agent in &self.fallback_agents {
+            if !chain.contains(agent) {
+                chain.push(agent.clone());
+            }
+        }
+        chain
+    }
+
+    /// This config with `agent` as the only agent (no fallbacks)
+    ///
+    /// `model` and `agent_args` are meant for the configured agent, so they
+    /// are dropped when switching to another one.
+    pub fn single_agent(&self, agent: AgentName) -> Self {
+        let same_agent = agent == self.agent;
+        Self {
+            agent,
+            fallback_agents: Vec::new(),
+            model: self.model.clone().filter(|_| same_agent),
+            agent_args: if same_agent {
+                self.agent_args.clone()
+            } else {
+                Vec::new()
+            },
+            ..self.clone()
+        }
+    }
+
+    /// Check that `agent` can run: built-in, or configured in an
+    /// `[agents.<name>]` section
+    ///
+    /// The error is the reason, suggesting the closest built-in agent for
+    /// names that look like a typo of one.
+    pub fn check_agent(&self, agent: &AgentName) -> Result<(), String> {
+        if agent.is_builtin() || self.agents.contains_key(agent.command_name()) {
+            return Ok(());
+        }
+        Err(match agent.closest_builtin() {
+            Some(builtin) => format!("unknown agent (did you mean `{}`?)", builtin),
+            None => format!(
+                "unknown agent (custom agents need an `[agents.{}]` section)",
+                agent
+            ),
+        })
+    }
+
+    /// How `agent` is run: its `[agents.<name>]` section, plus `model` and
+    /// `agent_args` if it is the configured agent
+    ///
+    /// A section's `timeout_secs` overrides the global one, unless that came
+    /// from `--timeout`. A command agent only gets a model if its `args`
+    /// pass it on with `{model}`.
+    pub fn agent_settings(&self, agent: &AgentName) -> AgentSettings {
+        let mut settings = AgentSettings {
+            timeout: self.timeout(),
+            ..AgentSettings::default()
+        };
+        let (timeout_secs, takes_model) = match self.agents.get(agent.command_name()) {
+            Some(AgentConfig::Builtin(section)) => {
+                settings.model = section.model.clone();
+                settings.args = section.args.clone();
+                settings.env = section.env.clone();
+                settings.cwd = section.cwd.clone();
+                (section.timeout_secs, true)
+            }
+            Some(AgentConfig::Command(section)) => {
+                settings.model = section.model.clone();
+                settings.env = section.env.clone();
+                settings.cwd = section.cwd.clone();
+                (section.timeout_secs, section.uses_model())
+            }
+            Some(AgentConfig::Http(section)) => {
+                settings.model = section.model.clone();
+                (section.timeout_secs, true)
+            }
+            None => (None, !matches!(agent, AgentName::Custom(_))),
+        };
+        if let Some(secs) = timeout_secs.filter(|_| !self.timeout_overrides_agents) {
+            settings.timeout = std::time::Duration::from_secs(secs);
+        }
+        if *agent == self.agent {
+            if let Some(model) = &self.model {
+                settings.model = Some(model.clone());
+            }
+            settings.args.extend(self.agent_args.iter().cloned());
+        }
+        if !takes_model {
+            settings.model = None;
+        }
+        settings
+    }
+
+    /// Diff token budget for prompts sent to `agent`
+    ///
+    /// `max_diff_tokens` if set, otherwise [`AgentName::diff_token_budget`].
+    pub fn diff_token_budget(&self, agent: &AgentName) -> usize {
+        self.max_diff_tokens
+            .unwrap_or_else(|| agent.diff_token_budget())
+    }
+
+    /// Model configured for `agent`, if any (see [`agent_settings`](Self::agent_settings))
+    pub fn agent_model(&self, agent: &AgentName) -> Option<String> {
+        self.agent_settings(agent).model
+    }
+
+    /// Rendered signature for messages from `agent` (`None` if disabled)
+    ///
+    /// The default signature names the model when one is configured.
+    pub fn signature_for(&self, agent: &AgentName) -> Option<String> {
+        self.signature.then(|| {
+            let model = self.agent_model(agent);
+            let template = match &model {
+                Some(_) if self.signature_template == DEFAULT_SIGNATURE_TEMPLATE => {
+                    DEFAULT_MODEL_SIGNATURE_TEMPLATE
+                }
+                _ => &self.signature_template,
+            };
+            render_signature(template, &SignatureVars::new(agent, model.as_deref()))
+        })
+    }
+
+    /// Validation rules: the configured commit types, and the scopes in `scopes`
+    pub fn commit_rules(&self, scopes: &ScopeMap) -> CommitRules {
+        CommitRules::new(self.commit_types.clone(), scopes.allowed())
+    }
+
+    /// Rules for agent output: [`commit_rules`](Self::commit_rules) plus
+    /// `subject_max_length`, and a breaking change marker if `diff` looks
+    /// breaking (see [`detect_breaking_changes`])
+    ///
+    /// Human-written messages only get a lint warning for long headers.
+    pub fn generation_rules(&self, scopes: &ScopeMap, diff: &StagedDiff) -> CommitRules {
+        CommitRules {
+            max_header_length: (self.subject_max_length > 0).then_some(self.subject_max_length),
+            require_breaking: self.breaking_changes == BreakingCheck::Require
+                && !detect_breaking_changes(diff).is_empty(),
+            ..self.commit_rules(scopes)
+        }
+    }
+
+    /// How agent output is tidied up before validation
+    pub fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            wrap_column: self.wrap_column,
+            lowercase_subject: self.lowercase_subject,
+        }
+    }
+}
+
+/// A partial configuration from a single source
+///
+/// All fields are optional - only the keys present in a source override
+/// lower-precedence layers. Deserialized directly from TOML config files.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigLayer {
+    pub agent: Option<AgentName>,
+    pub fallback_agents: Option<Vec<AgentName>>,
+    pub agent_mode: Option<AgentMode>,
+    pub timeout_secs: Option<u64>,
+    pub model: Option<String>,
+    pub agent_args: Option<Vec<String>>,
+    pub max_diff_tokens: Option<usize>,
+    /// Deprecated byte limit, converted to `max_diff_tokens` when merged
+    pub max_diff_length: Option<usize>,
+    pub strategy: Option<DiffStrategy>,
+    pub summary_concurrency: Option<usize>,
+    pub signature: Option<bool>,
+    pub signature_template: Option<String>,
+    pub wrap_column: Option<usize>,
+    pub subject_max_length: Option<usize>,
+    pub lowercase_subject: Option<bool>,
+    pub max_attempts: Option<u32>,
+    pub retry_backoff_ms: Option<u64>,
+    pub retry_timeouts: Option<bool>,
+    pub redaction: Option<RedactionMode>,
+    pub redact_patterns: Option<Vec<String>>,
+    pub history_examples: Option<usize>,
+    pub history_exclude_authors: Option<Vec<String>>,
+    pub infer_scopes: Option<bool>,
+    #[serde(default)]
+    pub scopes: BTreeMap<String, String>,
+    pub extra_scopes: Option<Vec<String>>,
+    pub lint_scopes: Option<bool>,
+    /// Commit types to add, or re-describe, as `name = "description"`
+    #[serde(default)]
+    pub commit_types: BTreeMap<String, String>,
+    /// Commit types to remove (e.g. `chore`)
+    pub exclude_commit_types: Option<Vec<String>>,
+    pub breaking_changes: Option<BreakingCheck>,
+    pub ticket_keys: Option<Vec<String>>,
+    pub ticket_patterns: Option<Vec<String>>,
+    pub ticket_placement: Option<TicketPlacement>,
+    #[serde(default, deserialize_with = "deserialize_agent_sections")]
+    pub agents: BTreeMap<String, AgentConfig>,
+}
+
+impl ConfigLayer {
+    /// Parse a layer from TOML text
+    ///
+    /// `path` is only used for error messages.
+    pub fn from_toml(content: &str, path: &Path) -> Result<Self, ConfigError> {
+        let parse_error = |reason: String| ConfigError::Parse {
+            path: path.display().to_string(),
+            reason,
+        };
+        let layer: Self =
+            toml::from_str(content).map_err(|e| parse_error(e.message().to_string()))?;
+
+        // Agent names must round-trip through `--agent <name>`
+        for name in layer.agents.keys() {
+            match name.parse::<AgentName>() {
+                Ok(agent) if agent.command_name() == name => {}
+                Ok(_) => {
+                    return Err(parse_error(format!(
+                        "custom agent `{}` conflicts with a built-in agent",
+                        name
+                    )));
+                }
+                Err(e) => return Err(parse_error(e.to_string())),
+            }
+        }
+
+        // Scopes must be usable in a commit header
+        for (dir, scope) in &layer.scopes {
+            if !is_valid_scope(scope) {
+                return Err(parse_error(format!(
+                    "invalid scope `{}` for `{}` (must be non-empty, without whitespace, parentheses or commas)",
+                    scope, dir
+                )));
+            }
+        }
+        for scope in layer.extra_scopes.iter().flatten() {
+            if !is_valid_scope(scope) {
+                return Err(parse_error(format!(
+                    "invalid scope `{}` in extra_scopes (must be non-empty, without whitespace, parentheses or commas)",
+                    scope
+                )));
+            }
+        }
+
+        // Types must parse as the type part of a header
+        for name in layer
+            .commit_types
+            .keys()
+            .chain(layer.exclude_commit_types.iter().flatten())
+        {
+            if !CommitType::is_valid_name(name) {
+                return Err(parse_error(format!(
+                    "invalid commit type `{}` (use lowercase letters and digits)",
+                    name
+                )));
+            }
+        }
+
+        if let Some(template) = &layer.signature_template {
+            check_template(template).map_err(|e| parse_error(e.to_string()))?;
+        }
+
+        // Report bad regexes with the file they came from
+        for pattern in layer.redact_patterns.iter().flatten() {
+            compile_pattern(pattern).map_err(|e| parse_error(e.to_string()))?;
+        }
+        for pattern in layer.ticket_patterns.iter().flatten() {
+            crate::ticket::compile_pattern(pattern).map_err(|e| parse_error(e.to_string()))?;
+        }
+        for key in layer.ticket_keys.iter().flatten() {
+            if !crate::ticket::is_valid_key(key) {
+                return Err(parse_error(format!(
+                    "invalid ticket key `{}` (use letters and digits, e.g. `PROJ`)",
+                    key
+                )));
+            }
+        }
+
+        Ok(layer)
+    }
+
+    /// Read a layer from a TOML file
+    ///
+    /// Relative agent `cwd` settings are resolved against the file's
+    /// directory. Returns `Ok(None)` if the file does not exist.
+    pub fn from_file(path: &Path) -> Result<Option<Self>, ConfigError> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.display().to_string(),
+            source,
+        })?;
+        let mut layer = Self::from_toml(&content, path)?;
+
+        // A relative agent `cwd` means the same directory however commitment
+        // is started (by hand, from a hook, with `--cwd`)
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for agent in layer.agents.values_mut() {
+            let cwd = match agent {
+                AgentConfig::Builtin(section) => &mut section.cwd,
+                AgentConfig::Command(section) => &mut section.cwd,
+                AgentConfig::Http(_) => continue,
+            };
+            if let Some(relative) = cwd.as_ref().filter(|cwd| cwd.is_relative()) {
+                *cwd = Some(dir.join(relative));
+            }
+        }
+        Ok(Some(layer))
+    }
+
+    /// Build a layer from `COMMITMENT_*` environment variables
+    ///
+    /// Takes the variables as an iterator so tests don't touch the real
+    /// process environment. Unrelated variables are ignored.
+    pub fn from_env_vars(
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        let mut layer = Self::default();
+
+        for (key, value) in vars {
+            let Some(name) = key.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            match name {
+                "AGENT" => layer.agent = Some(parse_env(&key, &value)?),
+                "FALLBACK_AGENTS" => {
+                    layer.fallback_agents = Some(
+                        value
+                            .split(',')
+                            .filter(|agent| !agent.trim().is_empty())
+                            .map(|agent| parse_env(&key, agent))
+                            .collect::<Result<_, _>>()?,
+                    )
+                }
+                "AGENT_MODE" => layer.agent_mode = Some(parse_env(&key, &value)?),
+                "TIMEOUT_SECS" => layer.timeout_secs = Some(parse_env(&key, &value)?),
+                "MODEL" => layer.model = Some(value.trim().to_string()),
+                "AGENT_ARGS" => {
+                    layer.agent_args = Some(value.split_whitespace().map(str::to_string).collect())
+                }
+                "MAX_DIFF_TOKENS" => layer.max_diff_tokens = Some(parse_env(&key, &value)?),
+                "MAX_DIFF_LENGTH" => layer.max_diff_length = Some(parse_env(&key, &value)?),
+                "STRATEGY" => layer.strategy = Some(parse_env(&key, &value)?),
+                "SUMMARY_CONCURRENCY" => layer.summary_concurrency = Some(parse_env(&key, &value)?),
+                "SIGNATURE" => layer.signature = Some(parse_env_bool(&key, &value)?),
+                "SIGNATURE_TEMPLATE" => {
+                    check_template(&value)?;
+                    layer.signature_template = Some(value);
+                }
+                "WRAP_COLUMN" => layer.wrap_column = Some(parse_env(&key, &value)?),
+                "SUBJECT_MAX_LENGTH" => layer.subject_max_length = Some(parse_env(&key, &value)?),
+                "LOWERCASE_SUBJECT" => {
+                    layer.lowercase_subject = Some(parse_env_bool(&key, &value)?)
+                }
+                "MAX_ATTEMPTS" => layer.max_attempts = Some(parse_env(&key, &value)?),
+                "RETRY_BACKOFF_MS" => layer.retry_backoff_ms = Some(parse_env(&key, &value)?),
+                "RETRY_TIMEOUTS" => layer.retry_timeouts = Some(parse_env_bool(&key, &value)?),
+                "REDACTION" => layer.redaction = Some(parse_env(&key, &value)?),
+                "REDACT_PATTERNS" => {
+                    layer.redact_patterns = Some(env_list(&key, &value, '\n', check_regex)?)
+                }
+                "HISTORY_EXAMPLES" => layer.history_examples = Some(parse_env(&key, &value)?),
+                "HISTORY_EXCLUDE_AUTHORS" => {
+                    layer.history_exclude_authors = Some(env_list(&key, &value, ',', |_| Ok(()))?)
+                }
+                "INFER_SCOPES" => layer.infer_scopes = Some(parse_env_bool(&key, &value)?),
+                "EXTRA_SCOPES" => {
+                    layer.extra_scopes = Some(env_list(&key, &value, ',', |scope| {
+                        is_valid_scope(scope)
+                            .then_some(())
+                            .ok_or_else(|| format!("invalid scope `{}`", scope))
+                    })?)
+                }
+                "LINT_SCOPES" => layer.lint_scopes = Some(parse_env_bool(&key, &value)?),
+                "EXCLUDE_COMMIT_TYPES" => {
+                    layer.exclude_commit_types = Some(env_list(&key, &value, ',', |name| {
+                        CommitType::is_valid_name(name)
+                            .then_some(())
+                            .ok_or_else(|| format!("invalid commit type `{}`", name))
+                    })?)
+                }
+                "BREAKING_CHANGES" => layer.breaking_changes = Some(parse_env(&key, &value)?),
+                "TICKET_KEYS" => {
+                    layer.ticket_keys = Some(env_list(&key, &value, ',', |ticket_key| {
+                        crate::ticket::is_valid_key(ticket_key)
+                            .then_some(())
+                            .ok_or_else(|| format!("invalid ticket key `{}`", ticket_key))
+                    })?)
+                }
+                "TICKET_PATTERNS" => {
+                    layer.ticket_patterns = Some(env_list(&key, &value, '\n', check_regex)?)
+                }
+                "TICKET_PLACEMENT" => layer.ticket_placement = Some(parse_env(&key, &value)?),
+                _ => {}
+            }
+        }
+
+        Ok(layer)
+    }
+}
+
+/// Parse an environment variable value via `FromStr`
+fn parse_env<T>(key: &str, value: &str) -> Result<T, ConfigError>
+where
+    T: std::str::FromStr,
+    T::Err: fmt::Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e: T::Err| ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// Split a list-valued environment variable, checking each entry
+///
+/// Entries are trimmed and empty ones skipped. Regex lists are split on
+/// newlines, since patterns may contain commas.
+fn env_list(
+    key: &str,
+    value: &str,
+    separator: char,
+    check: impl Fn(&str) -> Result<(), String>,
+) -> Result<Vec<String>, ConfigError> {
+    value
+        .split(separator)
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            check(entry)
+                .map(|()| entry.to_string())
+                .map_err(|reason| ConfigError::InvalidValue {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    reason,
+                })
+        })
+        .collect()
+}
+
+/// Check that a `*_PATTERNS` entry compiles
+fn check_regex(pattern: &str) -> Result<(), String> {
+    regex::Regex::new(pattern)
+        .map(drop)
+        .map_err(|e| e.to_string())
+}
+
+/// Whether `scope` can appear in a commit header
+fn is_valid_scope(scope: &str) -> bool {
+    !scope.is_empty() && !scope.contains(|c: char| c.is_whitespace() || "(),".contains(c))
+}
+
+/// Parse a boolean environment variable (`true/false`, `1/0`, `yes/no`, `on/off`)
+fn parse_env_bool(key: &str, value: &str) -> Result<bool, ConfigError> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "false" | "0" | "no" | "off" => Ok(false),
+        _ => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason: "expected true or false".to_string(),
+        }),
+    }
+}
+
+/// Where a resolved config value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in default
+    Default,
+    /// User-wide config file (`~/.config/commitment/config.toml`)
+    Global(PathBuf),
+    /// Repository config file (`<repo>/.commitment.toml`)
+    Repo(PathBuf),
+    /// `COMMITMENT_*` environment variables
+    Env,
+    /// Command-line flags
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::Global(path) => write!(f, "global config {}", path.display()),
+            Self::Repo(path) => write!(f, "repo config {}", path.display()),
+            Self::Env => write!(f, "environment"),
+            Self::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// Resolved configuration plus the source of each value
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    pub config: Config,
+    sources: HashMap<String, ConfigSource>,
+}
+
+impl LoadedConfig {
+    /// Merge layers in order, later layers taking precedence
+    pub fn resolve(layers: impl IntoIterator<Item = (ConfigSource, ConfigLayer)>) -> Self {
+        let mut loaded = Self {
+            config: Config::default(),
+            sources: HashMap::new(),
+        };
+        for (source, layer) in layers {
+            loaded.apply(layer, &source);
+        }
+        loaded
+    }
+
+    /// Reject merged settings that are valid per layer but unusable together
+    fn check(self) -> Result<Self, ConfigError> {
+        if self.config.commit_types.is_empty() {
+            return Err(ConfigError::InvalidValue {
+                key: "exclude_commit_types".to_string(),
+                value: "every type".to_string(),
+                reason: format!(
+                    "no commit types left (set in {})",
+                    self.source("commit_types")
+                ),
+            });
+        }
+        // A zero timeout would fail every agent call before it starts.
+        // (key, key its source is recorded under, seconds)
+        let timeouts = std::iter::once((
+            "timeout_secs".to_string(),
+            "timeout_secs".to_string(),
+            self.config.timeout_secs,
+        ))
+        .chain(self.config.agents.iter().filter_map(|(name, agent)| {
+            let section = format!("agents.{}", name);
+            Some((
+                format!("{}.timeout_secs", section),
+                section,
+                agent.timeout_secs()?,
+            ))
+        }));
+        for (key, source_key, secs) in timeouts {
+            if secs == 0 {
+                return Err(ConfigError::InvalidValue {
+                    key,
+                    value: secs.to_string(),
+                    reason: format!(
+                        "must be at least 1 second (set in {})",
+                        self.source(&source_key)
+                    ),
+                });
+            }
+        }
+        let agents = std::iter::once(("agent", &self.config.agent)).chain(
+            self.config
+                .fallback_agents
+                .iter()
+                .map(|agent| ("fallback_agents", agent)),
+        );
+        for (key, agent) in agents {
+            if let Err(reason) = self.config.check_agent(agent) {
+                return Err(ConfigError::InvalidValue {
+                    key: key.to_string(),
+                    value: agent.to_string(),
+                    reason: format!("{}; set in {}", reason, self.source(key)),
+                });
+            }
+        }
+        Ok(self)
+    }
+
+    fn apply(&mut self, layer: ConfigLayer, source: &ConfigSource) {
+        if let Some(agent) = layer.agent {
+            self.config.agent = agent;
+            self.sources.insert("agent".to_string(), source.clone());
+        }
+        if let Some(fallback_agents) = layer.fallback_agents {
+            self.config.fallback_agents = fallback_agents;
+            self.sources
+                .insert("fallback_agents".to_string(), source.clone());
+        }
+        if let Some(agent_mode) = layer.agent_mode {
+            self.config.agent_mode = agent_mode;
+            self.sources
+                .insert("agent_mode".to_string(), source.clone());
+        }
+        if let Some(timeout_secs) = layer.timeout_secs {
+            self.config.timeout_secs = timeout_secs;
+            self.config.timeout_overrides_agents = *source == ConfigSource::Cli;
+            self.sources
+                .insert("timeout_secs".to_string(), source.clone());
+        }
+        if let Some(model) = layer.model {
+            self.config.model = Some(model);
+            self.sources.insert("model".to_string(), source.clone());
+        }
+        if let Some(agent_args) = layer.agent_args {
+            self.config.agent_args = agent_args;
+            self.sources
+                .insert("agent_args".to_string(), source.clone());
+        }
+        // An explicit token budget in the same layer wins over the old byte limit
+        if let Some(max_diff_tokens) = layer
+            .max_diff_tokens
+            .or(layer.max_diff_length.map(|bytes| bytes / BYTES_PER_TOKEN))
+        {
+            self.config.max_diff_tokens = Some(max_diff_tokens);
+            self.sources
+                .insert("max_diff_tokens".to_string(), source.clone());
+        }
+        if let Some(strategy) = layer.strategy {
+            self.config.strategy = strategy;
+            self.sources.insert("strategy".to_string(), source.clone());
+        }
+        if let Some(summary_concurrency) = layer.summary_concurrency {
+            self.config.summary_concurrency = summary_concurrency;
+            self.sources
+                .insert("summary_concurrency".to_string(), source.clone());
+        }
+        if let Some(signature) = layer.signature {
+            self.config.signature = signature;
+            self.sources.insert("signature".to_string(), source.clone());
+        }
+        if let Some(signature_template) = layer.signature_template {
+            self.config.signature_template = signature_template;
+            self.sources
+                .insert("signature_template".to_string(), source.clone());
+        }
+        if let Some(wrap_column) = layer.wrap_column {
+            self.config.wrap_column = wrap_column;
+            self.sources
+                .insert("wrap_column".to_string(), source.clone());
+        }
+        if let Some(subject_max_length) = layer.subject_max_length {
+            self.config.subject_max_length = subject_max_length;
+            self.sources
+                .insert("subject_max_length".to_string(), source.clone());
+        }
+        if let Some(lowercase_subject) = layer.lowercase_subject {
+            self.config.lowercase_subject = lowercase_subject;
+            self.sources
+                .insert("lowercase_subject".to_string(), source.clone());
+        }
+        if let Some(max_attempts) = layer.max_attempts {
+            self.config.max_attempts = max_attempts;
+            self.sources
+                .insert("max_attempts".to_string(), source.clone());
+        }
+        if let Some(retry_backoff_ms) = layer.retry_backoff_ms {
+            self.config.retry_backoff_ms = retry_backoff_ms;
+            self.sources
+                .insert("retry_backoff_ms".to_string(), source.clone());
+        }
+        if let Some(retry_timeouts) = layer.retry_timeouts {
+            self.config.retry_timeouts = retry_timeouts;
+            self.sources
+                .insert("retry_timeouts".to_string(), source.clone());
+        }
+        if let Some(redaction) = layer.redaction {
+            self.config.redaction = redaction;
+            self.sources.insert("redaction".to_string(), source.clone());
+        }
+        if let Some(redact_patterns) = layer.redact_patterns {
+            self.config.redact_patterns = redact_patterns;
+            self.sources
+                .insert("redact_patterns".to_string(), source.clone());
+        }
+        if let Some(history_examples) = layer.history_examples {
+            self.config.history_examples = history_examples;
+            self.sources
+                .insert("history_examples".to_string(), source.clone());
+        }
+        if let Some(history_exclude_authors) = layer.history_exclude_authors {
+            self.config.history_exclude_authors = history_exclude_authors;
+            self.sources
+                .insert("history_exclude_authors".to_string(), source.clone());
+        }
+        if let Some(infer_scopes) = layer.infer_scopes {
+            self.config.infer_scopes = infer_scopes;
+            self.sources
+                .insert("infer_scopes".to_string(), source.clone());
+        }
+        for (dir, scope) in layer.scopes {
+            self.sources
+                .insert(format!("scopes.{}", dir), source.clone());
+            self.config.scopes.insert(dir, scope);
+        }
+        if let Some(extra_scopes) = layer.extra_scopes {
+            self.config.extra_scopes = extra_scopes;
+            self.sources
+                .insert("extra_scopes".to_string(), source.clone());
+        }
+        if let Some(lint_scopes) = layer.lint_scopes {
+            self.config.lint_scopes = lint_scopes;
+            self.sources
+                .insert("lint_scopes".to_string(), source.clone());
+        }
+        // Removals first, so a layer can replace a type it also excludes
+        if let Some(excluded) = layer.exclude_commit_types {
+            self.config
+                .commit_types
+                .retain(|commit_type| !excluded.contains(&commit_type.name));
+            self.sources
+                .insert("commit_types".to_string(), source.clone());
+        }
+        for (name, description) in layer.commit_types {
+            match self.config.commit_types.iter_mut().find(|t| t.name == name) {
+                Some(existing) => existing.description = description,
+                None => self
+                    .config
+                    .commit_types
+                    .push(CommitType::new(name, description)),
+            }
+            self.sources
+                .insert("commit_types".to_string(), source.clone());
+        }
+        if let Some(breaking_changes) = layer.breaking_changes {
+            self.config.breaking_changes = breaking_changes;
+            self.sources
+                .insert("breaking_changes".to_string(), source.clone());
+        }
+        if let Some(ticket_keys) = layer.ticket_keys {
+            self.config.ticket_keys = ticket_keys;
+            self.sources
+                .insert("ticket_keys".to_string(), source.clone());
+        }
+        if let Some(ticket_patterns) = layer.ticket_patterns {
+            self.config.ticket_patterns = ticket_patterns;
+            self.sources
+                .insert("ticket_patterns".to_string(), source.clone());
+        }
+        if let Some(ticket_placement) = layer.ticket_placement {
+            self.config.ticket_placement = ticket_placement;
+            self.sources
+                .insert("ticket_placement".to_string(), source.clone());
+        }
+        for (name, agent) in layer.agents {
+            self.sources
+                .insert(format!("agents.{}", name), source.clone());
+            self.config.agents.insert(name, agent);
+        }
+    }
+
+    /// Get the source of a config key (`Default` if no layer set it)
+    pub fn source(&self, key: &str) -> &ConfigSource {
+        self.sources.get(key).unwrap_or(&ConfigSource::Default)
+    }
+
+    /// All config keys with their display values and sources, in file order
+    pub fn entries(&self) -> Vec<(String, String, &ConfigSource)> {
+        let config = &self.config;
+        let mut entries = vec![
+            ("agent".to_string(), config.agent.to_string()),
+            (
+                "fallback_agents".to_string(),
+                format!(
+                    "[{}]",
+                    config
+                        .fallback_agents
+                        .iter()
+                        .map(|agent| format!("{:?}", agent.to_string()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+            ("agent_mode".to_string(), config.agent_mode.to_string()),
+            ("timeout_secs".to_string(), config.timeout_secs.to_string()),
+            (
+                "model".to_string(),
+                config
+                    .agent_model(&config.agent)
+                    .unwrap_or_else(|| "agent default".to_string()),
+            ),
+            ("agent_args".to_string(), format!("{:?}", config.agent_args)),
+            (
+                "max_diff_tokens".to_string(),
+                match config.max_diff_tokens {
+                    Some(tokens) => tokens.to_string(),
+                    None => format!(
+                        "auto ({} for {})",
+                        config.agent.diff_token_budget(),
+                        config.agent
+                    ),
+                },
+            ),
+            ("strategy".to_string(), config.strategy.to_string()),
+            (
+                "summary_concurrency".to_string(),
+                config.summary_concurrency.to_string(),
+            ),
+            ("signature".to_string(), config.signature.to_string()),
+            (
+                "signature_template".to_string(),
+                format!("{:?}", config.signature_template),
+            ),
+            ("wrap_column".to_string(), config.wrap_column.to_string()),
+            (
+                "subject_max_length".to_string(),
+                config.subject_max_length.to_string(),
+            ),
+            (
+                "lowercase_subject".to_string(),
+                config.lowercase_subject.to_string(),
+            ),
+            ("max_attempts".to_string(), config.max_attempts.to_string()),
+            (
+                "retry_backoff_ms".to_string(),
+                config.retry_backoff_ms.to_string(),
+            ),
+            (
+                "retry_timeouts".to_string(),
+                config.retry_timeouts.to_string(),
+            ),
+            ("redaction".to_string(), config.redaction.to_string()),
+            (
+                "redact_patterns".to_string(),
+                format!("{:?}", config.redact_patterns),
+            ),
+            (
+                "history_examples".to_string(),
+                config.history_examples.to_string(),
+            ),
+            (
+                "history_exclude_authors".to_string(),
+                format!("{:?}", config.history_exclude_authors),
+            ),
+            ("infer_scopes".to_string(), config.infer_scopes.to_string()),
+        ];
+        for (dir, scope) in &config.scopes {
+            entries.push((format!("scopes.{}", dir), scope.clone()));
+        }
+        entries.push((
+            "extra_scopes".to_string(),
+            format!("{:?}", config.extra_scopes),
+        ));
+        entries.push(("lint_scopes".to_string(), config.lint_scopes.to_string()));
+        entries.push((
+            "commit_types".to_string(),
+            commit_types_display(&config.commit_types),
+        ));
+        entries.push((
+            "breaking_changes".to_string(),
+            config.breaking_changes.to_string(),
+        ));
+        entries.push((
+            "ticket_keys".to_string(),
+            format!("{:?}", config.ticket_keys),
+        ));
+        entries.push((
+            "ticket_patterns".to_string(),
+            format!("{:?}", config.ticket_patterns),
+        ));
+        entries.push((
+            "ticket_placement".to_string(),
+            config.ticket_placement.to_string(),
+        ));
+        for (name, agent) in &config.agents {
+            entries.push((format!("agents.{}", name), agent.summary(name)));
+        }
+
+        entries
+            .into_iter()
+            .map(|(key, value)| {
+                let source = self.source(&key);
+                (key, value, source)
+            })
+            .collect()
+    }
+}
+
+/// Path of the user-wide config file
+///
+/// Honors `$XDG_CONFIG_HOME`, falling back to `~/.config/commitment/config.toml`.
+pub fn global_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("commitment").join("config.toml"))
+}
+
+/// Find the repository root by walking up from `cwd` to the first `.git` entry
+///
+/// `.git` may be a directory (regular repo) or a file (worktree).
+pub fn find_repo_root(cwd: &Path) -> Option<PathBuf> {
+    cwd.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Load configuration from all sources
+///
+/// Precedence (lowest to highest):
+/// 1. Built-in defaults
+/// 2. Global config file
+/// 3. Repository `.commitment.toml`
+/// 4. `COMMITMENT_*` environment variables
+/// 5. CLI flags (`cli` layer)
+pub fn load_config(cwd: &Path, cli: ConfigLayer) -> Result<LoadedConfig, ConfigError> {
+    load_config_from(cwd, global_config_path(), std::env::vars(), cli)
+}
+
+/// Load configuration like [`load_config`], from the given global config
+/// file and environment instead of the user's
+pub fn load_config_from(
+    cwd: &Path,
+    global: Option<PathBuf>,
+    env: impl IntoIterator<Item = (String, String)>,
+    cli: ConfigLayer,
+) -> Result<LoadedConfig, ConfigError> {
+    let mut layers = Vec::new();
+
+    if let Some(path) = global
+        && let Some(layer) = ConfigLayer::from_file(&path)?
+    {
+        layers.push((ConfigSource::Global(path), layer));
+    }
+
+    let repo_root = find_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+    let repo_path = repo_root.join(REPO_CONFIG_FILE);
+    if let Some(layer) = ConfigLayer::from_file(&repo_path)? {
+        layers.push((ConfigSource::Repo(repo_path), layer));
+    }
+
+    layers.push((ConfigSource::Env, ConfigLayer::from_env_vars(env)?));
+    layers.push((ConfigSource::Cli, cli));
+
+    LoadedConfig::resolve(layers).check()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn command_args(agent: &AgentConfig) -> &[String] {
+        match agent {
+            AgentConfig::Command(config) => &config.args,
+            AgentConfig::Builtin(_) | AgentConfig::Http(_) => panic!("expected command agent"),
+        }
+    }
+
+    #[test]
+    fn default_config_matches_previous_constants() {
+        let config = Config::default();
+        assert_eq!(config.agent, AgentName::Claude);
+        assert_eq!(config.timeout_secs, 120);
+        assert_eq!(config.max_diff_tokens, None);
+        assert_eq!(
+            config.diff_token_budget(&AgentName::Custom("llm".into())),
+            2000
+        );
+        assert!(config.signature);
+        assert_eq!(config.max_attempts, 3);
+        assert_eq!(
+            config.retry_backoff(),
+            std::time::Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn parses_toml_layer() {
+        let layer = ConfigLayer::from_toml(
+            "agent = \"codex\"\ntimeout_secs = 30\nsignature = false\n",
+            Path::new("test.toml"),
+        )
+        .unwrap();
+        assert_eq!(layer.agent, Some(AgentName::Codex));
+        assert_eq!(layer.timeout_secs, Some(30));
+        assert_eq!(layer.max_diff_length, None);
+        assert_eq!(layer.signature, Some(false));
+    }
+
+    #[test]
+    fn rejects_unknown_toml_keys() {
+        let result = ConfigLayer::from_toml("agnet = \"codex\"", Path::new("test.toml"));
+        match result {
+            Err(ConfigError::Parse { path, reason }) => {
+                assert_eq!(path, "test.toml");
+                assert!(reason.contains("agnet"));
+            }
+            other => panic!("expected Parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_agent_in_toml() {
+        let result = ConfigLayer::from_toml("agent = \"not valid!\"", Path::new("test.toml"));
+        assert!(matches!(result, Err(ConfigError::Parse { .. })));
+    }
+
+    #[test]
+    fn parses_custom_agent_sections() {
+        let layer = ConfigLayer::from_toml(
+            r#"
+agent = "ollama"
+
+[agents.ollama]
+args = ["run", "llama3"]
+"#,
+            Path::new("test.toml"),
+        )
+        .unwrap();
+        assert_eq!(layer.agent, Some(AgentName::Custom("ollama".to_string())));
+        assert_eq!(command_args(&layer.agents["ollama"]), ["run", "llama3"]);
+    }
+
+    #[test]
+    fn parses_http_agent_sections() {
+        let layer = ConfigLayer::from_toml(
+            r#"
+[agents.local]
+api = "openai"
+base_url = "http://localhost:8080/v1"
+model = "qwen2.5-coder"
+"#,
+            Path::new("test.toml"),
+        )
+        .unwrap();
+        match &layer.agents["local"] {
+            AgentConfig::Http(config) => assert_eq!(config.model.as_deref(), Some("qwen2.5-coder")),
+            other => panic!("expected HTTP agent, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_http_agent_api() {
+        let result = ConfigLayer::from_toml("[agents.local]\napi = \"grpc\"", Path::new("t.toml"));
+        assert!(matches!(result, Err(ConfigError::Parse { .. })));
+    }
+
+    #[test]
+    fn rejects_custom_agent_shadowing_builtin() {
+        let result =
+            ConfigLayer::from_toml("[agents.claude]\ncommand = \"x\"", Path::new("t.toml"));
+        match result {
+            Err(ConfigError::Parse { reason, .. }) => assert!(reason.contains("built-in")),
+            other => panic!("expected Parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_custom_agent_with_unparseable_name() {
+        let result = ConfigLayer::from_toml("[agents.\"My LLM\"]", Path::new("t.toml"));
+        assert!(matches!(result, Err(ConfigError::Parse { .. })));
+    }
+
+    #[test]
+    fn custom_agents_merge_across_layers() {
+        let global = ConfigLayer::from_toml(
+            "[agents.ollama]\nargs = [\"run\", \"llama3\"]\n[agents.llm]\n",
+            Path::new("global.toml"),
+        )
+        .unwrap();
+        let repo = ConfigLayer::from_toml(
+            "[agents.ollama]\nargs = [\"run\", \"qwen\"]\n",
+            Path::new("repo.toml"),
+        )
+        .unwrap();
+        let repo_path = PathBuf::from("/repo/.commitment.toml");
+
+        let loaded = LoadedConfig::resolve([
+            (ConfigSource::Global(PathBuf::from("/g.toml")), global),
+            (ConfigSource::Repo(repo_path.clone()), repo),
+        ]);
+
+        assert_eq!(loaded.config.agents.len(), 2);
+        assert_eq!(
+            command_args(&loaded.config.agents["ollama"]),
+            ["run", "qwen"]
+        );
+        assert_eq!(
+            loaded.source("agents.ollama"),
+            &ConfigSource::Repo(repo_path)
+        );
+        assert!(
+            loaded
+                .entries()
+                .iter()
+                .any(|(key, value, _)| key == "agents.ollama" && value.contains("ollama run qwen"))
+        );
+    }
+
+    #[test]
+    fn parses_redaction_settings() {
+        let layer = ConfigLayer::from_toml(
+            "redaction = \"strict\"\nredact_patterns = ['corp-[0-9]{6}']\n",
+            Path::new("test.toml"),
+        )
+        .unwrap();
+        assert_eq!(layer.redaction, Some(RedactionMode::Strict));
+        assert_eq!(
+            layer.redact_patterns,
+            Some(vec!["corp-[0-9]{6}".to_string()])
+        );
+    }
+
+    #[test]
+    fn parses_strategy_settings() {
+        let layer = ConfigLayer::from_toml(
+            "strategy = \"summarize\"\nsummary_concurrency = 2\n",
+            Path::new("test.toml"),
+        )
+        .unwrap();
+        assert_eq!(layer.strategy, Some(DiffStrategy::Summarize));
+        assert_eq!(layer.summary_concurrency, Some(2));
+
+        let layer =
+            ConfigLayer::from_env_vars(env(&[("COMMITMENT_STRATEGY", "summarize")])).unwrap();
+        assert_eq!(layer.strategy, Some(DiffStrategy::Summarize));
+    }
+
+    #[test]
+    fn parses_history_settings() {
+        let layer = ConfigLayer::from_toml(
+            "history_examples = 5\nhistory_exclude_authors = [\"renovate[bot]\"]\n",
+            Path::new("test.toml"),
+        )
+        .unwrap();
+        assert_eq!(layer.history_examples, Some(5));
+        assert_eq!(
+            layer.history_exclude_authors,
+            Some(vec!["renovate[bot]".to_string()])
+        );
+
+        let layer =
+            ConfigLayer::from_env_vars(env(&[("COMMITMENT_HISTORY_EXAMPLES", "0")])).unwrap();
+        assert_eq!(layer.history_examples, Some(0));
+    }
+
+    #[test]
+    fn commit_types_can_be_added_described_and_excluded() {
+        let global = ConfigLayer::from_toml(
+            "[commit_types]\ndeps = \"Dependency updates\"\n",
+            Path::new("global.toml"),
+        )
+        .unwrap();
+        let repo = ConfigLayer::from_toml(
+            "exclude_commit_types = [\"chore\"]\n[commit_types]\nsecurity = \"Fixes a vulnerability\"\nfix = \"A user-visible bug fix\"\n",
+            Path::new("repo.toml"),
+        )
+        .unwrap();
+
+        let loaded = LoadedConfig::resolve([
+            (ConfigSource::Global(PathBuf::from("g")), global),
+            (ConfigSource::Repo(PathBuf::from("r")), repo),
+        ]);
+
+        let types = &loaded.config.commit_types;
+        assert_eq!(
+            commit_types_display(types),
+            "feat, fix, docs, style, refactor, test, perf, build, ci, revert, deps, security"
+        );
+        assert_eq!(types[1].description, "A user-visible bug fix");
+        assert_eq!(
+            loaded.source("commit_types"),
+            &ConfigSource::Repo(PathBuf::from("r"))
+        );
+
+        let result = ConfigLayer::from_toml("[commit_types]\nFix = \"x\"\n", Path::new("t.toml"));
+        assert!(matches!(result, Err(ConfigError::Parse { .. })));
+    }
+
+    #[test]
+    fn parses_ticket_settings() {
+        let layer = ConfigLayer::from_toml(
+            "ticket_keys = [\"PROJ\"]\nticket_patterns = ['(?P<ticket>eng-[0-9]+)']\nticket_placement = \"prefix\"\n",
+            Path::new("test.toml"),
+        )
+        .unwrap();
+        assert_eq!(
+            layer.ticket_patterns,
+            Some(vec!["(?P<ticket>eng-[0-9]+)".to_string()])
+        );
+        assert_eq!(layer.ticket_placement, Some(TicketPlacement::Prefix));
+        assert_eq!(layer.ticket_keys, Some(vec!["PROJ".to_string()]));
+        assert!(ConfigLayer::from_toml("ticket_keys = [\"PROJ-\"]", Path::new("t.toml")).is_err());
+        assert!(Config::default().ticket_keys.is_empty());
+        assert!(Config::default().ticket_patterns.is_empty());
+
+        let layer =
+            ConfigLayer::from_env_vars(env(&[("COMMITMENT_TICKET_PLACEMENT", "off")])).unwrap();
+        assert_eq!(layer.ticket_placement, Some(TicketPlacement::Off));
+
+        let result = ConfigLayer::from_toml("ticket_patterns = ['(oops']", Path::new("test.toml"));
+        assert!(matches!(result, Err(ConfigError::Parse { .. })));
+    }
+
+    #[test]
+    fn renders_signature_template_for_the_agent() {
+        let layer = ConfigLayer::from_toml(
+            r#"
+signature_template = "Assisted-by: {agent} ({model})"
+
+[agents.local]
+api = "ollama"
+model = "llama3"
+"#,
+            Path::new("test.toml"),
+        )
+        .unwrap();
+        let config =
+            LoadedConfig::resolve([(ConfigSource::Repo(PathBuf::from("r")), layer)]).config;
+
+        assert_eq!(
+            config.signature_for(&AgentName::Custom("local".to_string())),
+            Some("Assisted-by: local (llama3)".to_string())
+        );
+        assert_eq!(
+            Config::default().signature_for(&AgentName::Claude),
+            Some(AgentName::Claude.commit_signature())
+        );
+        let disabled = Config {
+            signature: false,
+            ..config
+        };
+        assert_eq!(disabled.signature_for(&AgentName::Claude), None);
+
+        let result = ConfigLayer::from_toml("signature_template = \"{who}\"", Path::new("t.toml"));
+        assert!(matches!(result, Err(ConfigError::Parse { .. })));
+    }
+
+    #[test]
+    fn agent_settings_combine_section_and_overrides() {
+        let repo = ConfigLayer::from_toml(
+            r#"
+timeout_secs = 60
+
+[agents.claude]
+model = "sonnet"
+args = ["--max-turns", "1"]
+env = { ANTHROPIC_LOG = "error" }
+cwd = "/tmp"
+timeout_secs = 30
+"#,
+            Path::new("t.toml"),
+        )
+        .unwrap();
+        let env = ConfigLayer::from_env_vars(env(&[("COMMITMENT_MODEL", "haiku")])).unwrap();
+        let cli = ConfigLayer {
+            agent_args: Some(vec!["--verbose".to_string()]),
+            ..ConfigLayer::default()
+        };
+        let loaded = LoadedConfig::resolve([
+            (ConfigSource::Repo(PathBuf::from("r")), repo),
+            (ConfigSource::Env, env),
+            (ConfigSource::Cli, cli),
+        ]);
+        let config = &loaded.config;
+
+        let settings = config.agent_settings(&AgentName::Claude);
+        assert_eq!(settings.model.as_deref(), Some("haiku"));
+        assert_eq!(settings.args, ["--max-turns", "1", "--verbose"]);
+        assert_eq!(settings.env["ANTHROPIC_LOG"], "error");
+        assert_eq!(settings.cwd, Some(PathBuf::from("/tmp")));
+        assert_eq!(settings.timeout, std::time::Duration::from_secs(30));
+        assert_eq!(loaded.source("model"), &ConfigSource::Env);
+        assert_eq!(loaded.source("agent_args"), &ConfigSource::Cli);
+
+        // The overrides only apply to the configured agent
+        let codex = config.agent_settings(&AgentName::Codex);
+        assert_eq!(codex.model, None);
+        assert!(codex.args.is_empty());
+        assert_eq!(codex.timeout, std::time::Duration::from_secs(60));
+        let switched = config.single_agent(AgentName::Codex);
+        assert_eq!(switched.model, None);
+        assert!(switched.agent_args.is_empty());
+    }
+
+    #[test]
+    fn cli_timeout_beats_agent_section_timeouts() {
+        let repo =
+            ConfigLayer::from_toml("[agents.claude]\ntimeout_secs = 30\n", Path::new("t.toml"))
+                .unwrap();
+        let resolve = |source: ConfigSource| {
+            LoadedConfig::resolve([
+                (ConfigSource::Repo(PathBuf::from("r")), repo.clone()),
+                (
+                    source,
+                    ConfigLayer {
+                        timeout_secs: Some(300),
+                        ..ConfigLayer::default()
+                    },
+                ),
+            ])
+            .config
+            .agent_settings(&AgentName::Claude)
+            .timeout
+        };
+
+        assert_eq!(
+            resolve(ConfigSource::Cli),
+            std::time::Duration::from_secs(300)
+        );
+        // A configured global timeout is less specific than the section's
+        assert_eq!(
+            resolve(ConfigSource::Env),
+            std::time::Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn command_agents_only_report_a_model_they_pass_on() {
+        let layer = ConfigLayer::from_toml(
+            r#"
+agent = "plain"
+model = "haiku"
+
+[agents.plain]
+args = ["run"]
+
+[agents.templated]
+args = ["-m", "{model}"]
+model = "llama3"
+"#,
+            Path::new("t.toml"),
+        )
+        .unwrap();
+        let config =
+            LoadedConfig::resolve([(ConfigSource::Repo(PathBuf::from("r")), layer)]).config;
+        let plain = AgentName::Custom("plain".to_string());
+        let templated = AgentName::Custom("templated".to_string());
+
+        assert_eq!(config.agent_model(&plain), None);
+        assert_eq!(config.signature_for(&plain), Some(plain.commit_signature()));
+        assert_eq!(config.agent_model(&templated).as_deref(), Some("llama3"));
+        assert_eq!(
+            config.agent_model(&AgentName::Custom("unconfigured".to_string())),
+            None
+        );
+    }
+
+    #[test]
+    fn relative_agent_cwd_is_resolved_against_the_config_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(REPO_CONFIG_FILE);
+        fs::write(
+            &path,
+            "[agents.claude]\ncwd = \"tools\"\n\n[agents.local]\ncwd = \"/tmp\"\n",
+        )
+        .unwrap();
+
+        let layer = ConfigLayer::from_file(&path).unwrap().unwrap();
+        let config = LoadedConfig::resolve([(ConfigSource::Repo(path), layer)]).config;
+
+        assert_eq!(
+            config.agent_settings(&AgentName::Claude).cwd,
+            Some(dir.path().join("tools"))
+        );
+        assert_eq!(
+            config
+                .agent_settings(&AgentName::Custom("local".to_string()))
+                .cwd,
+            Some(PathBuf::from("/tmp"))
+        );
+    }
+
+    #[test]
+    fn default_signature_names_the_model() {
+        let config = Config {
+            model: Some("haiku".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(
+            config.signature_for(&AgentName::Claude),
+            Some("🤖 Generated with Claude (haiku) via commitment".to_string())
+        );
+        assert_eq!(
+            config.signature_for(&AgentName::Codex),
+            Some(AgentName::Codex.commit_signature())
+        );
+    }
+
+    #[test]
+    fn merges_scope_tables_per_directory() {
+        let global = ConfigLayer::from_toml(
+            "[scopes]\n\"crates/core\" = \"core\"\n\"docs\" = \"docs\"\n",
+            Path::new("global.toml"),
+        )
+        .unwrap();
+        let repo = ConfigLayer::from_toml(
+            "infer_scopes = false\n[scopes]\n\"crates/core\" = \"engine\"\n",
+            Path::new("repo.toml"),
+        )
+        .unwrap();
+
+        let loaded = LoadedConfig::resolve([
+            (ConfigSource::Global(PathBuf::from("g")), global),
+            (ConfigSource::Repo(PathBuf::from("r")), repo),
+        ]);
+
+        assert!(!loaded.config.infer_scopes);
+        assert_eq!(loaded.config.scopes["crates/core"], "engine");
+        assert_eq!(loaded.config.scopes["docs"], "docs");
+        assert_eq!(
+            loaded.source("scopes.docs"),
+            &ConfigSource::Global(PathBuf::from("g"))
+        );
+
+        let result = ConfigLayer::from_toml("[scopes]\nweb = \"my web\"\n", Path::new("t.toml"));
+        assert!(matches!(result, Err(ConfigError::Parse { .. })));
+    }
+
+    #[test]
+    fn rejects_excluding_every_commit_type() {
+        let all: Vec<String> = default_commit_types().into_iter().map(|t| t.name).collect();
+        let layer = ConfigLayer {
+            exclude_commit_types: Some(all),
+            ..ConfigLayer::default()
+        };
+        let result = LoadedConfig::resolve([(ConfigSource::Env, layer.clone())]).check();
+        match result {
+            Err(ConfigError::InvalidValue { key, reason, .. }) => {
+                assert_eq!(key, "exclude_commit_types");
+                assert!(reason.contains("environment"));
+            }
+            other => panic!("expected InvalidValue, got {:?}", other.map(|l| l.config)),
+        }
+
+        // Replacing them with custom types is fine
+        let layer = ConfigLayer {
+            commit_types: BTreeMap::from([("deps".to_string(), "Dependency bumps".to_string())]),
+            ..layer
+        };
+        assert!(
+            LoadedConfig::resolve([(ConfigSource::Env, layer)])
+                .check()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_agents_without_a_section() {
+        let cli = ConfigLayer {
+            agent: Some("claud".parse().unwrap()),
+            ..ConfigLayer::default()
+        };
+        match LoadedConfig::resolve([(ConfigSource::Cli, cli)]).check() {
+            Err(ConfigError::InvalidValue { key, reason, .. }) => {
+                assert_eq!(key, "agent");
+                assert!(reason.contains("did you mean `claude`?"));
+                assert!(reason.contains("command line"));
+            }
+            other => panic!("expected InvalidValue, got {:?}", other.map(|l| l.config)),
+        }
+
+        let repo = ConfigLayer::from_toml(
+            "fallback_agents = [\"ollama\", \"llm\"]\n[agents.ollama]\n",
+            Path::new("t.toml"),
+        )
+        .unwrap();
+        match LoadedConfig::resolve([(ConfigSource::Repo("t.toml".into()), repo)]).check() {
+            Err(ConfigError::InvalidValue { key, value, reason }) => {
+                assert_eq!(key, "fallback_agents");
+                assert_eq!(value, "llm");
+                assert!(reason.contains("[agents.llm]"));
+            }
+            other => panic!("expected InvalidValue, got {:?}", other.map(|l| l.config)),
+        }
+    }
+
+    #[test]
+    fn parses_extra_scopes_and_lint_scopes() {
+        let layer = ConfigLayer::from_toml(
+            "extra_scopes = [\"ci\", \"deps\"]\nlint_scopes = true\n",
+            Path::new("t.toml"),
+        )
+        .unwrap();
+        assert_eq!(
+            layer.extra_scopes,
+            Some(vec!["ci".to_string(), "deps".to_string()])
+        );
+        assert_eq!(layer.lint_scopes, Some(true));
+        assert!(ConfigLayer::from_toml("extra_scopes = [\"a b\"]", Path::new("t.toml")).is_err());
+        assert_eq!(
+            ConfigLayer::from_env_vars(env(&[("COMMITMENT_LINT_SCOPES", "1")]))
+                .unwrap()
+                .lint_scopes,
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_redact_pattern() {
+        let result = ConfigLayer::from_toml("redact_patterns = ['(oops']", Path::new("test.toml"));
+        match result {
+            Err(ConfigError::Parse { path, reason }) => {
+                assert_eq!(path, "test.toml");
+                assert!(reason.contains("redact_patterns"));
+            }
+            other => panic!("expected Parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn missing_file_yields_no_layer() {
+        let dir = TempDir::new().unwrap();
+        let layer = ConfigLayer::from_file(&dir.path().join("missing.toml")).unwrap();
+        assert!(layer.is_none());
+    }
+
+    #[test]
+    fn parses_env_layer() {
+        let layer = ConfigLayer::from_env_vars(env(&[
+            ("COMMITMENT_AGENT", "gemini"),
+            ("COMMITMENT_TIMEOUT_SECS", "45"),
+            ("COMMITMENT_SIGNATURE", "off"),
+            ("COMMITMENT_MAX_ATTEMPTS", "5"),
+            ("COMMITMENT_RETRY_BACKOFF_MS", "250"),
+            ("COMMITMENT_RETRY_TIMEOUTS", "yes"),
+            ("COMMITMENT_REDACTION", "strict"),
+            ("PATH", "/usr/bin"),
+        ]))
+        .unwrap();
+        assert_eq!(layer.agent, Some(AgentName::Gemini));
+        assert_eq!(layer.timeout_secs, Some(45));
+        assert_eq!(layer.signature, Some(false));
+        assert_eq!(layer.max_attempts, Some(5));
+        assert_eq!(layer.retry_backoff_ms, Some(250));
+        assert_eq!(layer.retry_timeouts, Some(true));
+        assert_eq!(layer.redaction, Some(RedactionMode::Strict));
+        assert_eq!(layer.max_diff_length, None);
+    }
+
+    #[test]
+    fn fallback_agents_form_a_chain_without_repeats() {
+        let layer = ConfigLayer::from_env_vars(env(&[
+            ("COMMITMENT_FALLBACK_AGENTS", "codex, claude,gemini"),
+            ("COMMITMENT_AGENT_MODE", "race"),
+        ]))
+        .unwrap();
+        let loaded = LoadedConfig::resolve([(ConfigSource::Env, layer)]);
+
+        assert_eq!(
+            loaded.config.agent_chain(),
+            [AgentName::Claude, AgentName::Codex, AgentName::Gemini]
+        );
+        assert_eq!(
+            loaded.config.single_agent(AgentName::Codex).agent_chain(),
+            [AgentName::Codex]
+        );
+        assert_eq!(loaded.source("fallback_agents"), &ConfigSource::Env);
+        assert_eq!(loaded.config.agent_mode, AgentMode::Race);
+
+        let layer = ConfigLayer::from_toml("fallback_agents = [\"gemini\"]", Path::new("t.toml"));
+        assert_eq!(
+            layer.unwrap().fallback_agents,
+            Some(vec![AgentName::Gemini])
+        );
+    }
+
+    #[test]
+    fn parses_env_lists() {
+        let layer = ConfigLayer::from_env_vars(env(&[
+            (
+                "COMMITMENT_REDACT_PATTERNS",
+                "corp-[0-9]{4,6}\n\n  token_[a-z]+ ",
+            ),
+            ("COMMITMENT_TICKET_PATTERNS", "(?P<ticket>eng-[0-9]+)"),
+            (
+                "COMMITMENT_HISTORY_EXCLUDE_AUTHORS",
+                "dependabot[bot], bot@example.com",
+            ),
+            ("COMMITMENT_EXCLUDE_COMMIT_TYPES", "chore,"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            layer.redact_patterns.unwrap(),
+            ["corp-[0-9]{4,6}", "token_[a-z]+"]
+        );
+        assert_eq!(layer.ticket_patterns.unwrap(), ["(?P<ticket>eng-[0-9]+)"]);
+        assert_eq!(
+            layer.history_exclude_authors.unwrap(),
+            ["dependabot[bot]", "bot@example.com"]
+        );
+        assert_eq!(layer.exclude_commit_types.unwrap(), ["chore"]);
+
+        for (key, value) in [
+            ("COMMITMENT_REDACT_PATTERNS", "(oops"),
+            ("COMMITMENT_EXTRA_SCOPES", "api,my scope"),
+            ("COMMITMENT_EXCLUDE_COMMIT_TYPES", "Chore!"),
+        ] {
+            match ConfigLayer::from_env_vars(env(&[(key, value)])) {
+                Err(ConfigError::InvalidValue { key: reported, .. }) => assert_eq!(reported, key),
+                other => panic!("expected InvalidValue for {}, got {:?}", key, other),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_env_value() {
+        let result = ConfigLayer::from_env_vars(env(&[("COMMITMENT_TIMEOUT_SECS", "soon")]));
+        match result {
+            Err(ConfigError::InvalidValue { key, value, .. }) => {
+                assert_eq!(key, "COMMITMENT_TIMEOUT_SECS");
+                assert_eq!(value, "soon");
+            }
+            other => panic!("expected InvalidValue error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn later_layers_override_earlier_layers() {
+        let global = PathBuf::from("/home/user/.config/commitment/config.toml");
+        let loaded = LoadedConfig::resolve([
+            (
+                ConfigSource::Global(global.clone()),
+                ConfigLayer {
+                    agent: Some(AgentName::Codex),
+                    timeout_secs: Some(60),
+                    ..Default::default()
+                },
+            ),
+            (
+                ConfigSource::Env,
+                ConfigLayer {
+                    timeout_secs: Some(30),
+                    ..Default::default()
+                },
+            ),
+            (
+                ConfigSource::Cli,
+                ConfigLayer {
+                    agent: Some(AgentName::Gemini),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        assert_eq!(loaded.config.agent, AgentName::Gemini);
+        assert_eq!(loaded.config.timeout_secs, 30);
+        assert_eq!(loaded.config.max_diff_tokens, None);
+        assert_eq!(loaded.source("agent"), &ConfigSource::Cli);
+        assert_eq!(loaded.source("timeout_secs"), &ConfigSource::Env);
+        assert_eq!(loaded.source("max_diff_tokens"), &ConfigSource::Default);
+    }
+
+    #[test]
+    fn deprecated_max_diff_length_converts_to_tokens() {
+        let loaded = LoadedConfig::resolve([
+            (
+                ConfigSource::Env,
+                ConfigLayer {
+                    max_diff_length: Some(8000),
+                    ..Default::default()
+                },
+            ),
+            (
+                ConfigSource::Cli,
+                ConfigLayer {
+                    max_diff_length: Some(4000),
+                    max_diff_tokens: Some(3000),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        assert_eq!(loaded.config.max_diff_tokens, Some(3000));
+
+        let loaded = LoadedConfig::resolve([(
+            ConfigSource::Env,
+            ConfigLayer {
+                max_diff_length: Some(8000),
+                ..Default::default()
+            },
+        )]);
+        assert_eq!(loaded.config.max_diff_tokens, Some(2000));
+        assert_eq!(loaded.source("max_diff_tokens"), &ConfigSource::Env);
+    }
+
+    #[test]
+    fn diff_token_budget_defaults_per_agent() {
+        let config = Config::default();
+        assert_eq!(config.diff_token_budget(&AgentName::Claude), 16_000);
+        assert_eq!(config.diff_token_budget(&AgentName::Gemini), 24_000);
+
+        let config = Config {
+            max_diff_tokens: Some(500),
+            ..Config::default()
+        };
+        assert_eq!(config.diff_token_budget(&AgentName::Gemini), 500);
+    }
+
+    #[test]
+    fn entries_report_value_and_source() {
+        let repo = PathBuf::from("/repo/.commitment.toml");
+        let loaded = LoadedConfig::resolve([(
+            ConfigSource::Repo(repo.clone()),
+            ConfigLayer {
+                signature: Some(false),
+                ..Default::default()
+            },
+        )]);
+
+        let entries = loaded.entries();
+        let (_, value, source) = entries
+            .iter()
+            .find(|(key, _, _)| key == "signature")
+            .unwrap();
+        assert_eq!(value, "false");
+        assert_eq!(*source, &ConfigSource::Repo(repo));
+    }
+
+    #[test]
+    fn config_source_display() {
+        assert_eq!(ConfigSource::Default.to_string(), "default");
+        assert_eq!(ConfigSource::Env.to_string(), "environment");
+        assert_eq!(ConfigSource::Cli.to_string(), "command line");
+        assert!(
+            ConfigSource::Repo(PathBuf::from("/repo/.commitment.toml"))
+                .to_string()
+                .contains("/repo/.commitment.toml")
+        );
+    }
+
+    #[test]
+    fn find_repo_root_walks_up_to_git_dir() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        let nested = dir.path().join("src/nested");
+        fs::create_dir_all(&nested).unwrap();
+
+        assert_eq!(find_repo_root(&nested), Some(dir.path().to_path_buf()));
+    }
+
+    #[test]
+    fn list_values_follow_file_env_cli_precedence() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(
+            dir.path().join(REPO_CONFIG_FILE),
+            "agent_args = [\"--file\"]\nextra_scopes = [\"docs\"]\n",
+        )
+        .unwrap();
+        let load = |vars: &[(&str, &str)], cli: ConfigLayer| {
+            load_config_from(dir.path(), None, env(vars), cli).unwrap()
+        };
+        let cli = || ConfigLayer {
+            agent_args: Some(vec!["--cli".to_string()]),
+            ..Default::default()
+        };
+        let vars = [
+            ("COMMITMENT_AGENT_ARGS", "--env --verbose"),
+            ("COMMITMENT_EXTRA_SCOPES", "ci, deps"),
+        ];
+
+        let loaded = load(&[], ConfigLayer::default());
+        assert_eq!(loaded.config.agent_args, ["--file"]);
+        assert_eq!(loaded.config.extra_scopes, ["docs"]);
+        assert!(matches!(loaded.source("agent_args"), ConfigSource::Repo(_)));
+
+        let loaded = load(&vars, ConfigLayer::default());
+        assert_eq!(loaded.config.agent_args, ["--env", "--verbose"]);
+        assert_eq!(loaded.config.extra_scopes, ["ci", "deps"]);
+        assert_eq!(loaded.source("agent_args"), &ConfigSource::Env);
+
+        let loaded = load(&vars, cli());
+        assert_eq!(loaded.config.agent_args, ["--cli"]);
+        assert_eq!(loaded.source("agent_args"), &ConfigSource::Cli);
+        assert_eq!(loaded.source("extra_scopes"), &ConfigSource::Env);
+    }
+
+    #[test]
+    fn load_config_reads_repo_file_and_cli_layer() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(
+            dir.path().join(REPO_CONFIG_FILE),
+            "agent = \"codex\"\nmax_diff_length = 4000\n",
+        )
+        .unwrap();
+        let global = dir.path().join("global.toml");
+        fs::write(&global, "agent = \"gemini\"\ntimeout_secs = 30\n").unwrap();
+
+        let loaded = load_config_from(
+            dir.path(),
+            Some(global.clone()),
+            env(&[("COMMITMENT_MAX_ATTEMPTS", "5")]),
+            ConfigLayer {
+                max_diff_tokens: Some(2000),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(loaded.config.max_diff_tokens, Some(2000));
+        assert_eq!(loaded.source("max_diff_tokens"), &ConfigSource::Cli);
+        assert_eq!(loaded.config.agent, AgentName::Codex);
+        assert!(matches!(loaded.source("agent"), ConfigSource::Repo(_)));
+        assert_eq!(loaded.config.timeout_secs, 30);
+        assert_eq!(loaded.source("timeout_secs"), &ConfigSource::Global(global));
+        assert_eq!(loaded.config.max_attempts, 5);
+        assert_eq!(loaded.source("max_attempts"), &ConfigSource::Env);
+    }
+
+    #[test]
+    fn rejects_zero_timeouts() {
+        let env_layer = ConfigLayer {
+            timeout_secs: Some(0),
+            ..ConfigLayer::default()
+        };
+        match LoadedConfig::resolve([(ConfigSource::Env, env_layer)]).check() {
+            Err(ConfigError::InvalidValue { key, reason, .. }) => {
+                assert_eq!(key, "timeout_secs");
+                assert!(reason.contains("environment"));
+            }
+            other => panic!("expected InvalidValue, got {:?}", other.map(|l| l.config)),
+        }
+
+        let repo =
+            ConfigLayer::from_toml("[agents.claude]\ntimeout_secs = 0\n", Path::new("t.toml"))
+                .unwrap();
+        match LoadedConfig::resolve([(ConfigSource::Repo("t.toml".into()), repo)]).check() {
+            Err(ConfigError::InvalidValue { key, reason, .. }) => {
+                assert_eq!(key, "agents.claude.timeout_secs");
+                assert!(reason.contains("t.toml"));
+            }
+            other => panic!("expected InvalidValue, got {:?}", other.map(|l| l.config)),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+/// One file's section of a unified diff
+///
+/// Runs from a `diff --git` header up to (not including) the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileDiff<'a> {
+    /// Path from the `diff --git a/<path> b/<path>` header (new path for renames)
+    pub path: Option<&'a str>,
+    /// Section text, including the header and trailing newline
+    pub text: &'a str,
+}
+
+/// Split a unified diff into per-file sections
+///
+/// Text before the first `diff --git` header (or a diff without headers)
+/// becomes a section with no path. Concatenating the sections' `text`
+/// reproduces the input.
+pub fn split_files(diff: &str) -> Vec<FileDiff<'_>> {
+    let mut sections = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("diff --git ") && offset > start {
+            sections.push(file_diff(&diff[start..offset]));
+            start = offset;
+        }
+        offset += line.len();
+    }
+    if start < diff.len() {
+        sections.push(file_diff(&diff[start..]));
+    }
+    sections
+}
+
+fn file_diff(text: &str) -> FileDiff<'_> {
+    let path = text
+        .lines()
+        .next()
+        .and_then(|header| header.strip_prefix("diff --git "))
+        .and_then(|header| header.rsplit_once(" b/"))
+        .map(|(_, path)| path);
+    FileDiff { path, text }
+}
+
+/// Path from a `git diff --name-status` line (new path for renames and copies)
+pub fn name_status_path(line: &str) -> Option<&str> {
+    let (_, paths) = line.split_once('\t')?;
+    Some(paths.rsplit('\t').next().unwrap_or(paths))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_sections_at_headers() {
+        let diff = "diff --git a/a.rs b/a.rs\n+a\ndiff --git a/dir/b.rs b/dir/b.rs\n+b\n";
+        let files = split_files(diff);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, Some("a.rs"));
+        assert_eq!(files[0].text, "diff --git a/a.rs b/a.rs\n+a\n");
+        assert_eq!(files[1].path, Some("dir/b.rs"));
+        assert_eq!(files.iter().map(|f| f.text).collect::<String>(), diff);
+    }
+
+    #[test]
+    fn diff_without_headers_is_one_pathless_section() {
+        let files = split_files("@@ -1 +1 @@\n+x");
+        assert_eq!(
+            files,
+            [FileDiff {
+                path: None,
+                text: "@@ -1 +1 @@\n+x"
+            }]
+        );
+        assert!(split_files("").is_empty());
+    }
+
+    #[test]
+    fn rename_uses_new_path() {
+        let files = split_files("diff --git a/old.rs b/new.rs\nrename from old.rs\n");
+        assert_eq!(files[0].path, Some("new.rs"));
+    }
+
+    #[test]
+    fn parses_name_status_paths() {
+        assert_eq!(name_status_path("M\tsrc/lib.rs"), Some("src/lib.rs"));
+        assert_eq!(name_status_path("R100\told.rs\tnew.rs"), Some("new.rs"));
+        assert_eq!(name_status_path("garbage"), None);
+    }
+}
@@ -8,9 +8,14 @@ pub enum AgentError {
     #[error("agent `{agent}` not found in PATH")]
     NotFound { agent: AgentName },
 
-    /// Agent process execution failed
-    #[error("agent `{agent}` execution failed: {stderr}")]
-    ExecutionFailed { agent: AgentName, stderr: String },
+    /// Agent process execution failed (`exit_code` is `None` if it never ran
+    /// to completion, was killed by a signal, or isn't a process)
+    #[error("agent `{agent}` execution failed{}: {stderr}", exit_code_suffix(.exit_code))]
+    ExecutionFailed {
+        agent: AgentName,
+        exit_code: Option<i32>,
+        stderr: String,
+    },
 
     /// Agent process timed out
     #[error("agent `{agent}` timed out after {timeout_secs}s")]
@@ -21,6 +26,13 @@ pub enum AgentError {
     InvalidResponse { reason: String },
 }
 
+/// ` (exit code N)` for [`AgentError::ExecutionFailed`] messages
+fn exit_code_suffix(exit_code: &Option<i32>) -> String {
+    exit_code
+        .map(|code| format!(" (exit code {})", code))
+        .unwrap_or_default()
+}
+
 /// Errors from git operations
 #[derive(Error, Debug)]
 pub enum GitError {
@@ -36,6 +48,14 @@ pub enum GitError {
     #[error("failed to resolve git worktree directory at: {path}")]
     WorktreeResolution { path: String },
 
+    /// Malformed glob in `.commitmentignore`
+    #[error("invalid pattern `{pattern}` in {path}: {reason}")]
+    InvalidIgnorePattern {
+        path: String,
+        pattern: String,
+        reason: String,
+    },
+
     /// I/O error during git operation
     #[error("I/O error during git operation")]
     Io(#[from] std::io::Error),
@@ -77,6 +97,30 @@ pub enum HookError {
     Io(#[from] std::io::Error),
 }
 
+/// Errors from loading configuration files and environment overrides
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    /// Config file exists but could not be read
+    #[error("failed to read config file {path}")]
+    Read {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Config file is not valid TOML or has unknown keys
+    #[error("failed to parse config file {path}: {reason}")]
+    Parse { path: String, reason: String },
+
+    /// A config value could not be converted to its expected type
+    #[error("invalid value `{value}` for `{key}`: {reason}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: String,
+    },
+}
+
 /// Errors from commit message generation
 #[derive(Error, Debug)]
 pub enum GeneratorError {
@@ -91,6 +135,14 @@ pub enum GeneratorError {
     /// Commit validation failed
     #[error("commit validation failed: {0}")]
     Validation(String),
+
+    /// Strict redaction found secrets in the staged diff
+    #[error("refusing to send staged secrets to the agent: {summary}")]
+    SecretsDetected { summary: String },
+
+    /// Configuration error (e.g. an invalid `redact_patterns` regex)
+    #[error(transparent)]
+    Config(#[from] ConfigError),
 }
 
 #[cfg(test)]
@@ -112,11 +164,12 @@ mod tests {
     fn agent_error_execution_failed_display() {
         let err = AgentError::ExecutionFailed {
             agent: AgentName::Codex,
+            exit_code: Some(126),
             stderr: "permission denied".to_string(),
         };
         let msg = err.to_string();
         assert!(msg.contains("codex"));
-        assert!(msg.contains("execution failed"));
+        assert!(msg.contains("execution failed (exit code 126)"));
         assert!(msg.contains("permission denied"));
     }
 
@@ -224,6 +277,30 @@ mod tests {
         assert!(msg.contains("invalid yaml"));
     }
 
+    #[test]
+    fn config_error_parse_display() {
+        let err = ConfigError::Parse {
+            path: ".commitment.toml".to_string(),
+            reason: "unknown field `agnet`".to_string(),
+        };
+        let msg = err.to_string();
+        assert!(msg.contains(".commitment.toml"));
+        assert!(msg.contains("agnet"));
+    }
+
+    #[test]
+    fn config_error_invalid_value_display() {
+        let err = ConfigError::InvalidValue {
+            key: "COMMITMENT_TIMEOUT_SECS".to_string(),
+            value: "soon".to_string(),
+            reason: "expected a positive integer".to_string(),
+        };
+        let msg = err.to_string();
+        assert!(msg.contains("COMMITMENT_TIMEOUT_SECS"));
+        assert!(msg.contains("soon"));
+        assert!(msg.contains("positive integer"));
+    }
+
     #[test]
     fn error_types_implement_error_trait() {
         // Verify all error types implement std::error::Error
@@ -231,6 +308,7 @@ mod tests {
         assert_error::<AgentError>();
         assert_error::<GitError>();
         assert_error::<HookError>();
+        assert_error::<ConfigError>();
         assert_error::<GeneratorError>();
     }
 
@@ -0,0 +1,382 @@
+use crate::types::is_footer_line;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Column body text is wrapped at unless `wrap_column` is configured
+pub const WRAP_COLUMN: usize = 72;
+
+/// Longest header accepted from the agent unless `subject_max_length` is configured
+pub const SUBJECT_MAX_LENGTH: usize = 72;
+
+/// List item: indentation, marker (`-`, `*`, `+`, `•`, `1.`, `1)`) and text
+static LIST_ITEM: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\s*)([-*+•]|[0-9]+[.)])\s+(.*)$").expect("valid regex pattern"));
+
+/// How generated messages are tidied up (see [`format_message`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Column to wrap body text and list items at (0: don't wrap)
+    pub wrap_column: usize,
+    /// Lowercase the first word of the description (`Add` → `add`)
+    pub lowercase_subject: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            wrap_column: WRAP_COLUMN,
+            lowercase_subject: false,
+        }
+    }
+}
+
+/// Tidy up a cleaned agent response
+///
+/// - Strips trailing periods from the header, and lowercases the first word
+///   of its description if `lowercase_subject` is set (acronyms like `API`
+///   and code spans are left alone)
+/// - Normalizes `*`, `+` and `•` bullets to `-`
+/// - Reflows paragraphs and list items at `wrap_column`, with hanging indents
+///   for list items
+///
+/// Code spans and URLs are never split. Fenced code blocks (```` ``` ```` or
+/// `~~~`), indented lines outside a list item and the final footer paragraph
+/// are kept as they are.
+///
+/// # Examples
+/// ```
+/// # use commitment_rs::format::{FormatOptions, format_message};
+/// let options = FormatOptions {
+///     wrap_column: 30,
+///     lowercase_subject: true,
+/// };
+/// assert_eq!(
+///     format_message("feat: Add login.\n\n* Validate the password on every request", &options),
+///     "feat: add login\n\n- Validate the password on\n  every request"
+/// );
+/// ```
+pub fn format_message(message: &str, options: &FormatOptions) -> String {
+    let (header, rest) = match message.split_once('\n') {
+        Some((header, rest)) => (header, Some(rest)),
+        None => (message, None),
+    };
+    let header = format_header(header, options);
+    let Some(rest) = rest else {
+        return header;
+    };
+    // No blank line after the header: left for validation to reject
+    let Some(body) = rest.strip_prefix('\n') else {
+        return format!("{}\n{}", header, rest);
+    };
+
+    let paragraphs = paragraphs(body);
+    let last = paragraphs.len() - 1;
+    let mut formatted = vec![header];
+    for (i, paragraph) in paragraphs.iter().enumerate() {
+        let is_footer_block =
+            i == last && is_footer_line(paragraph.lines().next().unwrap_or_default());
+        formatted.push(if is_footer_block {
+            paragraph.to_string()
+        } else {
+            format_paragraph(paragraph, options.wrap_column)
+        });
+    }
+    formatted.join("\n\n")
+}
+
+/// Split a body at blank lines, except inside fenced code blocks
+fn paragraphs(body: &str) -> Vec<String> {
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut open_fence = None;
+    for piece in body.split("\n\n") {
+        match paragraphs.last_mut() {
+            Some(paragraph) if open_fence.is_some() => {
+                paragraph.push_str("\n\n");
+                paragraph.push_str(piece);
+            }
+            _ => paragraphs.push(piece.to_string()),
+        }
+        for line in piece.lines() {
+            open_fence = track_fence(open_fence, line);
+        }
+    }
+    paragraphs
+}
+
+/// Fence marker of a line opening or closing a code block (`` ``` `` or `~~~`)
+fn fence(line: &str) -> Option<&'static str> {
+    let line = line.trim_start();
+    ["```", "~~~"]
+        .into_iter()
+        .find(|marker| line.starts_with(marker))
+}
+
+/// The open fence after `line`, given the one open before it
+fn track_fence(open: Option<&'static str>, line: &str) -> Option<&'static str> {
+    match (open, fence(line)) {
+        // A fence line closes the block it matches
+        (Some(open), Some(marker)) if open == marker => None,
+        (Some(open), _) => Some(open),
+        (None, marker) => marker,
+    }
+}
+
+fn format_header(header: &str, options: &FormatOptions) -> String {
+    let header = header.trim_end().trim_end_matches('.').trim_end();
+    let Some((prefix, description)) = header.split_once(": ") else {
+        return header.to_string();
+    };
+    if !options.lowercase_subject {
+        return header.to_string();
+    }
+    format!("{}: {}", prefix, lowercase_first_word(description))
+}
+
+/// `Add` → `add`; words with other capitals (`API`, `GitHub`) stay as they are
+fn lowercase_first_word(text: &str) -> String {
+    let word = text.split_whitespace().next().unwrap_or_default();
+    let mut chars = word.chars();
+    let Some(first) = chars.next() else {
+        return text.to_string();
+    };
+    if !first.is_uppercase() || chars.any(|c| !c.is_lowercase()) {
+        return text.to_string();
+    }
+    let mut lowered: String = first.to_lowercase().collect();
+    lowered.push_str(&text[first.len_utf8()..]);
+    lowered
+}
+
+/// A run of lines within a paragraph
+enum Block<'a> {
+    /// Plain text, reflowed as one unit
+    Text(Vec<&'a str>),
+    /// List item with its continuation lines
+    Item {
+        indent: &'a str,
+        marker: &'a str,
+        lines: Vec<&'a str>,
+    },
+    /// Indented line that isn't part of a list item, or a fenced code line
+    Verbatim(&'a str),
+}
+
+fn format_paragraph(paragraph: &str, width: usize) -> String {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut open_fence = None;
+    for line in paragraph.lines() {
+        let was_open = open_fence.is_some();
+        open_fence = track_fence(open_fence, line);
+        if was_open || open_fence.is_some() {
+            blocks.push(Block::Verbatim(line.trim_end()));
+            continue;
+        }
+        if let Some(captures) = LIST_ITEM.captures(line) {
+            let marker = captures.get(2).map_or("-", |m| m.as_str());
+            blocks.push(Block::Item {
+                indent: captures.get(1).map_or("", |m| m.as_str()),
+                marker: if "*+•".contains(marker) {
+                    "-"
+                } else {
+                    marker
+                },
+                lines: vec![captures.get(3).map_or("", |m| m.as_str())],
+            });
+            continue;
+        }
+        let indented = line.starts_with(char::is_whitespace);
+        match (blocks.last_mut(), indented) {
+            (Some(Block::Item { lines, .. }), true) => lines.push(line.trim()),
+            (_, true) => blocks.push(Block::Verbatim(line.trim_end())),
+            (Some(Block::Text(lines)), false) => lines.push(line),
+            (_, false) => blocks.push(Block::Text(vec![line])),
+        }
+    }
+
+    blocks
+        .iter()
+        .map(|block| match block {
+            Block::Text(lines) => wrap(&lines.join(" "), "", "", width),
+            Block::Item {
+                indent,
+                marker,
+                lines,
+            } => {
+                let first = format!("{}{} ", indent, marker);
+                let hanging = " ".repeat(first.chars().count());
+                wrap(&lines.join(" "), &first, &hanging, width)
+            }
+            Block::Verbatim(line) => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Greedy word wrap with a prefix for the first line and the rest
+///
+/// Words longer than the line (long URLs) get a line to themselves.
+fn wrap(text: &str, first: &str, hanging: &str, width: usize) -> String {
+    let mut wrapped = first.to_string();
+    let mut line_length = first.chars().count();
+    // Every line gets at least one word, however long
+    for (i, word) in words(text).into_iter().enumerate() {
+        let word_length = word.chars().count();
+        if i > 0 {
+            if width > 0 && line_length + 1 + word_length > width {
+                wrapped.push('\n');
+                wrapped.push_str(hanging);
+                line_length = hanging.chars().count();
+            } else {
+                wrapped.push(' ');
+                line_length += 1;
+            }
+        }
+        wrapped.push_str(word);
+        line_length += word_length;
+    }
+    wrapped.trim_end().to_string()
+}
+
+/// Split text at whitespace, keeping each `code span` in one piece
+///
+/// A backtick without a closing one is just a character.
+fn words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut in_code = false;
+    for (i, c) in text.char_indices() {
+        if c == '`' {
+            in_code = !in_code && text[i + 1..].contains('`');
+        }
+        if c.is_whitespace() && !in_code {
+            if let Some(s) = start.take() {
+                words.push(&text[s..i]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        words.push(&text[s..]);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(wrap_column: usize) -> FormatOptions {
+        FormatOptions {
+            wrap_column,
+            lowercase_subject: false,
+        }
+    }
+
+    #[test]
+    fn strips_trailing_periods_from_the_header() {
+        assert_eq!(
+            format_message("fix: handle eof.", &options(72)),
+            "fix: handle eof"
+        );
+        assert_eq!(
+            format_message("fix: Handle eof...  ", &options(72)),
+            "fix: Handle eof"
+        );
+    }
+
+    #[test]
+    fn lowercases_the_first_word_when_configured() {
+        let lower = FormatOptions {
+            lowercase_subject: true,
+            ..options(72)
+        };
+
+        assert_eq!(
+            format_message("feat(api): Add pagination", &lower),
+            "feat(api): add pagination"
+        );
+        assert_eq!(
+            format_message("feat: API pagination", &lower),
+            "feat: API pagination"
+        );
+        assert_eq!(
+            format_message("feat: `Config` loader", &lower),
+            "feat: `Config` loader"
+        );
+        assert_eq!(
+            format_message("feat: Add pagination", &options(72)),
+            "feat: Add pagination"
+        );
+    }
+
+    #[test]
+    fn normalizes_bullets_and_wraps_with_hanging_indent() {
+        let message =
+            "feat: x\n\n* First point that is rather long\n+ Second\n  continued here\n• Third";
+
+        assert_eq!(
+            format_message(message, &options(24)),
+            "feat: x\n\n- First point that is\n  rather long\n- Second continued here\n- Third"
+        );
+    }
+
+    #[test]
+    fn reflows_paragraphs_and_keeps_numbered_markers() {
+        let message = "fix: x\n\nThe parser\nskipped the last line.\n\n10. Read it\n    again";
+
+        assert_eq!(
+            format_message(message, &options(16)),
+            "fix: x\n\nThe parser\nskipped the last\nline.\n\n10. Read it\n    again"
+        );
+    }
+
+    #[test]
+    fn keeps_code_spans_and_urls_whole() {
+        let message =
+            "docs: x\n\n- Use `cargo test --workspace` see https://example.com/a/very/long/path";
+
+        assert_eq!(
+            format_message(message, &options(20)),
+            "docs: x\n\n- Use\n  `cargo test --workspace`\n  see\n  https://example.com/a/very/long/path"
+        );
+    }
+
+    #[test]
+    fn leaves_footers_and_indented_code_alone() {
+        let message = "feat: x\n\nRun:\n    cargo run --release -- --very-long-flag\n\nBREAKING CHANGE: the config file moved to a new location entirely";
+
+        assert_eq!(format_message(message, &options(20)), message);
+    }
+
+    #[test]
+    fn keeps_fenced_code_blocks_verbatim() {
+        let message = "docs: x\n\nExample:\n```toml\n* not = \"a bullet\"\n\n[agents.claude]\nargs = [\"--a-rather-long-argument\"]\n```\n\n~~~\n+ kept\n~~~";
+
+        assert_eq!(
+            format_message(message, &options(20)),
+            "docs: x\n\nExample:\n```toml\n* not = \"a bullet\"\n\n[agents.claude]\nargs = [\"--a-rather-long-argument\"]\n```\n\n~~~\n+ kept\n~~~"
+        );
+    }
+
+    #[test]
+    fn unbalanced_backticks_dont_stop_wrapping() {
+        let message = "fix: x\n\nQuote the ` character in every generated shell command";
+
+        assert_eq!(
+            format_message(message, &options(20)),
+            "fix: x\n\nQuote the `\ncharacter in every\ngenerated shell\ncommand"
+        );
+    }
+
+    #[test]
+    fn zero_column_disables_wrapping() {
+        let message =
+            "feat: x\n\n* A very long bullet that goes on and on past any sensible column";
+
+        assert_eq!(
+            format_message(message, &options(0)),
+            "feat: x\n\n- A very long bullet that goes on and on past any sensible column"
+        );
+    }
+}
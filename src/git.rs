@@ -1,5 +1,6 @@
 use crate::error::GitError;
-use crate::types::StagedDiff;
+use crate::ignore::IgnoreRules;
+use crate::types::{HistoryCommit, StagedDiff};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -47,6 +48,32 @@ pub trait GitProvider {
     fn get_staged_diff(&self) -> Result<StagedDiff, GitError>;
     fn has_staged_changes(&self) -> Result<bool, GitError>;
     fn commit(&self, message: &str) -> Result<(), GitError>;
+
+    /// Up to `limit` recent non-merge commits on the current branch, newest
+    /// first, limited to those touching `paths` (repo-relative) if any are given
+    ///
+    /// Defaults to no history, for providers that don't have any.
+    fn recent_commits(
+        &self,
+        _limit: usize,
+        _paths: &[&str],
+    ) -> Result<Vec<HistoryCommit>, GitError> {
+        Ok(Vec::new())
+    }
+
+    /// Short name of the checked-out branch (`None` on a detached HEAD)
+    ///
+    /// Defaults to none, for providers without branches.
+    fn current_branch(&self) -> Result<Option<String>, GitError> {
+        Ok(None)
+    }
+
+    /// Root of the working tree, where workspace manifests are read from
+    ///
+    /// Defaults to none, for providers without a working tree.
+    fn workspace_root(&self) -> Option<PathBuf> {
+        None
+    }
 }
 
 /// Production git provider using real git commands
@@ -78,6 +105,39 @@ impl RealGitProvider {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
+    /// Top-level directory of the repository containing `cwd`
+    fn repo_root(&self) -> Result<PathBuf, GitError> {
+        let output = self.run_git(&["rev-parse", "--show-toplevel"])?;
+        Ok(PathBuf::from(output.trim_end()))
+    }
+
+    /// Full messages of the commits in a revision range (e.g. `main..HEAD`)
+    ///
+    /// Returns `(sha, message)` pairs, newest first.
+    pub fn commit_messages(&self, range: &str) -> Result<Vec<(String, String)>, GitError> {
+        // NUL between sha and body, record separator between commits
+        let output = self.run_git(&["log", "--format=%H%x00%B%x1e", range])?;
+
+        Ok(output
+            .split('\x1e')
+            .filter_map(|record| {
+                let (sha, message) = record.trim_start_matches('\n').split_once('\0')?;
+                Some((sha.to_string(), message.trim_end().to_string()))
+            })
+            .collect())
+    }
+
+    /// Prefix of comment lines in commit messages (`core.commentChar`)
+    ///
+    /// `#` when unset. With `auto`, git picks a character per message that
+    /// isn't otherwise used; that choice isn't recorded, so `#` is assumed.
+    pub fn comment_char(&self) -> String {
+        match self.run_git(&["config", "--get", "core.commentChar"]) {
+            Ok(value) if !matches!(value.trim_end(), "" | "auto") => value.trim_end().to_string(),
+            _ => crate::lint::DEFAULT_COMMENT_CHAR.to_string(),
+        }
+    }
+
     /// Resolve git directory path, handling worktrees
     ///
     /// In a worktree, .git is a file containing: gitdir: <path>
@@ -110,11 +170,15 @@ impl GitProvider for RealGitProvider {
         // correctly and produce the expected compact, clean diffs.
         let diff = self.run_git(&["diff", "--cached", "--unified=3", "--ignore-space-change"])?;
 
-        Ok(StagedDiff {
+        // Leave lockfiles, snapshots and `.commitmentignore` matches out of the
+        // content (paths are relative to the repo root, not `cwd`)
+        let rules = IgnoreRules::load(&self.repo_root()?)?;
+        Ok(rules.apply(StagedDiff {
             stat,
             name_status,
             diff,
-        })
+            omitted: Vec::new(),
+        }))
     }
 
     fn has_staged_changes(&self) -> Result<bool, GitError> {
@@ -144,6 +208,61 @@ impl GitProvider for RealGitProvider {
 
         Ok(())
     }
+
+    fn recent_commits(&self, limit: usize, paths: &[&str]) -> Result<Vec<HistoryCommit>, GitError> {
+        // A repository without commits has no history to learn from
+        if self
+            .run_git(&["rev-parse", "--verify", "--quiet", "HEAD"])
+            .is_err()
+        {
+            return Ok(Vec::new());
+        }
+
+        // Unit separator between fields, record separator between commits;
+        // `:(top)` makes paths repo-relative even when `cwd` is a subdirectory
+        let max_count = format!("--max-count={}", limit);
+        let pathspecs: Vec<String> = paths.iter().map(|path| format!(":(top){}", path)).collect();
+        let mut args = vec![
+            "log",
+            "--no-merges",
+            max_count.as_str(),
+            "--format=%an%x1f%ae%x1f%B%x1e",
+        ];
+        if !pathspecs.is_empty() {
+            args.push("--");
+            args.extend(pathspecs.iter().map(String::as_str));
+        }
+        let output = self.run_git(&args)?;
+
+        Ok(output
+            .split('\x1e')
+            .filter_map(|record| {
+                let mut fields = record.trim_start_matches('\n').splitn(3, '\x1f');
+                Some(HistoryCommit {
+                    author_name: fields.next()?.to_string(),
+                    author_email: fields.next()?.to_string(),
+                    message: fields.next()?.trim_end().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    fn current_branch(&self) -> Result<Option<String>, GitError> {
+        // Exits non-zero (quietly) when HEAD isn't a branch
+        let output = Command::new("git")
+            .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+            .current_dir(&self.cwd)
+            .output()?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((!branch.is_empty()).then_some(branch))
+    }
+
+    fn workspace_root(&self) -> Option<PathBuf> {
+        self.repo_root().ok()
+    }
 }
 
 #[cfg(test)]
@@ -214,6 +333,7 @@ mod tests {
             stat: "1 file changed".to_string(),
             name_status: "M\ttest.rs".to_string(),
             diff: "@@ test diff".to_string(),
+            omitted: Vec::new(),
         });
 
         let result = mock.get_staged_diff();
@@ -255,6 +375,123 @@ mod tests {
         assert_eq!(provider.cwd, PathBuf::from("/tmp"));
     }
 
+    /// A fresh repository, and a function running git in it with a test identity
+    fn init_repo() -> (tempfile::TempDir, impl Fn(&[&str])) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().to_path_buf();
+        let git = move |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(&path)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        git(&["init", "--quiet"]);
+        (dir, git)
+    }
+
+    #[test]
+    fn commit_messages_reads_range_from_real_repo() {
+        let (dir, git) = init_repo();
+        git(&["commit", "--allow-empty", "-m", "chore: initial"]);
+        git(&["tag", "base"]);
+        git(&["commit", "--allow-empty", "-m", "feat: one\n\nBody line"]);
+        git(&["commit", "--allow-empty", "-m", "fix: two"]);
+
+        let provider = RealGitProvider::new(dir.path().to_path_buf());
+        let messages = provider.commit_messages("base..HEAD").unwrap();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].1, "fix: two");
+        assert_eq!(messages[1].1, "feat: one\n\nBody line");
+        assert_eq!(messages[0].0.len(), 40);
+    }
+
+    #[test]
+    fn recent_commits_reads_branch_and_path_history() {
+        let (dir, git) = init_repo();
+        let provider = RealGitProvider::new(dir.path().to_path_buf());
+        assert!(provider.recent_commits(5, &[]).unwrap().is_empty());
+
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "// lib\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "feat(core): add lib\n\nRefs: ABC-1"]);
+        git(&["commit", "--allow-empty", "-m", "chore: bump"]);
+
+        let branch = provider.recent_commits(5, &[]).unwrap();
+        assert_eq!(branch.len(), 2);
+        assert_eq!(branch[0].message, "chore: bump");
+        assert_eq!(branch[0].author_email, "test@example.com");
+
+        // Paths are repo-relative even from a subdirectory
+        let provider = RealGitProvider::new(dir.path().join("src"));
+        let touching = provider.recent_commits(5, &["src/lib.rs"]).unwrap();
+        assert_eq!(touching.len(), 1);
+        assert_eq!(touching[0].message, "feat(core): add lib\n\nRefs: ABC-1");
+        assert_eq!(touching[0].author_name, "Test");
+    }
+
+    #[test]
+    fn comment_char_reads_core_comment_char() {
+        let (dir, git) = init_repo();
+        let provider = RealGitProvider::new(dir.path().to_path_buf());
+
+        git(&["config", "core.commentChar", "auto"]);
+        assert_eq!(provider.comment_char(), "#");
+        git(&["config", "core.commentChar", ";"]);
+        assert_eq!(provider.comment_char(), ";");
+    }
+
+    #[test]
+    fn current_branch_reads_head() {
+        let (dir, git) = init_repo();
+        git(&["symbolic-ref", "HEAD", "refs/heads/feature/PROJ-7-login"]);
+        let provider = RealGitProvider::new(dir.path().to_path_buf());
+
+        // Unborn branches still have a name
+        assert_eq!(
+            provider.current_branch().unwrap().as_deref(),
+            Some("feature/PROJ-7-login")
+        );
+
+        git(&["commit", "--allow-empty", "-m", "chore: initial"]);
+        git(&["checkout", "--quiet", "--detach"]);
+        assert_eq!(provider.current_branch().unwrap(), None);
+    }
+
+    #[test]
+    fn staged_diff_omits_ignored_file_content() {
+        let (dir, git) = init_repo();
+        std::fs::write(dir.path().join(".commitmentignore"), "generated/\n").unwrap();
+        std::fs::create_dir(dir.path().join("generated")).unwrap();
+        std::fs::write(dir.path().join("generated/api.rs"), "GENERATED\n").unwrap();
+        std::fs::write(dir.path().join("Cargo.lock"), "LOCKED\n").unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        git(&["add", "generated/api.rs", "Cargo.lock", "main.rs"]);
+
+        // Run from a subdirectory: rules still resolve against the repo root
+        let provider = RealGitProvider::new(dir.path().join("generated"));
+        let diff = provider.get_staged_diff().unwrap();
+
+        assert_eq!(diff.omitted, ["Cargo.lock", "generated/api.rs"]);
+        assert!(diff.name_status.contains("A\tCargo.lock"));
+        assert!(diff.diff.contains("fn main() {}"));
+        assert!(!diff.diff.contains("LOCKED"));
+        assert!(!diff.diff.contains("GENERATED"));
+    }
+
+    #[test]
+    fn commit_messages_reports_bad_range() {
+        let (dir, _) = init_repo();
+        let provider = RealGitProvider::new(dir.path().to_path_buf());
+        let result = provider.commit_messages("nope..HEAD");
+        assert!(matches!(result, Err(GitError::CommandFailed { .. })));
+    }
+
     #[test]
     fn resolve_git_dir_handles_worktree() {
         use std::io::Write;
@@ -311,6 +548,7 @@ mod tests {
             stat: " src/git.rs | 100 +++++++++++++++++++++++++++++++++++++++++++++++++\n 1 file changed, 100 insertions(+)".to_string(),
             name_status: "M\tsrc/git.rs".to_string(),
             diff: "@@ -1,5 +1,105 @@\n use crate::error::GitError;\n+use std::process::Command;".to_string(),
+            omitted: Vec::new(),
         };
 
         assert!(diff.stat.contains("src/git.rs"));
@@ -0,0 +1,271 @@
+use crate::config::Config;
+use crate::diff::name_status_path;
+use crate::error::GitError;
+use crate::git::GitProvider;
+use crate::types::{CommitRules, ConventionalCommit, HistoryCommit, StagedDiff};
+
+/// Default number of past commits shown to the agent as examples
+pub(crate) const HISTORY_EXAMPLES: usize = 3;
+
+/// Commits fetched per wanted example (most are filtered out)
+const FETCH_FACTOR: usize = 10;
+
+/// Staged paths passed to `git log` when looking for related commits
+const MAX_PATHS: usize = 50;
+
+/// Examples longer than this (in bytes) are skipped to keep the prompt small
+const MAX_EXAMPLE_LENGTH: usize = 1000;
+
+/// Pick well-formed recent commits to show the agent as style examples
+///
+/// Commits that touched the staged paths come first, then the rest of the
+/// branch history. Only messages that pass `rules` (the configured types
+/// and scopes, see [`Config::commit_rules`]) are used; merges, over-long messages, repeated headers and commits by
+/// `config.history_exclude_authors` (matched case-insensitively against
+/// name or email) are skipped. Returns at most `config.history_examples`.
+///
+/// # Errors
+///
+/// Returns `GitError` if reading the history fails.
+pub fn collect_examples(
+    git: &impl GitProvider,
+    diff: &StagedDiff,
+    config: &Config,
+    rules: &CommitRules,
+) -> Result<Vec<ConventionalCommit>, GitError> {
+    let count = config.history_examples;
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let paths: Vec<&str> = diff
+        .name_status
+        .lines()
+        .filter_map(name_status_path)
+        .take(MAX_PATHS)
+        .collect();
+    let fetch = count * FETCH_FACTOR;
+
+    let mut commits = Vec::new();
+    if !paths.is_empty() {
+        commits.extend(git.recent_commits(fetch, &paths)?);
+    }
+    commits.extend(git.recent_commits(fetch, &[])?);
+
+    let examples = commits
+        .into_iter()
+        .filter(|commit| !is_excluded_author(commit, &config.history_exclude_authors))
+        .filter(|commit| commit.message.len() <= MAX_EXAMPLE_LENGTH)
+        .filter_map(|commit| ConventionalCommit::validate_with(&commit.message, rules).ok())
+        .collect();
+
+    let mut examples = crate::dedup_candidates(examples, |commit| commit);
+    examples.truncate(count);
+    Ok(examples)
+}
+
+fn is_excluded_author(commit: &HistoryCommit, excluded: &[String]) -> bool {
+    excluded.iter().any(|author| {
+        author.eq_ignore_ascii_case(&commit.author_name)
+            || author.eq_ignore_ascii_case(&commit.author_email)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::StagedDiff;
+    use std::sync::Mutex;
+
+    /// Git provider with canned history that records each query's paths
+    struct HistoryGit {
+        touching: Vec<HistoryCommit>,
+        branch: Vec<HistoryCommit>,
+        queries: Mutex<Vec<Vec<String>>>,
+    }
+
+    impl GitProvider for HistoryGit {
+        fn get_staged_diff(&self) -> Result<StagedDiff, GitError> {
+            unreachable!()
+        }
+
+        fn has_staged_changes(&self) -> Result<bool, GitError> {
+            unreachable!()
+        }
+
+        fn commit(&self, _message: &str) -> Result<(), GitError> {
+            unreachable!()
+        }
+
+        fn recent_commits(
+            &self,
+            limit: usize,
+            paths: &[&str],
+        ) -> Result<Vec<HistoryCommit>, GitError> {
+            self.queries
+                .lock()
+                .unwrap()
+                .push(paths.iter().map(|p| p.to_string()).collect());
+            let commits = if paths.is_empty() {
+                &self.branch
+            } else {
+                &self.touching
+            };
+            Ok(commits.iter().take(limit).cloned().collect())
+        }
+    }
+
+    fn commit(author: &str, message: &str) -> HistoryCommit {
+        HistoryCommit {
+            author_name: author.to_string(),
+            author_email: format!("{}@example.com", author.to_lowercase()),
+            message: message.to_string(),
+        }
+    }
+
+    fn history(touching: Vec<HistoryCommit>, branch: Vec<HistoryCommit>) -> HistoryGit {
+        HistoryGit {
+            touching,
+            branch,
+            queries: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn staged(name_status: &str) -> StagedDiff {
+        StagedDiff {
+            name_status: name_status.to_string(),
+            ..StagedDiff::default()
+        }
+    }
+
+    /// Examples valid under `config`'s types (no scopes)
+    fn examples(
+        git: &HistoryGit,
+        diff: &StagedDiff,
+        config: &Config,
+    ) -> Result<Vec<ConventionalCommit>, GitError> {
+        let rules = config.commit_rules(&crate::scope::ScopeMap::default());
+        collect_examples(git, diff, config, &rules)
+    }
+
+    fn headers(examples: &[ConventionalCommit]) -> Vec<&str> {
+        examples.iter().map(|c| c.header()).collect()
+    }
+
+    #[test]
+    fn prefers_commits_touching_staged_paths() {
+        let git = history(
+            vec![commit("Ann", "fix(parser): handle tabs")],
+            vec![
+                commit("Ann", "docs: update readme"),
+                commit("Ann", "fix(parser): handle tabs"),
+                commit("Bob", "feat(cli): add flag"),
+            ],
+        );
+
+        let examples = examples(&git, &staged("M\tsrc/parser.rs"), &Config::default());
+
+        assert_eq!(
+            headers(&examples.unwrap()),
+            [
+                "fix(parser): handle tabs",
+                "docs: update readme",
+                "feat(cli): add flag"
+            ]
+        );
+        assert_eq!(
+            *git.queries.lock().unwrap(),
+            [vec!["src/parser.rs".to_string()], vec![]]
+        );
+    }
+
+    #[test]
+    fn skips_invalid_long_and_excluded_commits() {
+        let git = history(
+            vec![],
+            vec![
+                commit("Ann", "WIP"),
+                commit("dependabot[bot]", "chore(deps): bump serde"),
+                commit("Ann", &format!("feat: big\n\n{}", "x".repeat(2000))),
+                commit("Bob", "fix: keep me"),
+            ],
+        );
+        let config = Config {
+            history_exclude_authors: vec!["Dependabot[bot]".to_string()],
+            ..Config::default()
+        };
+
+        let examples = examples(&git, &staged(""), &config).unwrap();
+
+        assert_eq!(headers(&examples), ["fix: keep me"]);
+        // Nothing staged: only the branch history is read
+        assert_eq!(git.queries.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn uses_the_configured_commit_types() {
+        let git = history(
+            vec![],
+            vec![
+                commit("Ann", "deps: bump serde"),
+                commit("Ann", "chore: tidy"),
+                commit("Ann", "fix: keep me"),
+            ],
+        );
+        let config = Config {
+            commit_types: crate::types::default_commit_types()
+                .into_iter()
+                .filter(|t| t.name != "chore")
+                .chain([crate::types::CommitType::new("deps", "Dependency bumps")])
+                .collect(),
+            ..Config::default()
+        };
+
+        let examples = examples(&git, &staged(""), &config).unwrap();
+
+        assert_eq!(headers(&examples), ["deps: bump serde", "fix: keep me"]);
+    }
+
+    #[test]
+    fn excludes_authors_by_email() {
+        let git = history(vec![], vec![commit("Bot", "chore: release")]);
+        let config = Config {
+            history_exclude_authors: vec!["bot@example.com".to_string()],
+            ..Config::default()
+        };
+
+        assert!(examples(&git, &staged(""), &config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn limits_to_configured_count() {
+        let branch = (0..10)
+            .map(|i| commit("Ann", &format!("fix: bug {}", i)))
+            .collect();
+        let git = history(vec![], branch);
+        let config = Config {
+            history_examples: 2,
+            ..Config::default()
+        };
+
+        let examples = examples(&git, &staged(""), &config).unwrap();
+
+        assert_eq!(headers(&examples), ["fix: bug 0", "fix: bug 1"]);
+    }
+
+    #[test]
+    fn zero_examples_skips_git() {
+        let git = history(vec![], vec![commit("Ann", "fix: bug")]);
+        let config = Config {
+            history_examples: 0,
+            ..Config::default()
+        };
+
+        assert!(
+            examples(&git, &staged("M\ta.rs"), &config)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(git.queries.lock().unwrap().is_empty());
+    }
+}
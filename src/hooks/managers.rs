@@ -17,10 +17,37 @@ fn hook_command(agent: &AgentName) -> String {
     format!("{} --agent {} --message-only", BINARY_NAME, agent)
 }
 
+/// Git hook that commitment can install
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitHook {
+    /// `prepare-commit-msg`: generate the message with the given agent
+    PrepareCommitMsg(AgentName),
+    /// `commit-msg`: reject messages that fail `commitment lint`
+    CommitMsg,
+}
+
+impl GitHook {
+    /// Git hook name (also the hook script file name)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PrepareCommitMsg(_) => "prepare-commit-msg",
+            Self::CommitMsg => "commit-msg",
+        }
+    }
+
+    /// Command for shell-script hooks, where `$1` is the message file
+    fn script_command(&self) -> String {
+        match self {
+            Self::PrepareCommitMsg(agent) => hook_command(agent),
+            Self::CommitMsg => format!("{} lint \"$1\"", BINARY_NAME),
+        }
+    }
+}
+
 /// Install Lefthook hook
 ///
-/// Updates or creates lefthook.yml with prepare-commit-msg hook
-pub fn install_lefthook(cwd: &Path, agent: &AgentName) -> Result<(), HookError> {
+/// Updates or creates lefthook.yml with the given hook
+pub fn install_lefthook(cwd: &Path, hook: &GitHook) -> Result<(), HookError> {
     // Find existing lefthook config file
     let config_files = [
         "lefthook.yml",
@@ -39,11 +66,13 @@ pub fn install_lefthook(cwd: &Path, agent: &AgentName) -> Result<(), HookError>
     let mut config: LefthookConfig = if config_path.exists() {
         let content = fs::read_to_string(&config_path).map_err(HookError::Io)?;
 
-        // Check if prepare-commit-msg hook already exists
-        if content.contains("prepare-commit-msg:") {
+        // Check if the hook already exists (top-level key)
+        let key = format!("{}:", hook.name());
+        if content.lines().any(|line| line.starts_with(&key)) {
             eprintln!(
-                "{}: lefthook.yml already has prepare-commit-msg hook, skipping installation",
-                console::style("Warning").yellow()
+                "{}: lefthook.yml already has {} hook, skipping installation",
+                console::style("Warning").yellow(),
+                hook.name()
             );
             eprintln!(
                 "  → To enable commitment, manually add the commitment command to your existing hook"
@@ -58,39 +87,33 @@ pub fn install_lefthook(cwd: &Path, agent: &AgentName) -> Result<(), HookError>
         LefthookConfig::default()
     };
 
-    // Add prepare-commit-msg hook
-    // Uses lefthook placeholders:
-    //   {1} = commit message file path
-    //   {2} = commit source ("message", "template", "merge", "squash", "commit", or unsubstituted)
-    // Only run for regular commits (when {2} is unsubstituted, contains literal braces)
-    let run_script = format!(
-        r#"case "{{2}}" in
+    let hook_entry = match hook {
+        // Uses lefthook placeholders:
+        //   {1} = commit message file path
+        //   {2} = commit source ("message", "template", "merge", "squash", "commit", or unsubstituted)
+        // Only run for regular commits (when {2} is unsubstituted, contains literal braces)
+        GitHook::PrepareCommitMsg(agent) => LefthookHook {
+            skip: Some(vec!["merge".to_string(), "rebase".to_string()]),
+            commands: lefthook_command(
+                format!(
+                    r#"case "{{2}}" in
   *"{{"*)
     echo "🤖 Generating commit message..." > /dev/tty 2>/dev/null || true
     {} > "{{1}}"
     ;;
 esac"#,
-        hook_command(agent)
-    );
-
-    let hook_entry = LefthookHook {
-        skip: Some(vec!["merge".to_string(), "rebase".to_string()]),
-        commands: {
-            let mut commands = HashMap::new();
-            commands.insert(
-                BINARY_NAME.to_string(),
-                LefthookCommand {
-                    run: run_script,
-                    interactive: Some(true),
-                },
-            );
-            commands
+                    hook_command(agent)
+                ),
+                Some(true),
+            ),
+        },
+        GitHook::CommitMsg => LefthookHook {
+            skip: None,
+            commands: lefthook_command(format!("{} lint \"{{1}}\"", BINARY_NAME), None),
         },
     };
 
-    config
-        .hooks
-        .insert("prepare-commit-msg".to_string(), hook_entry);
+    config.hooks.insert(hook.name().to_string(), hook_entry);
 
     // Write updated config
     let yaml = serde_yaml::to_string(&config).map_err(|e| HookError::ConfigWriteFailed {
@@ -104,10 +127,20 @@ esac"#,
     Ok(())
 }
 
+/// Single-command map for a lefthook hook entry
+fn lefthook_command(run: String, interactive: Option<bool>) -> HashMap<String, LefthookCommand> {
+    let mut commands = HashMap::new();
+    commands.insert(
+        BINARY_NAME.to_string(),
+        LefthookCommand { run, interactive },
+    );
+    commands
+}
+
 /// Install Husky hook
 ///
-/// Creates .husky/prepare-commit-msg script
-pub fn install_husky(cwd: &Path, agent: &AgentName) -> Result<(), HookError> {
+/// Creates .husky/<hook> script
+pub fn install_husky(cwd: &Path, hook: &GitHook) -> Result<(), HookError> {
     let husky_dir = cwd.join(".husky");
 
     // Create .husky directory if it doesn't exist
@@ -115,7 +148,7 @@ pub fn install_husky(cwd: &Path, agent: &AgentName) -> Result<(), HookError> {
         fs::create_dir_all(&husky_dir).map_err(HookError::Io)?;
     }
 
-    let hook_path = husky_dir.join("prepare-commit-msg");
+    let hook_path = husky_dir.join(hook.name());
 
     // Create hook script
     let script = format!(
@@ -124,7 +157,7 @@ pub fn install_husky(cwd: &Path, agent: &AgentName) -> Result<(), HookError> {
 
 {}
 "#,
-        hook_command(agent)
+        hook.script_command()
     );
 
     fs::write(&hook_path, script).map_err(|_| HookError::ScriptCreationFailed {
@@ -146,7 +179,7 @@ pub fn install_husky(cwd: &Path, agent: &AgentName) -> Result<(), HookError> {
 /// Install simple-git-hooks hook
 ///
 /// Updates package.json with simple-git-hooks configuration
-pub fn install_simple_git_hooks(cwd: &Path, agent: &AgentName) -> Result<(), HookError> {
+pub fn install_simple_git_hooks(cwd: &Path, hook: &GitHook) -> Result<(), HookError> {
     let package_json = cwd.join("package.json");
 
     if !package_json.exists() {
@@ -163,7 +196,7 @@ pub fn install_simple_git_hooks(cwd: &Path, agent: &AgentName) -> Result<(), Hoo
         })?;
 
     // Add or update simple-git-hooks section
-    let cmd = hook_command(agent);
+    let cmd = hook.script_command();
 
     if let Some(obj) = json.as_object_mut() {
         let hooks = obj
@@ -171,10 +204,7 @@ pub fn install_simple_git_hooks(cwd: &Path, agent: &AgentName) -> Result<(), Hoo
             .or_insert_with(|| serde_json::json!({}));
 
         if let Some(hooks_obj) = hooks.as_object_mut() {
-            hooks_obj.insert(
-                "prepare-commit-msg".to_string(),
-                serde_json::Value::String(cmd),
-            );
+            hooks_obj.insert(hook.name().to_string(), serde_json::Value::String(cmd));
         }
     }
 
@@ -193,9 +223,9 @@ pub fn install_simple_git_hooks(cwd: &Path, agent: &AgentName) -> Result<(), Hoo
 
 /// Install plain git hook
 ///
-/// Creates .git/hooks/prepare-commit-msg script
+/// Creates .git/hooks/<hook> script
 /// Handles git worktrees by resolving .git file's gitdir reference
-pub fn install_plain_git(cwd: &Path, agent: &AgentName) -> Result<(), HookError> {
+pub fn install_plain_git(cwd: &Path, hook: &GitHook) -> Result<(), HookError> {
     let git_dir = resolve_git_dir(cwd).map_err(|_| HookError::GitDirResolutionFailed)?;
     let hooks_dir = git_dir.join("hooks");
 
@@ -204,7 +234,7 @@ pub fn install_plain_git(cwd: &Path, agent: &AgentName) -> Result<(), HookError>
         fs::create_dir_all(&hooks_dir).map_err(HookError::Io)?;
     }
 
-    let hook_path = hooks_dir.join("prepare-commit-msg");
+    let hook_path = hooks_dir.join(hook.name());
 
     // Create hook script
     let script = format!(
@@ -214,7 +244,7 @@ pub fn install_plain_git(cwd: &Path, agent: &AgentName) -> Result<(), HookError>
 {}
 "#,
         BINARY_NAME,
-        hook_command(agent)
+        hook.script_command()
     );
 
     fs::write(&hook_path, script).map_err(|_| HookError::ScriptCreationFailed {
@@ -263,7 +293,7 @@ mod tests {
     #[test]
     fn install_lefthook_creates_new_config() {
         let dir = TempDir::new().unwrap();
-        install_lefthook(dir.path(), &AgentName::Claude).unwrap();
+        install_lefthook(dir.path(), &GitHook::PrepareCommitMsg(AgentName::Claude)).unwrap();
 
         let config_path = dir.path().join("lefthook.yml");
         assert!(config_path.exists());
@@ -300,7 +330,7 @@ pre-commit:
         )
         .unwrap();
 
-        install_lefthook(dir.path(), &AgentName::Codex).unwrap();
+        install_lefthook(dir.path(), &GitHook::PrepareCommitMsg(AgentName::Codex)).unwrap();
 
         let content = fs::read_to_string(&config_path).unwrap();
         assert!(content.contains("pre-commit"));
@@ -308,10 +338,80 @@ pre-commit:
         assert!(content.contains("--agent codex"));
     }
 
+    #[test]
+    fn hook_command_accepts_custom_agent() {
+        let agent = AgentName::Custom("ollama".to_string());
+        assert_eq!(
+            hook_command(&agent),
+            "commitment --agent ollama --message-only"
+        );
+    }
+
+    #[test]
+    fn install_lefthook_lint_hook_alongside_generation_hook() {
+        let dir = TempDir::new().unwrap();
+        install_lefthook(dir.path(), &GitHook::PrepareCommitMsg(AgentName::Claude)).unwrap();
+        install_lefthook(dir.path(), &GitHook::CommitMsg).unwrap();
+
+        let content = fs::read_to_string(dir.path().join("lefthook.yml")).unwrap();
+        let config: LefthookConfig = serde_yaml::from_str(&content).unwrap();
+        assert!(config.hooks.contains_key("prepare-commit-msg"));
+        assert_eq!(
+            config.hooks["commit-msg"].commands[BINARY_NAME].run,
+            "commitment lint \"{1}\""
+        );
+    }
+
+    #[test]
+    fn install_lefthook_skips_existing_commit_msg_hook() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("lefthook.yml");
+        fs::write(
+            &config_path,
+            "commit-msg:\n  commands:\n    commitlint:\n      run: npx commitlint\n",
+        )
+        .unwrap();
+
+        install_lefthook(dir.path(), &GitHook::CommitMsg).unwrap();
+
+        let content = fs::read_to_string(&config_path).unwrap();
+        assert!(content.contains("commitlint"));
+        assert!(!content.contains("commitment lint"));
+    }
+
+    #[test]
+    fn install_plain_git_lint_hook() {
+        let dir = TempDir::new().unwrap();
+        let git_dir = dir.path().join(".git");
+        fs::create_dir(&git_dir).unwrap();
+
+        install_plain_git(dir.path(), &GitHook::CommitMsg).unwrap();
+
+        let content = fs::read_to_string(git_dir.join("hooks/commit-msg")).unwrap();
+        assert!(content.contains("commitment lint \"$1\""));
+        assert!(!git_dir.join("hooks/prepare-commit-msg").exists());
+    }
+
+    #[test]
+    fn install_simple_git_hooks_lint_hook() {
+        let dir = TempDir::new().unwrap();
+        let package_json = dir.path().join("package.json");
+        fs::write(&package_json, r#"{"name": "test"}"#).unwrap();
+
+        install_simple_git_hooks(dir.path(), &GitHook::CommitMsg).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&package_json).unwrap()).unwrap();
+        assert_eq!(
+            json["simple-git-hooks"]["commit-msg"],
+            "commitment lint \"$1\""
+        );
+    }
+
     #[test]
     fn install_husky_creates_directory() {
         let dir = TempDir::new().unwrap();
-        install_husky(dir.path(), &AgentName::Gemini).unwrap();
+        install_husky(dir.path(), &GitHook::PrepareCommitMsg(AgentName::Gemini)).unwrap();
 
         let hook_path = dir.path().join(".husky/prepare-commit-msg");
         assert!(hook_path.exists());
@@ -334,7 +434,8 @@ pre-commit:
         // Create package.json
         fs::write(&package_json, r#"{"name": "test"}"#).unwrap();
 
-        install_simple_git_hooks(dir.path(), &AgentName::Claude).unwrap();
+        install_simple_git_hooks(dir.path(), &GitHook::PrepareCommitMsg(AgentName::Claude))
+            .unwrap();
 
         let content = fs::read_to_string(&package_json).unwrap();
         assert!(content.contains("simple-git-hooks"));
@@ -345,7 +446,8 @@ pre-commit:
     #[test]
     fn install_simple_git_hooks_fails_without_package_json() {
         let dir = TempDir::new().unwrap();
-        let result = install_simple_git_hooks(dir.path(), &AgentName::Claude);
+        let result =
+            install_simple_git_hooks(dir.path(), &GitHook::PrepareCommitMsg(AgentName::Claude));
         assert!(matches!(result, Err(HookError::ConfigNotFound { .. })));
     }
 
@@ -355,7 +457,7 @@ pre-commit:
         let git_dir = dir.path().join(".git");
         fs::create_dir(&git_dir).unwrap();
 
-        install_plain_git(dir.path(), &AgentName::Claude).unwrap();
+        install_plain_git(dir.path(), &GitHook::PrepareCommitMsg(AgentName::Claude)).unwrap();
 
         let hook_path = git_dir.join("hooks/prepare-commit-msg");
         assert!(hook_path.exists());
@@ -413,7 +515,7 @@ pre-commit:
     fn install_plain_git_fails_without_git_dir() {
         // Test that error mapping from GitError to HookError works
         let dir = TempDir::new().unwrap();
-        let result = install_plain_git(dir.path(), &AgentName::Claude);
+        let result = install_plain_git(dir.path(), &GitHook::PrepareCommitMsg(AgentName::Claude));
         assert!(matches!(result, Err(HookError::GitDirResolutionFailed)));
     }
 
@@ -435,7 +537,7 @@ prepare-commit-msg:
         .unwrap();
 
         // Install should return Ok without modifying file
-        let result = install_lefthook(dir.path(), &AgentName::Claude);
+        let result = install_lefthook(dir.path(), &GitHook::PrepareCommitMsg(AgentName::Claude));
         assert!(result.is_ok());
 
         // Config should be unchanged
@@ -463,7 +565,7 @@ pre-commit:
         .unwrap();
 
         // Install should succeed
-        let result = install_lefthook(dir.path(), &AgentName::Claude);
+        let result = install_lefthook(dir.path(), &GitHook::PrepareCommitMsg(AgentName::Claude));
         assert!(result.is_ok());
 
         // Config should have commitment hook added
@@ -1,7 +1,9 @@
 pub mod managers;
 
+pub use managers::GitHook;
+
+use crate::config::Config;
 use crate::error::HookError;
-use crate::types::AgentName;
 use std::fmt;
 use std::path::Path;
 use std::str::FromStr;
@@ -101,13 +103,28 @@ pub fn detect_hook_manager(cwd: &Path) -> Option<HookManager> {
 
 /// Install hook for the specified manager
 ///
-/// Dispatches to the appropriate manager-specific installation function
-pub fn install_hook(manager: HookManager, cwd: &Path, agent: &AgentName) -> Result<(), HookError> {
+/// Installs the `prepare-commit-msg` generation hook, which runs the agent
+/// from the resolved `config`.
+pub fn install_hook(manager: HookManager, cwd: &Path, config: &Config) -> Result<(), HookError> {
+    install(
+        manager,
+        cwd,
+        &GitHook::PrepareCommitMsg(config.agent.clone()),
+    )
+}
+
+/// Install the `commit-msg` hook that runs `commitment lint` on every message
+pub fn install_lint_hook(manager: HookManager, cwd: &Path) -> Result<(), HookError> {
+    install(manager, cwd, &GitHook::CommitMsg)
+}
+
+/// Dispatch to the manager-specific installation function
+fn install(manager: HookManager, cwd: &Path, hook: &GitHook) -> Result<(), HookError> {
     match manager {
-        HookManager::Lefthook => managers::install_lefthook(cwd, agent),
-        HookManager::Husky => managers::install_husky(cwd, agent),
-        HookManager::SimpleGitHooks => managers::install_simple_git_hooks(cwd, agent),
-        HookManager::PlainGit => managers::install_plain_git(cwd, agent),
+        HookManager::Lefthook => managers::install_lefthook(cwd, hook),
+        HookManager::Husky => managers::install_husky(cwd, hook),
+        HookManager::SimpleGitHooks => managers::install_simple_git_hooks(cwd, hook),
+        HookManager::PlainGit => managers::install_plain_git(cwd, hook),
     }
 }
 
@@ -233,6 +250,32 @@ mod tests {
         assert_eq!(detect_hook_manager(dir.path()), Some(HookManager::Lefthook));
     }
 
+    #[test]
+    fn install_hook_uses_configured_agent() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".husky")).unwrap();
+        let config = Config {
+            agent: crate::types::AgentName::Codex,
+            ..Config::default()
+        };
+
+        install_hook(HookManager::Husky, dir.path(), &config).unwrap();
+
+        let content = fs::read_to_string(dir.path().join(".husky/prepare-commit-msg")).unwrap();
+        assert!(content.contains("commitment --agent codex"));
+    }
+
+    #[test]
+    fn install_lint_hook_writes_commit_msg_script() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".husky")).unwrap();
+
+        install_lint_hook(HookManager::Husky, dir.path()).unwrap();
+
+        let content = fs::read_to_string(dir.path().join(".husky/commit-msg")).unwrap();
+        assert!(content.contains("commitment lint \"$1\""));
+    }
+
     #[test]
     fn detect_priority_husky_over_simple_git_hooks() {
         let dir = TempDir::new().unwrap();
@@ -0,0 +1,259 @@
+use crate::diff::{name_status_path, split_files};
+use crate::error::GitError;
+use crate::types::StagedDiff;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+/// Exclusion file read from the repository root
+pub const IGNORE_FILE: &str = ".commitmentignore";
+
+/// Appended to excluded files in FILE STATUS
+pub const OMITTED_NOTE: &str = "(content omitted)";
+
+/// Excluded unless re-included with `!<pattern>` in `.commitmentignore`
+///
+/// Lockfiles, snapshots and minified bundles are large, machine-written, and
+/// say nothing about intent.
+pub const DEFAULT_PATTERNS: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "*.snap",
+    "*.min.js",
+    "*.min.css",
+];
+
+/// One compiled pattern
+struct Rule {
+    matcher: GlobSet,
+    /// `!pattern`: re-include paths an earlier rule excluded
+    negated: bool,
+}
+
+/// Gitignore-style rules for files whose content is left out of the prompt
+///
+/// Supports the common subset of `.gitignore` syntax:
+/// - `*.snap` (no slash) matches the name at any depth
+/// - `/schema.sql` or `gen/*.rs` (with a slash) is anchored to the repo root
+/// - a pattern also matches everything below a directory of that name
+/// - `!pattern` re-includes; the last matching rule wins
+/// - blank lines and `#` comments are ignored
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Compile patterns in order
+    pub fn new<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Result<Self, globset::Error> {
+        let rules = patterns
+            .into_iter()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(compile_rule)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Default patterns followed by the repository's `.commitmentignore`
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::InvalidIgnorePattern` for a malformed glob, or
+    /// `GitError::Io` if the file exists but can't be read.
+    pub fn load(repo_root: &Path) -> Result<Self, GitError> {
+        let path = repo_root.join(IGNORE_FILE);
+        let content = if path.is_file() {
+            std::fs::read_to_string(&path)?
+        } else {
+            String::new()
+        };
+
+        let mut rules =
+            Self::new(DEFAULT_PATTERNS.iter().copied()).expect("default patterns are valid globs");
+        for line in content.lines() {
+            let rule = Self::new([line]).map_err(|e| GitError::InvalidIgnorePattern {
+                path: path.display().to_string(),
+                pattern: line.trim().to_string(),
+                reason: e.kind().to_string(),
+            })?;
+            rules.rules.extend(rule.rules);
+        }
+        Ok(rules)
+    }
+
+    /// Whether a repo-relative path is excluded
+    pub fn is_excluded(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matcher.is_match(path))
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Drop excluded files' content from the diff
+    ///
+    /// Their paths are listed in `omitted`, and the prompt marks their FILE
+    /// STATUS lines "(content omitted)", so the agent still knows they
+    /// changed. `stat` and `name_status` are kept as-is.
+    pub fn apply(&self, diff: StagedDiff) -> StagedDiff {
+        let excluded = |path: Option<&str>| path.is_some_and(|path| self.is_excluded(path));
+
+        let omitted: Vec<String> = diff
+            .name_status
+            .lines()
+            .filter_map(name_status_path)
+            .filter(|path| self.is_excluded(path))
+            .map(str::to_string)
+            .collect();
+        if omitted.is_empty() {
+            return diff;
+        }
+
+        let content = split_files(&diff.diff)
+            .into_iter()
+            .filter(|file| !excluded(file.path))
+            .map(|file| file.text)
+            .collect();
+
+        StagedDiff {
+            diff: content,
+            omitted,
+            ..diff
+        }
+    }
+}
+
+/// Translate one gitignore-style line into globs
+fn compile_rule(line: &str) -> Result<Rule, globset::Error> {
+    let (negated, pattern) = match line.strip_prefix('!') {
+        Some(pattern) => (true, pattern),
+        None => (false, line),
+    };
+    let anchored = pattern.trim_end_matches('/').contains('/');
+    let pattern = pattern.trim_matches('/');
+    let base = if anchored {
+        pattern.to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+
+    let mut matcher = GlobSetBuilder::new();
+    for glob in [base.clone(), format!("{}/**", base)] {
+        matcher.add(GlobBuilder::new(&glob).literal_separator(true).build()?);
+    }
+    Ok(Rule {
+        matcher: matcher.build()?,
+        negated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(patterns: &[&str]) -> IgnoreRules {
+        IgnoreRules::new(patterns.iter().copied()).unwrap()
+    }
+
+    #[test]
+    fn name_patterns_match_at_any_depth() {
+        let rules = rules(&["*.snap", "Cargo.lock"]);
+        assert!(rules.is_excluded("Cargo.lock"));
+        assert!(rules.is_excluded("crates/core/Cargo.lock"));
+        assert!(rules.is_excluded("tests/__snapshots__/app.snap"));
+        assert!(!rules.is_excluded("src/lib.rs"));
+    }
+
+    #[test]
+    fn patterns_with_slash_are_anchored() {
+        let rules = rules(&["/schema.sql", "gen/*.rs"]);
+        assert!(rules.is_excluded("schema.sql"));
+        assert!(!rules.is_excluded("db/schema.sql"));
+        assert!(rules.is_excluded("gen/api.rs"));
+        assert!(!rules.is_excluded("gen/nested/api.rs"));
+        assert!(!rules.is_excluded("src/gen/api.rs"));
+    }
+
+    #[test]
+    fn directory_patterns_match_contents() {
+        let rules = rules(&["vendor/", "third_party"]);
+        assert!(rules.is_excluded("vendor/lib/a.go"));
+        assert!(rules.is_excluded("pkg/third_party/x.c"));
+        assert!(!rules.is_excluded("src/vendored.rs"));
+    }
+
+    #[test]
+    fn negation_re_includes_and_last_match_wins() {
+        let rules = rules(&["*.lock", "!Cargo.lock", "# comment", ""]);
+        assert!(rules.is_excluded("yarn.lock"));
+        assert!(!rules.is_excluded("Cargo.lock"));
+    }
+
+    #[test]
+    fn apply_drops_content_and_lists_omitted_files() {
+        let diff = StagedDiff {
+            stat: " Cargo.lock | 40 +++\n src/lib.rs | 2 +-\n".to_string(),
+            name_status: "M\tCargo.lock\nM\tsrc/lib.rs".to_string(),
+            diff: "diff --git a/Cargo.lock b/Cargo.lock\n+lots\ndiff --git a/src/lib.rs b/src/lib.rs\n+fn x() {}\n".to_string(),
+            omitted: Vec::new(),
+        };
+
+        let filtered = rules(&["Cargo.lock"]).apply(diff.clone());
+
+        assert_eq!(filtered.name_status, diff.name_status);
+        assert_eq!(filtered.omitted, ["Cargo.lock"]);
+        assert_eq!(
+            filtered.diff,
+            "diff --git a/src/lib.rs b/src/lib.rs\n+fn x() {}\n"
+        );
+        assert_eq!(filtered.stat, diff.stat);
+    }
+
+    #[test]
+    fn apply_without_matches_is_unchanged() {
+        let diff = StagedDiff {
+            stat: String::new(),
+            name_status: "M\tsrc/lib.rs".to_string(),
+            diff: "diff --git a/src/lib.rs b/src/lib.rs\n".to_string(),
+            omitted: Vec::new(),
+        };
+        assert_eq!(rules(&["*.snap"]).apply(diff.clone()), diff);
+    }
+
+    #[test]
+    fn load_reads_file_after_defaults() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join(IGNORE_FILE),
+            "!Cargo.lock\ndocs/generated/\n",
+        )
+        .unwrap();
+
+        let rules = IgnoreRules::load(dir.path()).unwrap();
+        assert!(!rules.is_excluded("Cargo.lock"));
+        assert!(rules.is_excluded("yarn.lock"));
+        assert!(rules.is_excluded("docs/generated/api.md"));
+    }
+
+    #[test]
+    fn load_reports_invalid_pattern() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join(IGNORE_FILE), "src/[oops\n").unwrap();
+
+        match IgnoreRules::load(dir.path()) {
+            Err(GitError::InvalidIgnorePattern { pattern, .. }) => {
+                assert_eq!(pattern, "src/[oops")
+            }
+            _ => panic!("expected InvalidIgnorePattern error"),
+        }
+    }
+}
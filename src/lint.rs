@@ -0,0 +1,478 @@
+use crate::types::{CommitRules, CommitValidationError, ConventionalCommit, SyntaxError};
+use std::fmt;
+
+/// Headers longer than this get a `header-max-length` warning from [`lint_message`]
+pub const HEADER_MAX_LENGTH: usize = 72;
+
+/// Git's default `core.commentChar`
+pub const DEFAULT_COMMENT_CHAR: &str = "#";
+
+/// Git's scissors line after the comment character; everything below it is
+/// ignored (`git commit -v`)
+const SCISSORS: &str = " ------------------------ >8 ------------------------";
+
+/// Prefixes of messages git writes itself, which are never linted
+const GENERATED_PREFIXES: &[&str] = &["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "];
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Fails the lint (non-zero exit)
+    Error,
+    /// Reported, but doesn't fail the lint
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `pad` so callers can align with `{:<8}`
+        f.pad(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        })
+    }
+}
+
+/// A single rule violation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Rule identifier (e.g. `type-enum`)
+    pub rule: &'static str,
+    pub severity: Severity,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based character column
+    pub column: usize,
+    pub message: String,
+}
+
+/// Lint result for one commit message
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintReport {
+    pub diagnostics: Vec<Diagnostic>,
+    /// Message was generated by git (merge, revert, fixup) and skipped
+    pub skipped: bool,
+}
+
+impl LintReport {
+    /// Number of error diagnostics
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    /// Number of warning diagnostics
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    /// Whether the message passes (warnings allowed)
+    pub fn is_ok(&self) -> bool {
+        self.error_count() == 0
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    }
+}
+
+/// Lint a commit message as written by a human
+///
+/// Strips git comment lines and anything below the scissors line (as git
+/// itself does before committing), skips git-generated messages, then runs
+/// the same parser used for AI output plus a few style rules. The style
+/// rules and the scope check look at the header itself, so they are
+/// reported even when the parser rejects the message.
+pub fn lint_message(raw: &str) -> LintReport {
+    let rules = CommitRules {
+        max_header_length: Some(HEADER_MAX_LENGTH),
+        ..CommitRules::default()
+    };
+    lint_message_with(raw, &rules)
+}
+
+/// Lint a commit message against project-specific [`CommitRules`]
+///
+/// Same as [`lint_message`]; scopes outside `rules.scopes` are reported
+/// under `scope-enum`, and headers over `rules.max_header_length` (if set)
+/// get a `header-max-length` warning rather than an error.
+pub fn lint_message_with(raw: &str, rules: &CommitRules) -> LintReport {
+    lint_message_with_comment_char(raw, rules, DEFAULT_COMMENT_CHAR)
+}
+
+/// Lint a commit message whose comment lines start with `comment_char`
+///
+/// Same as [`lint_message_with`], for repositories that set git's
+/// `core.commentChar` (see [`crate::git::RealGitProvider::comment_char`]).
+pub fn lint_message_with_comment_char(
+    raw: &str,
+    rules: &CommitRules,
+    comment_char: &str,
+) -> LintReport {
+    let message = strip_git_comments(raw, comment_char);
+    // Diagnostics count lines of the raw message
+    let leading_blank = message
+        .lines()
+        .take_while(|line| line.trim().is_empty())
+        .count();
+    let message = message.trim();
+
+    if GENERATED_PREFIXES
+        .iter()
+        .any(|prefix| message.starts_with(prefix))
+    {
+        return LintReport {
+            diagnostics: Vec::new(),
+            skipped: true,
+        };
+    }
+
+    let mut diagnostics = Vec::new();
+
+    // The parser accepts any case (Conventional Commits 1.0, rule 15), but
+    // lowercase is the convention
+    let header = message.lines().next().unwrap_or_default();
+    let kind = header
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .next()
+        .unwrap_or_default();
+    let lowercase = kind.to_lowercase();
+    if kind != lowercase && rules.types.iter().any(|t| t.name == lowercase) {
+        diagnostics.push(Diagnostic {
+            rule: "type-case",
+            severity: Severity::Warning,
+            line: 1,
+            column: 1,
+            message: format!("type '{}' should be lowercase", kind),
+        });
+    }
+
+    // Scopes and length are checked below, even when the parser gives up
+    // before reaching them
+    let parse_rules = CommitRules {
+        max_header_length: None,
+        scopes: Vec::new(),
+        ..rules.clone()
+    };
+    match ConventionalCommit::validate_with(message, &parse_rules) {
+        Ok(_) => {}
+        Err(CommitValidationError::Empty) => diagnostics.push(Diagnostic {
+            rule: "message-empty",
+            severity: Severity::Error,
+            line: 1,
+            column: 1,
+            message: "commit message is empty".to_string(),
+        }),
+        Err(CommitValidationError::Syntax {
+            line,
+            column,
+            error,
+            ..
+        }) => diagnostics.push(Diagnostic {
+            rule: syntax_rule(&error),
+            severity: Severity::Error,
+            line,
+            column,
+            message: error.to_string(),
+        }),
+    }
+
+    if !message.is_empty() {
+        diagnostics.extend(header_diagnostics(header, rules));
+    }
+
+    for diagnostic in &mut diagnostics {
+        diagnostic.line += leading_blank;
+    }
+    LintReport {
+        diagnostics,
+        skipped: false,
+    }
+}
+
+/// Rules checked on the raw header, independently of the parser
+///
+/// Reports scopes outside `rules.scopes` (`scope-enum`), a header over
+/// `rules.max_header_length` and a description ending in a period, so a
+/// header with several problems gets a diagnostic for each.
+fn header_diagnostics(header: &str, rules: &CommitRules) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if let Some((column, scope)) = unknown_scope(header, &rules.scopes) {
+        diagnostics.push(Diagnostic {
+            rule: "scope-enum",
+            severity: Severity::Error,
+            line: 1,
+            column,
+            message: SyntaxError::UnknownScope {
+                found: scope.to_string(),
+                allowed: rules.scopes.clone(),
+            }
+            .to_string(),
+        });
+    }
+
+    let header = header.trim_end();
+    let header_length = header.chars().count();
+    if let Some(max) = rules.max_header_length
+        && header_length > max
+    {
+        diagnostics.push(Diagnostic {
+            rule: "header-max-length",
+            severity: Severity::Warning,
+            line: 1,
+            column: max + 1,
+            message: format!("header is {} characters (max {})", header_length, max),
+        });
+    }
+    if header
+        .split_once(':')
+        .is_some_and(|(_, description)| description.ends_with('.'))
+    {
+        diagnostics.push(Diagnostic {
+            rule: "description-full-stop",
+            severity: Severity::Warning,
+            line: 1,
+            column: header_length,
+            message: "description should not end with a period".to_string(),
+        });
+    }
+
+    diagnostics
+}
+
+/// The first scope in `header` outside `allowed`, with its column
+///
+/// Malformed scopes are left to the parser (`scope-format`).
+fn unknown_scope<'a>(header: &'a str, allowed: &[String]) -> Option<(usize, &'a str)> {
+    if allowed.is_empty() {
+        return None;
+    }
+    let open = header.find('(')?;
+    if !header[..open].chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let close = open + header[open..].find(')')?;
+    let mut offset = open + 1;
+    for part in header[open + 1..close].split(',') {
+        let malformed = part.is_empty() || part.contains(|c: char| c.is_whitespace() || c == '(');
+        if !malformed && !allowed.iter().any(|scope| scope == part) {
+            return Some((header[..offset].chars().count() + 1, part));
+        }
+        offset += part.len() + 1;
+    }
+    None
+}
+
+/// Rule identifier for each parser error
+fn syntax_rule(error: &SyntaxError) -> &'static str {
+    match error {
+        SyntaxError::MissingType | SyntaxError::UnknownType { .. } => "type-enum",
+        SyntaxError::UnclosedScope | SyntaxError::EmptyScope | SyntaxError::InvalidScope => {
+            "scope-format"
+        }
+        SyntaxError::UnknownScope { .. } => "scope-enum",
+        SyntaxError::MissingColon | SyntaxError::MissingSpace | SyntaxError::LeadingWhitespace => {
+            "header-format"
+        }
+        SyntaxError::EmptyDescription => "description-empty",
+        SyntaxError::MissingBlankLine => "body-leading-blank",
+        SyntaxError::HeaderTooLong { .. } => "header-max-length",
+        SyntaxError::UnmarkedBreakingChange => "breaking-change-marker",
+    }
+}
+
+/// Blank out comment lines and drop everything from the scissors line down
+///
+/// Comment lines become empty lines rather than disappearing, so line
+/// numbers in the result match the raw message.
+pub(crate) fn strip_git_comments(raw: &str, comment_char: &str) -> String {
+    let scissors = format!("{}{}", comment_char, SCISSORS);
+    raw.lines()
+        .take_while(|line| *line != scissors)
+        .map(|line| {
+            if line.starts_with(comment_char) {
+                ""
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(report: &LintReport) -> Vec<&'static str> {
+        report.diagnostics.iter().map(|d| d.rule).collect()
+    }
+
+    #[test]
+    fn valid_message_has_no_diagnostics() {
+        let report = lint_message("feat(api): add endpoint\n\n- Details");
+        assert!(report.is_ok());
+        assert!(report.diagnostics.is_empty());
+        assert!(!report.skipped);
+    }
+
+    #[test]
+    fn reports_rule_and_position_for_syntax_errors() {
+        let report = lint_message("feat add endpoint");
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.diagnostics,
+            vec![Diagnostic {
+                rule: "header-format",
+                severity: Severity::Error,
+                line: 1,
+                column: 5,
+                message: "expected ':' after the type or scope".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn maps_parser_errors_to_rules() {
+        assert_eq!(rules(&lint_message("feature: x")), ["type-enum"]);
+        assert_eq!(rules(&lint_message("fix(): x")), ["scope-format"]);
+        assert_eq!(rules(&lint_message("fix:")), ["description-empty"]);
+        assert_eq!(rules(&lint_message("fix: x\nbody")), ["body-leading-blank"]);
+        assert_eq!(rules(&lint_message("")), ["message-empty"]);
+    }
+
+    #[test]
+    fn uppercase_types_are_only_a_warning() {
+        for message in ["FIX: x", "Fix: x"] {
+            let report = lint_message(message);
+            assert_eq!(rules(&report), ["type-case"]);
+            assert_eq!(report.diagnostics[0].severity, Severity::Warning);
+            assert!(report.is_ok());
+        }
+    }
+
+    #[test]
+    fn reports_scopes_outside_the_allowed_set() {
+        let allowed = CommitRules {
+            scopes: vec!["core".to_string()],
+            ..CommitRules::default()
+        };
+        assert!(lint_message_with("fix(core): x", &allowed).is_ok());
+        assert_eq!(
+            rules(&lint_message_with("fix(web): x", &allowed)),
+            ["scope-enum"]
+        );
+    }
+
+    #[test]
+    fn reports_every_violation_in_a_header() {
+        let allowed = CommitRules {
+            scopes: vec!["core".to_string()],
+            max_header_length: Some(20),
+            ..CommitRules::default()
+        };
+
+        let report = lint_message_with("feature(web): add a login form.", &allowed);
+        assert_eq!(
+            rules(&report),
+            [
+                "type-enum",
+                "scope-enum",
+                "header-max-length",
+                "description-full-stop"
+            ]
+        );
+        assert_eq!(report.diagnostics[1].column, 9);
+        assert_eq!(report.error_count(), 2);
+
+        // An empty scope is a format error, not an unknown scope
+        let report = lint_message_with("feature(): add a login form.", &allowed);
+        assert_eq!(
+            rules(&report),
+            ["type-enum", "header-max-length", "description-full-stop"]
+        );
+    }
+
+    #[test]
+    fn style_rules_are_warnings() {
+        let long = format!("feat: {}.", "a".repeat(80));
+        let report = lint_message(&long);
+        assert!(report.is_ok());
+        assert_eq!(
+            rules(&report),
+            ["header-max-length", "description-full-stop"]
+        );
+        assert_eq!(report.warning_count(), 2);
+    }
+
+    #[test]
+    fn header_length_follows_the_rules() {
+        let header = format!("feat: {}", "a".repeat(50));
+        let short = CommitRules {
+            max_header_length: Some(50),
+            ..CommitRules::default()
+        };
+
+        let report = lint_message_with(&header, &short);
+        assert!(report.is_ok());
+        assert_eq!(rules(&report), ["header-max-length"]);
+        assert_eq!(report.diagnostics[0].column, 51);
+        assert_eq!(
+            report.diagnostics[0].message,
+            "header is 56 characters (max 50)"
+        );
+
+        // No limit configured
+        assert!(
+            lint_message_with(
+                &format!("feat: {}", "a".repeat(100)),
+                &CommitRules::default()
+            )
+            .diagnostics
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn ignores_git_comments_and_scissors() {
+        let raw = "fix: handle eof\n\n# Please enter the commit message\n# ------------------------ >8 ------------------------\ndiff --git a/x b/x\n";
+        assert!(lint_message(raw).diagnostics.is_empty());
+    }
+
+    #[test]
+    fn lines_count_from_the_raw_message() {
+        let report = lint_message("# Please enter the commit message\n\nfix: x\nbody\n");
+        assert_eq!(rules(&report), ["body-leading-blank"]);
+        assert_eq!(report.diagnostics[0].line, 4);
+    }
+
+    #[test]
+    fn uses_the_configured_comment_char() {
+        let raw = "fix: handle eof\n\n; Please enter the commit message\n; ------------------------ >8 ------------------------\ndiff --git a/x b/x\n";
+        let rules = CommitRules::default();
+        assert!(
+            lint_message_with_comment_char(raw, &rules, ";")
+                .diagnostics
+                .is_empty()
+        );
+        // `#` lines are message text when another comment char is set
+        let report = lint_message_with_comment_char("# fix: x\n", &rules, ";");
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn skips_git_generated_messages() {
+        for msg in [
+            "Merge branch 'main' into feature",
+            "Revert \"feat: add thing\"",
+            "fixup! feat: add thing",
+        ] {
+            let report = lint_message(msg);
+            assert!(report.skipped, "should skip: {}", msg);
+            assert!(report.is_ok());
+        }
+    }
+}
@@ -1,10 +1,39 @@
 use clap::Parser;
+use commitment_rs::agents::kill_running_agents;
 use commitment_rs::cli::Cli;
+use console::style;
+use std::time::Duration;
+
+/// How long a run gets to unwind after Ctrl-C before the process exits anyway
+const INTERRUPT_GRACE: Duration = Duration::from_secs(2);
 
 #[tokio::main]
 async fn main() {
+    // Ctrl-C normally cancels the run below, which drops its process guards
+    // and prompt files; this exits anyway if the run is stuck in blocking I/O
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            tokio::time::sleep(INTERRUPT_GRACE).await;
+            kill_running_agents();
+            std::process::exit(130);
+        }
+    });
+
     let cli = Cli::parse();
-    if cli.run().await.is_err() {
-        std::process::exit(1);
+    let outcome = tokio::select! {
+        result = cli.run() => Some(result),
+        _ = tokio::signal::ctrl_c() => None,
+    };
+    match outcome {
+        Some(Ok(())) => {}
+        Some(Err(e)) => {
+            eprintln!("{} {:#}", style("error:").red().bold(), e);
+            std::process::exit(1);
+        }
+        None => {
+            // The run was dropped; stop anything that outlived its guards
+            kill_running_agents();
+            std::process::exit(130);
+        }
     }
 }
@@ -1,21 +1,37 @@
-use crate::types::{CONVENTIONAL_COMMIT_TYPES, StagedDiff};
+use crate::breaking::{BreakingChange, BreakingCheck, detect_breaking_changes};
+use crate::config::Config;
+use crate::diff::{FileDiff, name_status_path, split_files};
+use crate::ignore::OMITTED_NOTE;
+use crate::types::{AgentName, ConventionalCommit, StagedDiff, commit_types_display};
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-/// Maximum length for diff content before truncation
-const MAX_DIFF_LENGTH: usize = 8000;
+/// Rough bytes per token used to estimate prompt size
+///
+/// Code tokenizes a little denser than English; the agent budgets leave
+/// enough headroom that the difference doesn't matter.
+pub(crate) const BYTES_PER_TOKEN: usize = 4;
+
+/// Tokens set aside per file for a "hunks omitted" summary line
+const SUMMARY_TOKENS: usize = 20;
+
+/// Estimated token count of `text`
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(BYTES_PER_TOKEN)
+}
 
 /// Truncate diff content to prevent token limit issues
 ///
-/// If diff exceeds MAX_DIFF_LENGTH, truncates at a character boundary
-/// and appends a truncation indicator.
-fn truncate_diff(diff: &str) -> String {
-    if diff.len() <= MAX_DIFF_LENGTH {
+/// If diff exceeds `max_length`, truncates at a character boundary
+/// and appends a truncation indicator. Only used for text that can't be
+/// split into hunks; see [`budget_diff`].
+fn truncate_diff(diff: &str, max_length: usize) -> String {
+    if diff.len() <= max_length {
         return diff.to_string();
     }
 
     // Find character boundary to avoid panicking on UTF-8
-    let mut boundary = MAX_DIFF_LENGTH;
+    let mut boundary = max_length;
     while boundary > 0 && !diff.is_char_boundary(boundary) {
         boundary -= 1;
     }
@@ -23,6 +39,224 @@ fn truncate_diff(diff: &str) -> String {
     format!("{}\n... (diff truncated)", &diff[..boundary])
 }
 
+/// One file's section split at its `@@` hunk headers
+struct FileHunks<'a> {
+    path: Option<&'a str>,
+    /// Everything before the first hunk (`diff --git`, index, `---`/`+++`)
+    header: &'a str,
+    /// Each hunk from its `@@` line up to the next one
+    hunks: Vec<&'a str>,
+}
+
+impl<'a> FileHunks<'a> {
+    fn split(file: FileDiff<'a>) -> Self {
+        let text = file.text;
+        let mut starts = Vec::new();
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            if line.starts_with("@@") {
+                starts.push(offset);
+            }
+            offset += line.len();
+        }
+
+        let header = &text[..starts.first().copied().unwrap_or(text.len())];
+        let hunks = starts
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| &text[start..starts.get(i + 1).copied().unwrap_or(text.len())])
+            .collect();
+        Self {
+            path: file.path,
+            header,
+            hunks,
+        }
+    }
+}
+
+/// Fit a unified diff into roughly `budget` tokens
+///
+/// Diffs that already fit are returned unchanged. Otherwise every file keeps
+/// its header and the budget is shared fairly: files smaller than an even
+/// share are kept whole, and what they leave over is split between the
+/// larger ones. Hunks that fit are kept whole; the first one that doesn't is
+/// cut to what is left of the file's share (its `@@` line counts too), and
+/// later ones are dropped. Each file that lost lines gets a summary line
+/// such as `... +120/-40 lines in src/foo.rs, 1 hunk truncated, 6 hunks omitted`.
+///
+/// Text without hunk headers is cut at the byte equivalent of the budget.
+pub fn budget_diff(diff: &str, budget: usize) -> String {
+    if estimate_tokens(diff) <= budget {
+        return diff.to_string();
+    }
+
+    let files: Vec<FileHunks> = split_files(diff)
+        .into_iter()
+        .map(FileHunks::split)
+        .collect();
+    if files.iter().all(|file| file.hunks.is_empty()) {
+        return truncate_diff(diff, budget * BYTES_PER_TOKEN);
+    }
+
+    let fixed: usize = files
+        .iter()
+        .map(|file| estimate_tokens(file.header) + SUMMARY_TOKENS)
+        .sum();
+    let needs: Vec<usize> = files
+        .iter()
+        .map(|file| file.hunks.iter().map(|hunk| estimate_tokens(hunk)).sum())
+        .collect();
+    let mut available = budget.saturating_sub(fixed);
+    let shares = fair_shares(&needs, available);
+
+    // Keep whole hunks in order within each file's share, then cut the
+    // first one that didn't fit to what is left of it...
+    let mut keep: Vec<Vec<usize>> = Vec::with_capacity(files.len());
+    for (file, share) in files.iter().zip(shares) {
+        let mut left = share;
+        let mut kept: Vec<usize> = file
+            .hunks
+            .iter()
+            .map(|hunk| {
+                let cost = estimate_tokens(hunk);
+                if cost > left {
+                    return 0;
+                }
+                left -= cost;
+                available -= cost;
+                hunk.len()
+            })
+            .collect();
+        if let Some(index) = kept.iter().position(|&len| len == 0) {
+            kept[index] = hunk_prefix(file.hunks[index], left);
+            available -= estimate_tokens(&file.hunks[index][..kept[index]]);
+        }
+        keep.push(kept);
+    }
+    // ...then spend what whole-hunk rounding left over, in diff order
+    for (file, keep) in files.iter().zip(&mut keep) {
+        for (hunk, kept) in file.hunks.iter().zip(keep.iter_mut()) {
+            let cost = estimate_tokens(hunk);
+            if *kept == 0 && cost <= available {
+                *kept = hunk.len();
+                available -= cost;
+            }
+        }
+    }
+
+    let mut budgeted = String::new();
+    for (file, keep) in files.iter().zip(keep) {
+        budgeted.push_str(file.header);
+        let mut omitted = OmittedHunks::default();
+        for (hunk, kept) in file.hunks.iter().zip(keep) {
+            let (shown, rest) = hunk.split_at(kept);
+            budgeted.push_str(shown);
+            match (shown.is_empty(), rest.is_empty()) {
+                (_, true) => {}
+                (true, false) => omitted.add(hunk),
+                (false, false) => omitted.cut(rest),
+            }
+        }
+        if omitted.hunks + omitted.truncated > 0 {
+            if !budgeted.ends_with('\n') {
+                budgeted.push('\n');
+            }
+            budgeted.push_str(&omitted.summary(file.path.unwrap_or("diff")));
+            budgeted.push('\n');
+        }
+    }
+    budgeted
+}
+
+/// Length of the start of `hunk` that fits in `budget` tokens
+///
+/// The `@@` line and whole lines after it; 0 if not even one line fits.
+fn hunk_prefix(hunk: &str, budget: usize) -> usize {
+    let max = budget * BYTES_PER_TOKEN;
+    let mut lines = hunk.split_inclusive('\n');
+    let header = lines.next().map_or(0, str::len);
+    let mut len = header;
+    for line in lines {
+        if len + line.len() > max {
+            break;
+        }
+        len += line.len();
+    }
+    if len > header && len <= max { len } else { 0 }
+}
+
+/// Split `budget` between files needing `needs[i]` tokens each
+///
+/// Water-filling: going from the smallest file up, each gets the lesser of
+/// what it needs and an even share of what's left.
+pub(crate) fn fair_shares(needs: &[usize], budget: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..needs.len()).collect();
+    order.sort_by_key(|&i| needs[i]);
+
+    let mut shares = vec![0; needs.len()];
+    let mut left = budget;
+    for (done, &i) in order.iter().enumerate() {
+        shares[i] = needs[i].min(left / (needs.len() - done));
+        left -= shares[i];
+    }
+    shares
+}
+
+/// Line counts of the hunks dropped (or cut short) from one file
+#[derive(Default)]
+struct OmittedHunks {
+    hunks: usize,
+    truncated: usize,
+    added: usize,
+    removed: usize,
+}
+
+impl OmittedHunks {
+    /// A hunk left out whole
+    fn add(&mut self, hunk: &str) {
+        self.hunks += 1;
+        self.count(hunk.lines().skip(1));
+    }
+
+    /// The lines cut from the end of a hunk
+    fn cut(&mut self, rest: &str) {
+        self.truncated += 1;
+        self.count(rest.lines());
+    }
+
+    fn count<'a>(&mut self, lines: impl Iterator<Item = &'a str>) {
+        for line in lines {
+            if line.starts_with('+') {
+                self.added += 1;
+            } else if line.starts_with('-') {
+                self.removed += 1;
+            }
+        }
+    }
+
+    fn summary(&self, path: &str) -> String {
+        let hunks = |count: usize| if count == 1 { "hunk" } else { "hunks" };
+        let mut parts = Vec::new();
+        if self.truncated > 0 {
+            parts.push(format!(
+                "{} {} truncated",
+                self.truncated,
+                hunks(self.truncated)
+            ));
+        }
+        if self.hunks > 0 {
+            parts.push(format!("{} {} omitted", self.hunks, hunks(self.hunks)));
+        }
+        format!(
+            "... +{}/-{} lines in {}, {}",
+            self.added,
+            self.removed,
+            path,
+            parts.join(", ")
+        )
+    }
+}
+
 /// Parse change summary from git stat and name-status output
 ///
 /// Extracts:
@@ -61,14 +295,63 @@ fn parse_change_summary(stat: &str, name_status: &str) -> String {
 ///
 /// Creates a template with:
 /// - Instructions for conventional commit format
+/// - The allowed commit types and when to use each
 /// - Change summary (file count, lines added/removed)
 /// - File statistics (--stat)
 /// - File name/status (--name-status)
-/// - Full diff content (truncated if over 8000 chars)
+/// - Full diff content (fitted to the agent's token budget, see [`budget_diff`])
 /// - Marker tags for response extraction
 ///
 /// No diff analysis - AI handles pattern detection
 pub fn build_prompt(diff: &StagedDiff) -> String {
+    build_prompt_with_config(diff, &Config::default())
+}
+
+/// Build AI prompt from staged git diff using the given configuration
+///
+/// Same template as [`build_prompt`], with the diff fitted to the token
+/// budget of `config.agent`.
+pub fn build_prompt_with_config(diff: &StagedDiff, config: &Config) -> String {
+    build_prompt_for_agent(diff, config, &config.agent, &[])
+}
+
+/// Build AI prompt for a specific agent, with style examples
+///
+/// Like [`build_prompt_with_config`], but budgets the diff for `agent`
+/// (which may differ from `config.agent`, e.g. with `--candidates-from`)
+/// and shows `examples` from the repository's history (see
+/// [`crate::history::collect_examples`]).
+pub fn build_prompt_for_agent(
+    diff: &StagedDiff,
+    config: &Config,
+    agent: &AgentName,
+    examples: &[ConventionalCommit],
+) -> String {
+    let mut prompt = build_prompt_overview(diff, config, examples);
+
+    // Full diff section (fitted to the token budget)
+    prompt.push_str("=== FULL DIFF ===\n");
+    if diff.diff.is_empty() {
+        prompt.push_str("(no changes)\n");
+    } else {
+        let budgeted = budget_diff(&diff.diff, config.diff_token_budget(agent));
+        prompt.push_str(&budgeted);
+        prompt.push('\n');
+    }
+
+    prompt
+}
+
+/// Instructions, markers and change overview shared by every generation prompt
+///
+/// Everything up to (not including) the diff itself, so the summarize
+/// strategy can follow it with chunk summaries instead.
+pub(crate) fn build_prompt_overview(
+    diff: &StagedDiff,
+    config: &Config,
+    examples: &[ConventionalCommit],
+) -> String {
+    let types = &config.commit_types;
     let mut prompt = String::new();
 
     // Instructions for conventional commit format
@@ -80,8 +363,14 @@ pub fn build_prompt(diff: &StagedDiff) -> String {
     );
     prompt.push_str(&format!(
         "   - Start with type: {}\n",
-        CONVENTIONAL_COMMIT_TYPES.join(", ")
+        commit_types_display(types)
     ));
+    for commit_type in types {
+        prompt.push_str(&format!(
+            "     {}: {}\n",
+            commit_type.name, commit_type.description
+        ));
+    }
     prompt.push_str("   - Optional scope in parentheses: type(scope): description\n");
     prompt.push_str("3. Be CONCISE - match detail level to scope of changes:\n");
     prompt.push_str("   - Single file/method: 2-4 bullet points max\n");
@@ -109,6 +398,23 @@ pub fn build_prompt(diff: &StagedDiff) -> String {
     prompt.push_str("(commit message goes here)\n");
     prompt.push_str("<<<COMMIT_MESSAGE_END>>>\n\n");
 
+    // House style from the repository's own history
+    if !examples.is_empty() {
+        prompt.push_str("=== RECENT COMMITS IN THIS REPOSITORY ===\n");
+        prompt.push_str("Follow the conventions these commits use (scopes, body style,\n");
+        prompt.push_str("footers) where they fit. Do not copy their content.\n\n");
+        for example in examples {
+            prompt.push_str(example.as_str());
+            prompt.push_str("\n---\n");
+        }
+        prompt.push('\n');
+    }
+
+    // Heuristic findings the agent should confirm
+    if config.breaking_changes != BreakingCheck::Off {
+        prompt.push_str(&breaking_section(&detect_breaking_changes(diff)));
+    }
+
     // Change summary section
     prompt.push_str("=== CHANGE SUMMARY ===\n");
     prompt.push_str(&parse_change_summary(&diff.stat, &diff.name_status));
@@ -129,27 +435,150 @@ pub fn build_prompt(diff: &StagedDiff) -> String {
     if diff.name_status.is_empty() {
         prompt.push_str("(no changes)\n");
     } else {
-        prompt.push_str(&diff.name_status);
+        prompt.push_str(&file_status(diff));
         prompt.push('\n');
     }
     prompt.push('\n');
 
-    // Full diff section (with truncation)
-    prompt.push_str("=== FULL DIFF ===\n");
-    if diff.diff.is_empty() {
-        prompt.push_str("(no changes)\n");
+    prompt
+}
+
+/// `name_status` with omitted files marked [`OMITTED_NOTE`]
+fn file_status(diff: &StagedDiff) -> String {
+    diff.name_status
+        .lines()
+        .map(|line| match name_status_path(line) {
+            Some(path) if diff.omitted.iter().any(|omitted| omitted == path) => {
+                format!("{} {}", line, OMITTED_NOTE)
+            }
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Ask the agent to mark likely breaking changes (empty if there are none)
+fn breaking_section(changes: &[BreakingChange]) -> String {
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut section = String::from("=== POSSIBLE BREAKING CHANGES ===\n");
+    section.push_str("These changes look like they break the public API or CLI:\n");
+    for change in changes {
+        section.push_str(&format!("- {}\n", change));
+    }
+    section.push_str("Mark the commit as breaking: add `!` before the colon in the header\n");
+    section.push_str("(feat(api)!: ...) and a footer paragraph starting with\n");
+    section.push_str("`BREAKING CHANGE: ` that tells users what changed and how to migrate.\n\n");
+    section
+}
+
+/// Build a follow-up prompt asking the agent to repair a rejected message
+///
+/// Repeats the original prompt (so the agent still has the diff) followed by
+/// the rejected output and the validation error.
+pub fn build_repair_prompt(prompt: &str, rejected: &str, reason: &str) -> String {
+    let mut repair = String::from(prompt);
+
+    repair.push_str("\n=== PREVIOUS ATTEMPT REJECTED ===\n");
+    repair.push_str("Your previous commit message was rejected by validation.\n\n");
+    repair.push_str("Rejected message:\n");
+    repair.push_str(if rejected.is_empty() {
+        "(empty)"
     } else {
-        let truncated = truncate_diff(&diff.diff);
-        prompt.push_str(&truncated);
-        prompt.push('\n');
+        rejected
+    });
+    repair.push_str("\n\nReason:\n");
+    repair.push_str(reason);
+    repair.push_str("\n\n");
+    repair.push_str("Fix the problem and return the corrected commit message between the\n");
+    repair.push_str("<<<COMMIT_MESSAGE_START>>> and <<<COMMIT_MESSAGE_END>>> markers.\n");
+
+    repair
+}
+
+/// Line separating messages when several candidates are requested
+pub const CANDIDATE_SEPARATOR: &str = "<<<COMMIT_MESSAGE_SEPARATOR>>>";
+
+/// Ask for `count` alternative messages instead of one
+///
+/// Appended to a prompt from [`build_prompt_with_config`]; the response is
+/// split with [`crate::agents::clean_ai_candidates`].
+pub fn build_candidates_prompt(prompt: &str, count: usize) -> String {
+    let mut candidates = String::from(prompt);
+
+    candidates.push_str("\n=== MULTIPLE CANDIDATES ===\n");
+    candidates.push_str(&format!(
+        "Instead of a single message, write {} distinct alternative commit messages\n",
+        count
+    ));
+    candidates.push_str("for these changes (vary the type, scope, wording or level of detail).\n");
+    candidates.push_str("Return all of them between one pair of markers, separated by a line\n");
+    candidates.push_str("containing only the separator:\n");
+    candidates.push_str("<<<COMMIT_MESSAGE_START>>>\n");
+    candidates.push_str("(first commit message)\n");
+    candidates.push_str(CANDIDATE_SEPARATOR);
+    candidates.push_str("\n(second commit message)\n");
+    candidates.push_str("<<<COMMIT_MESSAGE_END>>>\n");
+
+    candidates
+}
+
+/// Append user guidance (e.g. "mention the migration") to a prompt
+///
+/// Used when regenerating interactively; the guidance goes last so it takes
+/// priority over the generic requirements at the top.
+pub fn build_guidance_prompt(prompt: &str, guidance: &[String]) -> String {
+    let mut guided = String::from(prompt);
+
+    guided.push_str("\n=== ADDITIONAL GUIDANCE ===\n");
+    guided.push_str("The user reviewed an earlier message and asked for the following:\n");
+    for line in guidance {
+        guided.push_str("- ");
+        guided.push_str(line);
+        guided.push('\n');
     }
 
-    prompt
+    guided
+}
+
+/// Restrict the scope to the repository's workspace layout
+///
+/// `inferred` are the scopes the staged files belong to (see
+/// [`crate::scope::ScopeMap::infer`]), `allowed` every scope validation
+/// accepts.
+pub fn build_scope_prompt(prompt: &str, inferred: &[String], allowed: &[String]) -> String {
+    let mut scoped = String::from(prompt);
+
+    scoped.push_str("\n=== SCOPE ===\n");
+    scoped.push_str("Scopes in this repository follow its workspace layout. The only valid\n");
+    scoped.push_str(&format!("scopes are: {}\n", allowed.join(", ")));
+    match inferred {
+        [] => {
+            scoped.push_str("The staged files are outside all of them, so leave the scope out.\n")
+        }
+        [scope] => scoped.push_str(&format!(
+            "The staged files belong to `{}`; use it as the scope.\n",
+            scope
+        )),
+        scopes => {
+            scoped.push_str(&format!(
+                "The staged files belong to: {} (most changed first).\n",
+                scopes.join(", ")
+            ));
+            scoped.push_str("Use the scope the change is really about, or list several\n");
+            scoped.push_str("separated by commas without spaces, e.g. feat(a,b): ...\n");
+        }
+    }
+
+    scoped
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::CommitType;
 
     #[test]
     fn includes_conventional_commit_instructions() {
@@ -165,6 +594,22 @@ mod tests {
         assert!(prompt.contains("Quality over quantity"));
     }
 
+    #[test]
+    fn lists_configured_types_with_descriptions() {
+        let mut config = Config::default();
+        config.commit_types.retain(|t| t.name != "chore");
+        config
+            .commit_types
+            .push(CommitType::new("deps", "Dependency updates"));
+
+        let prompt = build_prompt_with_config(&StagedDiff::default(), &config);
+
+        assert!(prompt.contains("ci, revert, deps\n"));
+        assert!(prompt.contains("     feat: A new feature\n"));
+        assert!(prompt.contains("     deps: Dependency updates\n"));
+        assert!(!prompt.contains("chore"));
+    }
+
     #[test]
     fn includes_marker_tags() {
         let diff = StagedDiff::default();
@@ -199,6 +644,7 @@ mod tests {
             stat: "1 file changed, 10 insertions(+)".to_string(),
             name_status: String::new(),
             diff: String::new(),
+            omitted: Vec::new(),
         };
         let prompt = build_prompt(&diff);
 
@@ -212,6 +658,7 @@ mod tests {
             stat: String::new(),
             name_status: "A\tsrc/test.rs\nM\tsrc/lib.rs".to_string(),
             diff: String::new(),
+            omitted: Vec::new(),
         };
         let prompt = build_prompt(&diff);
 
@@ -220,12 +667,25 @@ mod tests {
         assert!(prompt.contains("M\tsrc/lib.rs"));
     }
 
+    #[test]
+    fn marks_omitted_files_in_name_status_section() {
+        let diff = StagedDiff {
+            name_status: "M\tCargo.lock\nM\tsrc/lib.rs".to_string(),
+            omitted: vec!["Cargo.lock".to_string()],
+            ..StagedDiff::default()
+        };
+        let prompt = build_prompt(&diff);
+
+        assert!(prompt.contains("M\tCargo.lock (content omitted)\nM\tsrc/lib.rs\n"));
+    }
+
     #[test]
     fn includes_full_diff_section() {
         let diff = StagedDiff {
             stat: String::new(),
             name_status: String::new(),
             diff: "@@ -1,3 +1,4 @@\n fn main() {\n+    println!(\"hello\");\n }".to_string(),
+            omitted: Vec::new(),
         };
         let prompt = build_prompt(&diff);
 
@@ -240,6 +700,7 @@ mod tests {
             stat: "1 file changed, 1 insertion(+)".to_string(),
             name_status: "M\tsrc/main.rs".to_string(),
             diff: "@@ -1 +1,2 @@\n fn main() {\n+    println!(\"test\");\n }".to_string(),
+            omitted: Vec::new(),
         };
         let prompt = build_prompt(&diff);
 
@@ -264,6 +725,7 @@ mod tests {
             stat: "test stat".to_string(),
             name_status: "test status".to_string(),
             diff: "test diff".to_string(),
+            omitted: Vec::new(),
         };
         let prompt = build_prompt(&diff);
 
@@ -294,6 +756,7 @@ mod tests {
             stat: "test".to_string(),
             name_status: "test".to_string(),
             diff: "test".to_string(),
+            omitted: Vec::new(),
         };
         let prompt = build_prompt(&diff);
 
@@ -302,10 +765,12 @@ mod tests {
     }
 
     // Truncation tests
+    const TRUNCATE_LIMIT: usize = 8000;
+
     #[test]
     fn truncate_diff_within_limit() {
         let short_diff = "a".repeat(7999);
-        let result = truncate_diff(&short_diff);
+        let result = truncate_diff(&short_diff, TRUNCATE_LIMIT);
         assert_eq!(result, short_diff);
         assert!(!result.contains("truncated"));
     }
@@ -313,7 +778,7 @@ mod tests {
     #[test]
     fn truncate_diff_at_exact_limit() {
         let exact_diff = "a".repeat(8000);
-        let result = truncate_diff(&exact_diff);
+        let result = truncate_diff(&exact_diff, TRUNCATE_LIMIT);
         assert_eq!(result, exact_diff);
         assert!(!result.contains("truncated"));
     }
@@ -321,19 +786,19 @@ mod tests {
     #[test]
     fn truncate_diff_over_limit() {
         let long_diff = "a".repeat(8001);
-        let result = truncate_diff(&long_diff);
+        let result = truncate_diff(&long_diff, TRUNCATE_LIMIT);
         // Result should be truncated original + message
         assert!(result.contains("... (diff truncated)"));
         assert!(result.starts_with("aaa"));
         // Verify the diff portion is truncated to 8000 or less
         let diff_portion = result.trim_end_matches("\n... (diff truncated)");
-        assert!(diff_portion.len() <= MAX_DIFF_LENGTH);
+        assert!(diff_portion.len() <= TRUNCATE_LIMIT);
     }
 
     #[test]
     fn truncate_diff_message_appended() {
         let long_diff = "x".repeat(10000);
-        let result = truncate_diff(&long_diff);
+        let result = truncate_diff(&long_diff, TRUNCATE_LIMIT);
         assert!(result.ends_with("... (diff truncated)"));
     }
 
@@ -344,7 +809,7 @@ mod tests {
         diff.push('🦀'); // 4-byte UTF-8 char
         diff.push_str(&"b".repeat(100));
 
-        let result = truncate_diff(&diff);
+        let result = truncate_diff(&diff, TRUNCATE_LIMIT);
         // Should not panic and should be valid UTF-8
         assert!(result.len() <= 8000 + "... (diff truncated)".len() + 10);
     }
@@ -450,6 +915,7 @@ mod tests {
             stat: "2 files changed, 10 insertions(+), 5 deletions(-)".to_string(),
             name_status: "M\tsrc/a.rs\nA\tsrc/b.rs".to_string(),
             diff: "test diff".to_string(),
+            omitted: Vec::new(),
         };
         let prompt = build_prompt(&diff);
 
@@ -465,6 +931,7 @@ mod tests {
             stat: "1 file changed, 5 insertions(+)".to_string(),
             name_status: "M\tsrc/test.rs".to_string(),
             diff: "diff".to_string(),
+            omitted: Vec::new(),
         };
         let prompt = build_prompt(&diff);
 
@@ -474,15 +941,241 @@ mod tests {
         assert!(summary_pos < stats_pos);
     }
 
+    #[test]
+    fn prompt_respects_configured_max_diff_tokens() {
+        let diff = StagedDiff {
+            stat: "1 file changed, 1 insertion(+)".to_string(),
+            name_status: "M\tsrc/test.rs".to_string(),
+            diff: "x".repeat(600),
+            omitted: Vec::new(),
+        };
+        let config = Config {
+            max_diff_tokens: Some(125),
+            ..Config::default()
+        };
+
+        assert!(!build_prompt(&diff).contains("... (diff truncated)"));
+        assert!(build_prompt_with_config(&diff, &config).contains("... (diff truncated)"));
+    }
+
     #[test]
     fn prompt_truncates_large_diff() {
         let diff = StagedDiff {
             stat: "1 file changed, 1 insertion(+)".to_string(),
             name_status: "M\tsrc/test.rs".to_string(),
-            diff: "x".repeat(10000),
+            diff: "x".repeat(100_000),
+            omitted: Vec::new(),
         };
         let prompt = build_prompt(&diff);
 
         assert!(prompt.contains("... (diff truncated)"));
     }
+
+    #[test]
+    fn prompt_budget_follows_agent() {
+        let diff = StagedDiff {
+            stat: String::new(),
+            name_status: String::new(),
+            diff: "x".repeat(20_000),
+            omitted: Vec::new(),
+        };
+        let config = Config::default();
+
+        assert!(
+            !build_prompt_for_agent(&diff, &config, &AgentName::Claude, &[]).contains("truncated")
+        );
+        assert!(
+            build_prompt_for_agent(&diff, &config, &AgentName::Custom("llm".into()), &[])
+                .contains("... (diff truncated)")
+        );
+    }
+
+    #[test]
+    fn prompt_includes_history_examples_before_changes() {
+        let examples = [
+            ConventionalCommit::validate("feat(api): add pagination\n\nRefs: ABC-12").unwrap(),
+            ConventionalCommit::validate("fix(api): reject empty cursor").unwrap(),
+        ];
+        let prompt = build_prompt_for_agent(
+            &StagedDiff::default(),
+            &Config::default(),
+            &AgentName::Claude,
+            &examples,
+        );
+
+        let examples_pos = prompt
+            .find("=== RECENT COMMITS IN THIS REPOSITORY ===")
+            .unwrap();
+        assert!(examples_pos < prompt.find("=== CHANGE SUMMARY ===").unwrap());
+        assert!(prompt.contains("feat(api): add pagination\n\nRefs: ABC-12\n---\n"));
+        assert!(prompt.contains("fix(api): reject empty cursor\n---\n"));
+        assert!(!build_prompt(&StagedDiff::default()).contains("RECENT COMMITS"));
+    }
+
+    // Budgeting tests
+
+    /// A hunk adding `lines` lines of 40 bytes each
+    fn hunk(start: usize, lines: usize) -> String {
+        let mut hunk = format!("@@ -{start},0 +{start},{lines} @@\n");
+        for i in 0..lines {
+            hunk.push_str(&format!("+{:<38}\n", format!("line {}", i)));
+        }
+        hunk
+    }
+
+    fn file(path: &str, hunks: &[String]) -> String {
+        format!(
+            "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n{}",
+            hunks.concat()
+        )
+    }
+
+    #[test]
+    fn budget_diff_keeps_diff_that_fits() {
+        let diff = file("src/a.rs", &[hunk(1, 3)]);
+        assert_eq!(budget_diff(&diff, 1000), diff);
+    }
+
+    #[test]
+    fn budget_diff_keeps_whole_hunks_and_summarizes_the_rest() {
+        let hunks: Vec<String> = (0..6).map(|i| hunk(i * 100 + 1, 10)).collect();
+        let diff = file("src/foo.rs", &hunks);
+
+        // Room for the header, two hunks (~100 tokens each) and the summary
+        let budgeted = budget_diff(&diff, 250);
+
+        assert!(budgeted.starts_with("diff --git a/src/foo.rs b/src/foo.rs\n"));
+        assert!(budgeted.contains(&hunks[0]));
+        assert!(budgeted.contains(&hunks[1]));
+        assert!(!budgeted.contains("@@ -201,0"));
+        assert!(budgeted.ends_with("... +40/-0 lines in src/foo.rs, 4 hunks omitted\n"));
+        assert!(estimate_tokens(&budgeted) <= 250);
+    }
+
+    #[test]
+    fn budget_diff_shares_budget_across_files() {
+        let big: Vec<String> = (0..20).map(|i| hunk(i * 100 + 1, 10)).collect();
+        let small = [hunk(1, 2)];
+        let diff = file("src/big.rs", &big) + &file("src/small.rs", &small);
+
+        let budgeted = budget_diff(&diff, 600);
+
+        // The big file comes first but doesn't starve the small one
+        assert!(budgeted.contains(&file("src/small.rs", &small)));
+        assert!(budgeted.contains(&big[0]));
+        assert!(budgeted.contains("lines in src/big.rs"));
+        assert!(!budgeted.contains("lines in src/small.rs"));
+        assert!(estimate_tokens(&budgeted) <= 600);
+    }
+
+    #[test]
+    fn budget_diff_cuts_hunks_larger_than_share() {
+        let big = hunk(1, 100);
+        let diff = file("src/a.rs", &[big.clone(), hunk(200, 2)]);
+
+        let budgeted = budget_diff(&diff, 200);
+
+        // The small hunk stays whole and the big one gets what is left
+        let start = budgeted.find("@@ -1,0").unwrap();
+        let end = budgeted.find("@@ -200,0 +200,2 @@").unwrap();
+        let shown = &budgeted[start..end];
+        assert!(big.starts_with(shown));
+        let lines = shown.lines().count() - 1;
+        assert!(lines > 0);
+        assert!(budgeted.ends_with(&format!(
+            "... +{}/-0 lines in src/a.rs, 1 hunk truncated\n",
+            100 - lines
+        )));
+        assert!(estimate_tokens(&budgeted) <= 200);
+    }
+
+    #[test]
+    fn budget_diff_cuts_a_single_hunk_file_over_budget() {
+        let diff = file("src/a.rs", &[hunk(1, 100)]);
+
+        let budgeted = budget_diff(&diff, 200);
+
+        assert!(budgeted.starts_with("diff --git a/src/a.rs b/src/a.rs\n"));
+        assert!(budgeted.contains("@@ -1,0 +1,100 @@\n+line 0 "));
+        assert!(budgeted.contains("+line 14 "));
+        assert!(!budgeted.contains("+line 15 "));
+        assert!(budgeted.ends_with("\n... +85/-0 lines in src/a.rs, 1 hunk truncated\n"));
+        assert!(estimate_tokens(&budgeted) <= 200);
+    }
+
+    #[test]
+    fn budget_diff_counts_removed_lines() {
+        let removal = "@@ -1,3 +1,1 @@\n-old one\n-old two\n+new\n context\n".repeat(40);
+        let diff = file("src/a.rs", &[removal]);
+
+        let budgeted = budget_diff(&diff, 30);
+
+        assert!(budgeted.contains("... +40/-80 lines in src/a.rs, 40 hunks omitted"));
+    }
+
+    #[test]
+    fn fair_shares_fill_small_needs_first() {
+        assert_eq!(fair_shares(&[1000, 10, 1000], 310), vec![150, 10, 150]);
+        assert_eq!(fair_shares(&[5, 10], 100), vec![5, 10]);
+        assert_eq!(fair_shares(&[], 100), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn repair_prompt_includes_original_prompt_and_rejection() {
+        let repair = build_repair_prompt("ORIGINAL PROMPT\n", "added stuff", "missing type");
+
+        assert!(repair.starts_with("ORIGINAL PROMPT\n"));
+        assert!(repair.contains("=== PREVIOUS ATTEMPT REJECTED ==="));
+        assert!(repair.contains("added stuff"));
+        assert!(repair.contains("missing type"));
+        assert!(repair.contains("<<<COMMIT_MESSAGE_START>>>"));
+    }
+
+    #[test]
+    fn repair_prompt_marks_empty_rejection() {
+        let repair = build_repair_prompt("PROMPT\n", "", "commit message is empty");
+        assert!(repair.contains("Rejected message:\n(empty)"));
+    }
+
+    #[test]
+    fn candidates_prompt_requests_count_with_separator() {
+        let prompt = build_candidates_prompt("PROMPT\n", 3);
+
+        assert!(prompt.starts_with("PROMPT\n"));
+        assert!(prompt.contains("write 3 distinct alternative commit messages"));
+        assert!(prompt.contains(CANDIDATE_SEPARATOR));
+        assert!(prompt.ends_with("<<<COMMIT_MESSAGE_END>>>\n"));
+    }
+
+    #[test]
+    fn guidance_prompt_appends_each_instruction() {
+        let guided = build_guidance_prompt(
+            "PROMPT\n",
+            &[
+                "mention the migration".to_string(),
+                "use scope db".to_string(),
+            ],
+        );
+
+        assert!(guided.starts_with("PROMPT\n"));
+        assert!(guided.contains("=== ADDITIONAL GUIDANCE ==="));
+        assert!(guided.ends_with("- mention the migration\n- use scope db\n"));
+    }
+
+    #[test]
+    fn scope_prompt_names_inferred_and_allowed_scopes() {
+        let allowed = ["cli".to_string(), "core".to_string()];
+
+        let single = build_scope_prompt("PROMPT\n", &allowed[1..], &allowed);
+        assert!(single.starts_with("PROMPT\n"));
+        assert!(single.contains("=== SCOPE ===\n"));
+        assert!(single.contains("scopes are: cli, core\n"));
+        assert!(single.contains("belong to `core`; use it as the scope"));
+
+        let several = build_scope_prompt("", &["core".to_string(), "cli".to_string()], &allowed);
+        assert!(several.contains("belong to: core, cli (most changed first)"));
+
+        let none = build_scope_prompt("", &[], &allowed);
+        assert!(none.contains("leave the scope out"));
+    }
 }
@@ -0,0 +1,234 @@
+use crate::error::AgentError;
+use crate::redact::RedactionReport;
+use crate::types::CommitValidationError;
+use std::fmt;
+use std::time::Duration;
+
+/// Default number of agent calls per generation (first try plus two more)
+pub(crate) const MAX_ATTEMPTS: u32 = 3;
+
+/// Default delay before the first retry of a failed agent call
+pub(crate) const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// One agent call during generation, as reported to progress callbacks
+#[derive(Debug)]
+pub struct Attempt<'a> {
+    /// 1-based attempt number
+    pub number: u32,
+    /// Configured attempt budget
+    pub max_attempts: u32,
+    /// What happened
+    pub outcome: AttemptOutcome<'a>,
+}
+
+/// Result of a single attempt
+#[derive(Debug)]
+pub enum AttemptOutcome<'a> {
+    /// Response passed validation
+    Accepted,
+    /// Response failed validation; the agent is asked to repair it if
+    /// attempts remain
+    Rejected {
+        response: &'a str,
+        error: &'a CommitValidationError,
+    },
+    /// Agent call failed; `retry_in` is set when it will be retried
+    Failed {
+        error: &'a AgentError,
+        retry_in: Option<Duration>,
+    },
+}
+
+impl Attempt<'_> {
+    /// Whether another attempt follows this one
+    pub fn will_retry(&self) -> bool {
+        match &self.outcome {
+            AttemptOutcome::Accepted => false,
+            AttemptOutcome::Rejected { .. } => self.number < self.max_attempts,
+            AttemptOutcome::Failed { retry_in, .. } => retry_in.is_some(),
+        }
+    }
+}
+
+impl fmt::Display for Attempt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "attempt {}/{}: ", self.number, self.max_attempts)?;
+        match &self.outcome {
+            AttemptOutcome::Accepted => write!(f, "accepted"),
+            AttemptOutcome::Rejected { error, .. } => {
+                // Validation errors add a caret diagram; keep the report on one line
+                let reason = error.to_string();
+                let reason = reason.lines().next().unwrap_or_default();
+                write!(f, "rejected ({})", reason)?;
+                if self.will_retry() {
+                    write!(f, ", asking agent to repair")?;
+                }
+                Ok(())
+            }
+            AttemptOutcome::Failed { error, retry_in } => {
+                write!(f, "failed ({})", error.to_string().replace('\n', " "))?;
+                if let Some(delay) = retry_in {
+                    write!(f, ", retrying in {}ms", delay.as_millis())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Progress event reported to callbacks during generation
+#[derive(Debug)]
+pub enum Progress<'a> {
+    /// Secrets were masked in the staged diff before anything was sent
+    Redacted(&'a RedactionReport),
+    /// Summarize strategy: `done` of `total` chunks have been summarized
+    Summarized { done: usize, total: usize },
+    /// An agent call for the commit message itself
+    Attempt(Attempt<'a>),
+    /// The agent's response so far, while it streams in (see
+    /// [`crate::agents::streamed_message`] for the part worth showing)
+    Output(&'a str),
+}
+
+impl fmt::Display for Progress<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Redacted(report) => write!(f, "masked {}", report.summary()),
+            Self::Summarized { done, total } => write!(f, "summarized {}/{} chunks", done, total),
+            Self::Attempt(attempt) => attempt.fmt(f),
+            Self::Output(text) => write!(f, "received {} bytes", text.len()),
+        }
+    }
+}
+
+/// Whether an agent error is worth retrying
+///
+/// Failed runs can be transient (rate limits, network hiccups). Timeouts are
+/// only retried with `retry_timeouts`: an agent that hangs usually hangs
+/// again, and each retry waits out the full timeout. A missing CLI or an
+/// unparseable response won't fix itself by calling again.
+pub(crate) fn is_retryable(error: &AgentError, retry_timeouts: bool) -> bool {
+    match error {
+        AgentError::ExecutionFailed { .. } => true,
+        AgentError::Timeout { .. } => retry_timeouts,
+        _ => false,
+    }
+}
+
+/// Exponential backoff: `base`, `2 * base`, `4 * base`, ...
+pub(crate) fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AgentName;
+
+    #[test]
+    fn retryable_errors() {
+        assert!(is_retryable(
+            &AgentError::ExecutionFailed {
+                agent: AgentName::Claude,
+                exit_code: None,
+                stderr: "rate limited".to_string(),
+            },
+            false
+        ));
+        let timeout = AgentError::Timeout {
+            agent: AgentName::Claude,
+            timeout_secs: 120,
+        };
+        assert!(!is_retryable(&timeout, false));
+        assert!(is_retryable(&timeout, true));
+        assert!(!is_retryable(
+            &AgentError::NotFound {
+                agent: AgentName::Claude,
+            },
+            true
+        ));
+        assert!(!is_retryable(
+            &AgentError::InvalidResponse {
+                reason: "bad".to_string(),
+            },
+            true
+        ));
+    }
+
+    #[test]
+    fn backoff_doubles_each_attempt() {
+        let base = Duration::from_millis(500);
+        assert_eq!(backoff_delay(base, 1), Duration::from_millis(500));
+        assert_eq!(backoff_delay(base, 2), Duration::from_millis(1000));
+        assert_eq!(backoff_delay(base, 3), Duration::from_millis(2000));
+    }
+
+    #[test]
+    fn backoff_saturates_instead_of_overflowing() {
+        assert_eq!(
+            backoff_delay(Duration::from_secs(1), 200),
+            Duration::from_secs(1).saturating_mul(u32::MAX)
+        );
+    }
+
+    #[test]
+    fn display_rejected_attempt_with_retry() {
+        let error = CommitValidationError::Empty;
+        let attempt = Attempt {
+            number: 1,
+            max_attempts: 3,
+            outcome: AttemptOutcome::Rejected {
+                response: "",
+                error: &error,
+            },
+        };
+        assert!(attempt.will_retry());
+        assert_eq!(
+            attempt.to_string(),
+            "attempt 1/3: rejected (commit message is empty), asking agent to repair"
+        );
+    }
+
+    #[test]
+    fn display_final_rejected_attempt() {
+        let error = CommitValidationError::Empty;
+        let attempt = Attempt {
+            number: 3,
+            max_attempts: 3,
+            outcome: AttemptOutcome::Rejected {
+                response: "",
+                error: &error,
+            },
+        };
+        assert!(!attempt.will_retry());
+        assert_eq!(
+            attempt.to_string(),
+            "attempt 3/3: rejected (commit message is empty)"
+        );
+    }
+
+    #[test]
+    fn display_summarized_progress() {
+        let progress = Progress::Summarized { done: 2, total: 5 };
+        assert_eq!(progress.to_string(), "summarized 2/5 chunks");
+    }
+
+    #[test]
+    fn display_failed_attempt_with_backoff() {
+        let error = AgentError::Timeout {
+            agent: AgentName::Codex,
+            timeout_secs: 5,
+        };
+        let attempt = Attempt {
+            number: 2,
+            max_attempts: 3,
+            outcome: AttemptOutcome::Failed {
+                error: &error,
+                retry_in: Some(Duration::from_millis(1000)),
+            },
+        };
+        let display = attempt.to_string();
+        assert!(display.starts_with("attempt 2/3: failed ("));
+        assert!(display.ends_with(", retrying in 1000ms"));
+    }
+}
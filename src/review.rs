@@ -0,0 +1,222 @@
+use crate::agents::parallel::ResponseJudge;
+use crate::agents::{AgentExecutor, OutputSink};
+use crate::error::AgentError;
+use crate::lint::{DEFAULT_COMMENT_CHAR, strip_git_comments};
+use crate::prompt::build_guidance_prompt;
+use crate::types::AgentName;
+use std::io;
+use std::process::Command;
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Help text written below the message in the editor (stripped on save)
+const EDIT_INSTRUCTIONS: &str = "\n\
+# Edit the commit message above. Lines starting with '#' are ignored.
+# Saving an empty message keeps the previous one.
+";
+
+/// Choice offered after showing a generated message in `--interactive` mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewAction {
+    /// Use the message as-is
+    Accept,
+    /// Open the message in `$EDITOR`
+    Edit,
+    /// Ask the agent again, optionally with extra guidance
+    Regenerate,
+    /// Regenerate with a different agent
+    SwitchAgent,
+    /// Exit without committing
+    Abort,
+}
+
+impl ReviewAction {
+    /// Menu shown under the message
+    pub const MENU: &'static str = "[a]ccept  [e]dit  [r]egenerate  [s]witch agent  [q]uit";
+
+    /// Map a key press to an action (`None` for unbound keys)
+    pub fn from_key(key: char) -> Option<Self> {
+        match key.to_ascii_lowercase() {
+            'a' => Some(Self::Accept),
+            'e' => Some(Self::Edit),
+            'r' => Some(Self::Regenerate),
+            's' => Some(Self::SwitchAgent),
+            'q' => Some(Self::Abort),
+            _ => None,
+        }
+    }
+}
+
+/// Agent wrapper that appends review guidance to every prompt
+///
+/// Wrapping the agent (rather than the prompt) means repair prompts built by
+/// the generation loop carry the guidance too.
+pub struct GuidedAgent<'a, A> {
+    inner: &'a A,
+    guidance: &'a [String],
+}
+
+impl<'a, A> GuidedAgent<'a, A> {
+    /// Wrap `inner`, appending `guidance` (if any) to each prompt
+    pub fn new(inner: &'a A, guidance: &'a [String]) -> Self {
+        Self { inner, guidance }
+    }
+}
+
+impl<A: AgentExecutor + Sync> AgentExecutor for GuidedAgent<'_, A> {
+    async fn execute(&self, prompt: &str) -> Result<String, AgentError> {
+        self.execute_streaming(prompt, &mut |_| {}).await
+    }
+
+    async fn execute_streaming(
+        &self,
+        prompt: &str,
+        on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
+        if self.guidance.is_empty() {
+            return self.inner.execute_streaming(prompt, on_output).await;
+        }
+        let prompt = build_guidance_prompt(prompt, self.guidance);
+        self.inner.execute_streaming(&prompt, on_output).await
+    }
+
+    async fn execute_message(
+        &self,
+        prompt: &str,
+        judge: &ResponseJudge,
+        on_output: OutputSink<'_>,
+    ) -> Result<String, AgentError> {
+        if self.guidance.is_empty() {
+            return self.inner.execute_message(prompt, judge, on_output).await;
+        }
+        let prompt = build_guidance_prompt(prompt, self.guidance);
+        self.inner.execute_message(&prompt, judge, on_output).await
+    }
+
+    fn name(&self) -> AgentName {
+        self.inner.name()
+    }
+}
+
+/// Editor command from `$VISUAL`, then `$EDITOR`, then `vi`
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+/// Open `message` in `editor` and return the edited text
+///
+/// The editor runs through `sh -c`, so values like `code --wait` work. The
+/// file is named `COMMIT_EDITMSG` so editors pick git commit highlighting.
+/// Comment lines are stripped from the result; returns `None` if nothing is
+/// left.
+pub fn edit_message(message: &str, editor: &str) -> io::Result<Option<String>> {
+    let dir = tempfile::Builder::new()
+        .prefix("commitment-edit-")
+        .tempdir()?;
+    let path = dir.path().join("COMMIT_EDITMSG");
+    std::fs::write(&path, format!("{}\n{}", message, EDIT_INSTRUCTIONS))?;
+
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(&path)
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "editor `{}` exited with {}",
+            editor, status
+        )));
+    }
+
+    let edited = strip_git_comments(&std::fs::read_to_string(&path)?, DEFAULT_COMMENT_CHAR);
+    let edited = edited.trim();
+    Ok((!edited.is_empty()).then(|| edited.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Agent that records prompts and echoes a fixed response
+    struct RecordingAgent {
+        prompts: Mutex<Vec<String>>,
+    }
+
+    impl AgentExecutor for RecordingAgent {
+        async fn execute(&self, prompt: &str) -> Result<String, AgentError> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok("feat: ok".to_string())
+        }
+
+        fn name(&self) -> AgentName {
+            AgentName::Gemini
+        }
+    }
+
+    fn recording_agent() -> RecordingAgent {
+        RecordingAgent {
+            prompts: Mutex::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn maps_keys_to_actions() {
+        assert_eq!(ReviewAction::from_key('a'), Some(ReviewAction::Accept));
+        assert_eq!(ReviewAction::from_key('E'), Some(ReviewAction::Edit));
+        assert_eq!(ReviewAction::from_key('r'), Some(ReviewAction::Regenerate));
+        assert_eq!(ReviewAction::from_key('s'), Some(ReviewAction::SwitchAgent));
+        assert_eq!(ReviewAction::from_key('q'), Some(ReviewAction::Abort));
+        assert_eq!(ReviewAction::from_key('x'), None);
+        assert_eq!(ReviewAction::from_key('\n'), None);
+    }
+
+    #[tokio::test]
+    async fn guided_agent_appends_guidance() {
+        let inner = recording_agent();
+        let guidance = vec!["mention the migration".to_string()];
+        let agent = GuidedAgent::new(&inner, &guidance);
+
+        agent.execute("PROMPT").await.unwrap();
+
+        let prompts = inner.prompts.lock().unwrap();
+        assert!(prompts[0].starts_with("PROMPT"));
+        assert!(prompts[0].contains("- mention the migration"));
+        assert_eq!(agent.name(), AgentName::Gemini);
+    }
+
+    #[tokio::test]
+    async fn guided_agent_without_guidance_passes_prompt_through() {
+        let inner = recording_agent();
+        let agent = GuidedAgent::new(&inner, &[]);
+
+        agent.execute("PROMPT").await.unwrap();
+
+        assert_eq!(*inner.prompts.lock().unwrap(), ["PROMPT"]);
+    }
+
+    #[test]
+    fn edit_message_returns_edited_text_without_comments() {
+        let edited = edit_message("feat: add thing", "sed -i -e 's/add/remove/'").unwrap();
+        assert_eq!(edited.as_deref(), Some("feat: remove thing"));
+    }
+
+    #[test]
+    fn edit_message_returns_none_when_emptied() {
+        assert_eq!(
+            edit_message("feat: add thing", "cp /dev/null").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn edit_message_reports_editor_failure() {
+        assert!(edit_message("feat: add thing", "false").is_err());
+    }
+}
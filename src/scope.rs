@@ -0,0 +1,537 @@
+use crate::config::Config;
+use crate::diff::name_status_path;
+use crate::git::GitProvider;
+use globset::Glob;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Directories never searched when expanding workspace member globs
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target"];
+
+/// Repo-relative directories mapped to commit scopes
+///
+/// Built from workspace manifests (Cargo `[workspace] members`, `package.json`
+/// `workspaces`, `pnpm-workspace.yaml`) and the explicit `[scopes]` config
+/// table, plus `extra_scopes` that aren't tied to a directory. The longest
+/// matching directory wins, so a nested package gets its own scope. An empty
+/// map means the repository has no scopes to enforce.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScopeMap {
+    /// `(directory, scope)`, longest directory first
+    entries: Vec<(String, String)>,
+    /// Scopes allowed without a directory (e.g. `ci`, `deps`)
+    extra: Vec<String>,
+}
+
+impl ScopeMap {
+    /// Build a map from `(directory, scope)` pairs
+    ///
+    /// A later pair for the same directory replaces an earlier one. The
+    /// repository root itself can't be a scope (it would match every file).
+    pub fn new(entries: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut by_dir = BTreeMap::new();
+        for (dir, scope) in entries {
+            let dir = dir
+                .trim_start_matches("./")
+                .trim_end_matches('/')
+                .to_string();
+            if !dir.is_empty() && dir != "." {
+                by_dir.insert(dir, scope);
+            }
+        }
+
+        let mut entries: Vec<(String, String)> = by_dir.into_iter().collect();
+        entries.sort_by_key(|(dir, _)| std::cmp::Reverse(dir.len()));
+        Self {
+            entries,
+            extra: Vec::new(),
+        }
+    }
+
+    /// Also allow `scopes`, which don't map to a directory
+    pub fn with_extra(mut self, scopes: &[String]) -> Self {
+        self.extra.extend(scopes.iter().cloned());
+        self
+    }
+
+    /// Scopes for the repository at `root`
+    ///
+    /// Workspace members come first (unless `config.infer_scopes` is off),
+    /// then `config.scopes`, which overrides them, plus `config.extra_scopes`.
+    /// Missing or malformed manifests are skipped.
+    pub fn load(root: &Path, config: &Config) -> Self {
+        let mut entries = Vec::new();
+        if config.infer_scopes {
+            entries.extend(cargo_members(root));
+            entries.extend(npm_members(root));
+            entries.extend(pnpm_members(root));
+        }
+        entries.extend(config.scopes.clone());
+        Self::new(entries).with_extra(&config.extra_scopes)
+    }
+
+    /// Scopes for the repository `git` works in
+    ///
+    /// Only the explicit `config.scopes` and `config.extra_scopes` apply if
+    /// the provider has no working tree.
+    pub fn for_repo(git: &impl GitProvider, config: &Config) -> Self {
+        match git.workspace_root() {
+            Some(root) => Self::load(&root, config),
+            None => Self::new(config.scopes.clone()).with_extra(&config.extra_scopes),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.extra.is_empty()
+    }
+
+    /// Scope of a repo-relative path, if it's inside a mapped directory
+    pub fn scope_for(&self, path: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(dir, _)| {
+                path.strip_prefix(dir.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .map(|(_, scope)| scope.as_str())
+    }
+
+    /// Every scope in the map and the extra scopes, sorted and without duplicates
+    pub fn allowed(&self) -> Vec<String> {
+        let mut scopes: Vec<String> = self.entries.iter().map(|(_, s)| s.clone()).collect();
+        scopes.extend(self.extra.iter().cloned());
+        scopes.sort();
+        scopes.dedup();
+        scopes
+    }
+
+    /// Scopes touched by a `git diff --name-status` listing, most files first
+    ///
+    /// Files outside every mapped directory don't count towards any scope.
+    pub fn infer(&self, name_status: &str) -> Vec<String> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for path in name_status.lines().filter_map(name_status_path) {
+            if let Some(scope) = self.scope_for(path) {
+                *counts.entry(scope).or_default() += 1;
+            }
+        }
+
+        let mut touched: Vec<(&str, usize)> = counts.into_iter().collect();
+        touched.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        touched
+            .into_iter()
+            .map(|(scope, _)| scope.to_string())
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct CargoManifest {
+    workspace: Option<CargoWorkspace>,
+    package: Option<CargoPackage>,
+}
+
+#[derive(Deserialize)]
+struct CargoWorkspace {
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct PackageJson {
+    name: Option<String>,
+    workspaces: Option<NpmWorkspaces>,
+}
+
+/// `"workspaces": [...]` or the Yarn form `"workspaces": { "packages": [...] }`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NpmWorkspaces {
+    Patterns(Vec<String>),
+    Config {
+        #[serde(default)]
+        packages: Vec<String>,
+    },
+}
+
+#[derive(Deserialize)]
+struct PnpmWorkspace {
+    #[serde(default)]
+    packages: Vec<String>,
+}
+
+/// Members of a Cargo workspace, scoped by crate name
+fn cargo_members(root: &Path) -> Vec<(String, String)> {
+    let Some(workspace) = read_manifest(&root.join("Cargo.toml"), toml_str::<CargoManifest>)
+        .and_then(|m| m.workspace)
+    else {
+        return Vec::new();
+    };
+
+    members(
+        root,
+        &workspace.members,
+        &workspace.exclude,
+        "Cargo.toml",
+        |dir| {
+            read_manifest(&dir.join("Cargo.toml"), toml_str::<CargoManifest>)
+                .and_then(|manifest| manifest.package)
+                .map(|package| package.name)
+        },
+    )
+}
+
+/// Members of an npm or Yarn workspace, scoped by package name
+fn npm_members(root: &Path) -> Vec<(String, String)> {
+    let patterns = match read_manifest(&root.join("package.json"), json_str::<PackageJson>)
+        .and_then(|package| package.workspaces)
+    {
+        Some(NpmWorkspaces::Patterns(patterns)) => patterns,
+        Some(NpmWorkspaces::Config { packages }) => packages,
+        None => return Vec::new(),
+    };
+    workspace_members(root, &patterns)
+}
+
+/// Members of a pnpm workspace, scoped by package name
+fn pnpm_members(root: &Path) -> Vec<(String, String)> {
+    match read_manifest(&root.join("pnpm-workspace.yaml"), yaml_str::<PnpmWorkspace>) {
+        Some(workspace) => workspace_members(root, &workspace.packages),
+        None => Vec::new(),
+    }
+}
+
+/// Expand JavaScript workspace patterns, where `!pattern` excludes
+fn workspace_members(root: &Path, patterns: &[String]) -> Vec<(String, String)> {
+    let (exclude, include): (Vec<String>, Vec<String>) = patterns
+        .iter()
+        .cloned()
+        .partition(|pattern| pattern.starts_with('!'));
+    let exclude: Vec<String> = exclude.iter().map(|p| p[1..].to_string()).collect();
+
+    members(root, &include, &exclude, "package.json", |dir| {
+        read_manifest(&dir.join("package.json"), json_str::<PackageJson>)
+            .and_then(|package| package.name)
+    })
+}
+
+/// Directories with a `manifest` file matching `include` but not `exclude`,
+/// with their scopes
+///
+/// The scope is the package name from `name` (without an npm `@org/`
+/// prefix), falling back to the directory name.
+fn members(
+    root: &Path,
+    include: &[String],
+    exclude: &[String],
+    manifest: &str,
+    name: impl Fn(&Path) -> Option<String>,
+) -> Vec<(String, String)> {
+    let excluded: Vec<String> = exclude.iter().flat_map(|p| expand(root, p)).collect();
+
+    include
+        .iter()
+        .flat_map(|pattern| expand(root, pattern))
+        .filter(|dir| !excluded.contains(dir) && root.join(dir).join(manifest).is_file())
+        .map(|dir| {
+            let scope = name(&root.join(&dir))
+                .map(|name| name.rsplit('/').next().unwrap_or_default().to_string())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| dir.rsplit('/').next().unwrap_or_default().to_string());
+            (dir, scope)
+        })
+        .collect()
+}
+
+/// Existing directories matching a workspace glob (`crates/*`, `packages/**`)
+///
+/// Returns repo-relative paths. Each `/`-separated component is matched
+/// against directory names; `**` matches any number of levels.
+fn expand(root: &Path, pattern: &str) -> Vec<String> {
+    let mut found = vec![String::new()];
+
+    for component in pattern.trim_start_matches("./").split('/') {
+        if component.is_empty() || component == "." {
+            continue;
+        }
+        found = found
+            .into_iter()
+            .flat_map(|dir| {
+                if component == "**" {
+                    descendants(root, dir)
+                } else if component.contains(['*', '?', '[', '{']) {
+                    let Ok(glob) = Glob::new(component) else {
+                        return Vec::new();
+                    };
+                    let matcher = glob.compile_matcher();
+                    subdirs(root, &dir)
+                        .into_iter()
+                        .filter(|sub| matcher.is_match(sub.rsplit('/').next().unwrap_or(sub)))
+                        .collect()
+                } else {
+                    let path = join(&dir, component);
+                    if root.join(&path).is_dir() {
+                        vec![path]
+                    } else {
+                        Vec::new()
+                    }
+                }
+            })
+            .collect();
+    }
+
+    found.retain(|dir| !dir.is_empty());
+    found
+}
+
+/// `dir` and every directory below it
+fn descendants(root: &Path, dir: String) -> Vec<String> {
+    let mut all = Vec::new();
+    let mut pending = vec![dir];
+    while let Some(dir) = pending.pop() {
+        pending.extend(subdirs(root, &dir));
+        all.push(dir);
+    }
+    all.sort();
+    all
+}
+
+/// Immediate subdirectories of `dir`, skipping hidden and build directories
+fn subdirs(root: &Path, dir: &str) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(root.join(dir)) else {
+        return Vec::new();
+    };
+
+    let mut dirs: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()))
+        .map(|name| join(dir, &name))
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// Read and parse a manifest, treating any failure as "no manifest"
+fn read_manifest<T>(path: &Path, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| parse(&content))
+}
+
+fn toml_str<T: DeserializeOwned>(content: &str) -> Option<T> {
+    toml::from_str(content).ok()
+}
+
+fn json_str<T: DeserializeOwned>(content: &str) -> Option<T> {
+    serde_json::from_str(content).ok()
+}
+
+fn yaml_str<T: DeserializeOwned>(content: &str) -> Option<T> {
+    serde_yaml::from_str(content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn map(entries: &[(&str, &str)]) -> ScopeMap {
+        ScopeMap::new(
+            entries
+                .iter()
+                .map(|(dir, scope)| (dir.to_string(), scope.to_string())),
+        )
+    }
+
+    fn scopes(root: &Path) -> Vec<(String, String)> {
+        ScopeMap::load(root, &Config::default()).entries
+    }
+
+    #[test]
+    fn longest_directory_wins() {
+        let scopes = map(&[("packages", "pkgs"), ("packages/ui/", "ui"), (".", "root")]);
+
+        assert_eq!(scopes.scope_for("packages/ui/src/a.ts"), Some("ui"));
+        assert_eq!(scopes.scope_for("packages/api/x.ts"), Some("pkgs"));
+        assert_eq!(scopes.scope_for("packages-old/x.ts"), None);
+        assert_eq!(scopes.scope_for("README.md"), None);
+        assert_eq!(scopes.allowed(), ["pkgs", "ui"]);
+    }
+
+    #[test]
+    fn infers_touched_scopes_by_file_count() {
+        let scopes = map(&[("crates/core", "core"), ("crates/cli", "cli")]);
+        let name_status = "M\tcrates/cli/src/main.rs\nM\tcrates/core/src/a.rs\nA\tcrates/core/src/b.rs\nM\tREADME.md\nR100\told.rs\tcrates/core/c.rs";
+
+        assert_eq!(scopes.infer(name_status), ["core", "cli"]);
+        assert!(scopes.infer("M\tREADME.md").is_empty());
+    }
+
+    #[test]
+    fn reads_cargo_workspace_members() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\", \"tools/xtask\"]\nexclude = [\"crates/old\"]\n",
+        );
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"app-core\"\nversion.workspace = true\n",
+        );
+        write(root, "crates/cli/Cargo.toml", "not toml [");
+        write(root, "crates/old/Cargo.toml", "[package]\nname = \"old\"\n");
+        write(
+            root,
+            "tools/xtask/Cargo.toml",
+            "[package]\nname = \"xtask\"\n",
+        );
+        write(
+            root,
+            "crates/target/Cargo.toml",
+            "[package]\nname = \"built\"\n",
+        );
+        fs::create_dir_all(root.join("crates/empty")).unwrap();
+
+        assert_eq!(
+            scopes(root),
+            [
+                ("crates/core".to_string(), "app-core".to_string()),
+                ("tools/xtask".to_string(), "xtask".to_string()),
+                ("crates/cli".to_string(), "cli".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_npm_and_pnpm_workspaces() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "package.json",
+            r#"{"name": "root", "workspaces": {"packages": ["apps/*"]}}"#,
+        );
+        write(root, "apps/web/package.json", r#"{"name": "@acme/web"}"#);
+        write(
+            root,
+            "pnpm-workspace.yaml",
+            "packages:\n  - 'libs/**'\n  - '!libs/internal'\n",
+        );
+        write(root, "libs/ui/package.json", r#"{"name": "ui-kit"}"#);
+        write(
+            root,
+            "libs/internal/package.json",
+            r#"{"name": "internal"}"#,
+        );
+        write(
+            root,
+            "libs/ui/node_modules/dep/package.json",
+            r#"{"name": "dep"}"#,
+        );
+
+        let scopes = ScopeMap::load(root, &Config::default());
+
+        assert_eq!(scopes.scope_for("apps/web/index.ts"), Some("web"));
+        assert_eq!(scopes.scope_for("libs/ui/button.tsx"), Some("ui-kit"));
+        assert_eq!(scopes.scope_for("libs/internal/x.ts"), None);
+        assert_eq!(scopes.scope_for("libs/README.md"), None);
+        assert_eq!(scopes.allowed(), ["ui-kit", "web"]);
+    }
+
+    #[test]
+    fn explicit_scopes_override_and_extend_manifests() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        );
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"app-core\"\n",
+        );
+        let config = Config {
+            scopes: BTreeMap::from([
+                ("crates/core".to_string(), "core".to_string()),
+                ("docs/".to_string(), "docs".to_string()),
+            ]),
+            ..Config::default()
+        };
+
+        assert_eq!(ScopeMap::load(root, &config).allowed(), ["core", "docs"]);
+
+        let config = Config {
+            infer_scopes: false,
+            ..config
+        };
+        let scopes = ScopeMap::load(root, &config);
+        assert_eq!(scopes.allowed(), ["core", "docs"]);
+        assert!(
+            ScopeMap::load(
+                root,
+                &Config {
+                    infer_scopes: false,
+                    ..Config::default()
+                }
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn extra_scopes_are_allowed_alongside_directories() {
+        let dir = TempDir::new().unwrap();
+        let config = Config {
+            scopes: BTreeMap::from([("web".to_string(), "web".to_string())]),
+            extra_scopes: vec!["deps".to_string(), "ci".to_string()],
+            ..Config::default()
+        };
+
+        let scopes = ScopeMap::load(dir.path(), &config);
+        assert_eq!(scopes.allowed(), ["ci", "deps", "web"]);
+        // Extra scopes never match a file
+        assert_eq!(scopes.infer("M\tci/build.sh\nM\tweb/app.ts"), ["web"]);
+    }
+
+    #[test]
+    fn repository_without_workspace_has_no_scopes() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "Cargo.toml", "[package]\nname = \"single\"\n");
+
+        let scopes = ScopeMap::load(dir.path(), &Config::default());
+
+        assert!(scopes.is_empty());
+        assert!(scopes.allowed().is_empty());
+    }
+}
@@ -0,0 +1,330 @@
+use crate::error::ConfigError;
+use crate::types::{AgentName, is_footer_line};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Signature used unless `signature_template` is configured
+///
+/// Renders to the same line as [`AgentName::commit_signature`].
+pub const DEFAULT_SIGNATURE_TEMPLATE: &str = "🤖 Generated with {agent} via commitment";
+
+/// Default signature for agents with a configured model
+pub const DEFAULT_MODEL_SIGNATURE_TEMPLATE: &str =
+    "🤖 Generated with {agent} ({model}) via commitment";
+
+/// Variables available in a signature template
+const VARIABLES: &[&str] = &["agent", "model", "version", "date"];
+
+/// Values substituted into a signature template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureVars {
+    /// Agent display name (`Claude`, `my-llm`)
+    pub agent: String,
+    /// Model the agent was configured with (empty if unknown)
+    pub model: String,
+    /// commitment version
+    pub version: String,
+    /// Today's date in UTC (`2024-05-01`)
+    pub date: String,
+}
+
+impl SignatureVars {
+    /// Variables for a message generated now by `agent`
+    pub fn new(agent: &AgentName, model: Option<&str>) -> Self {
+        Self {
+            agent: agent.display_name().to_string(),
+            model: model.unwrap_or_default().to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            date: today(),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        match name {
+            "agent" => Some(&self.agent),
+            "model" => Some(&self.model),
+            "version" => Some(&self.version),
+            "date" => Some(&self.date),
+            _ => None,
+        }
+    }
+}
+
+/// Check that a template only uses known `{variables}`
+pub(crate) fn check_template(template: &str) -> Result<(), ConfigError> {
+    for name in placeholders(template) {
+        if !VARIABLES.contains(&name) {
+            return Err(ConfigError::InvalidValue {
+                key: "signature_template".to_string(),
+                value: template.to_string(),
+                reason: format!(
+                    "unknown variable `{{{}}}` (expected one of: {})",
+                    name,
+                    VARIABLES.join(", ")
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Names of the `{name}` placeholders in `template`, in order
+fn placeholders(template: &str) -> impl Iterator<Item = &str> {
+    template.split('{').skip(1).filter_map(|rest| {
+        let (name, _) = rest.split_once('}')?;
+        let is_name = !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c == '_');
+        is_name.then_some(name)
+    })
+}
+
+/// Fill in a signature template
+///
+/// Unknown placeholders (rejected when the config is loaded) are left as
+/// they are. Trailing whitespace is trimmed from each line, so an empty
+/// `{model}` at the end of a line doesn't leave a dangling space.
+///
+/// # Examples
+/// ```
+/// # use commitment_rs::signature::{SignatureVars, render_signature};
+/// let vars = SignatureVars {
+///     agent: "Claude".to_string(),
+///     model: "sonnet".to_string(),
+///     version: "0.1.0".to_string(),
+///     date: "2024-05-01".to_string(),
+/// };
+/// assert_eq!(
+///     render_signature("Assisted-by: {agent} {model}", &vars),
+///     "Assisted-by: Claude sonnet"
+/// );
+/// ```
+pub fn render_signature(template: &str, vars: &SignatureVars) -> String {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after
+            .split_once('}')
+            .and_then(|(name, tail)| Some((vars.get(name)?, tail)))
+        {
+            Some((value, tail)) => {
+                rendered.push_str(value);
+                rest = tail;
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    rendered
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Split a message into its content and final trailer block, if any
+///
+/// Like `git interpret-trailers`, the trailer block is the last paragraph
+/// (never the header) when it starts with a `Token: value` line.
+fn split_trailer_block(message: &str) -> (&str, Option<&str>) {
+    match message.rsplit_once("\n\n") {
+        Some((content, last)) if is_footer_line(last.lines().next().unwrap_or_default()) => {
+            (content, Some(last))
+        }
+        _ => (message, None),
+    }
+}
+
+/// Add trailers to a message's trailer block, or start one
+///
+/// Trailers the message already has (same line) are not repeated.
+pub fn append_trailers<S: AsRef<str>>(message: &str, trailers: &[S]) -> String {
+    let new: Vec<&str> = trailers
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|trailer| !message.lines().any(|line| line.trim() == *trailer))
+        .collect();
+    if new.is_empty() {
+        return message.to_string();
+    }
+
+    let separator = if split_trailer_block(message).1.is_some() {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    format!("{}{}{}", message, separator, new.join("\n"))
+}
+
+/// Append a rendered signature to a message
+///
+/// Signature lines that are git trailers (`Co-authored-by: ...`) join the
+/// message's trailer block. Any other text goes in its own paragraph before
+/// that block, so existing footers stay last and still parse as footers.
+pub fn append_signature(message: &str, signature: &str) -> String {
+    let signature = signature.trim();
+    if signature.is_empty() {
+        return message.to_string();
+    }
+
+    // A signature that is all trailers has no header to skip
+    let (text, trailers) = if is_footer_line(signature.lines().next().unwrap_or_default()) {
+        ("", Some(signature))
+    } else {
+        split_trailer_block(signature)
+    };
+
+    let (content, block) = split_trailer_block(message);
+    let mut signed = content.to_string();
+    if !text.is_empty() {
+        signed.push_str("\n\n");
+        signed.push_str(text);
+    }
+    if let Some(block) = block {
+        signed.push_str("\n\n");
+        signed.push_str(block);
+    }
+    match trailers {
+        Some(trailers) => append_trailers(&signed, &trailers.lines().collect::<Vec<_>>()),
+        None => signed,
+    }
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Gregorian date for a number of days since 1970-01-01
+///
+/// Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ConventionalCommit;
+
+    fn vars() -> SignatureVars {
+        SignatureVars {
+            agent: "Claude".to_string(),
+            model: String::new(),
+            version: "0.1.0".to_string(),
+            date: "2024-05-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn default_template_matches_agent_signature() {
+        let vars = SignatureVars::new(&AgentName::Gemini, None);
+
+        assert_eq!(
+            render_signature(DEFAULT_SIGNATURE_TEMPLATE, &vars),
+            AgentName::Gemini.commit_signature()
+        );
+    }
+
+    #[test]
+    fn renders_variables_and_trims_empty_ones() {
+        assert_eq!(
+            render_signature(
+                "Assisted-by: {agent} {model}\nGenerated-on: {date} (v{version})",
+                &vars()
+            ),
+            "Assisted-by: Claude\nGenerated-on: 2024-05-01 (v0.1.0)"
+        );
+        // Braces that aren't variables are kept
+        assert_eq!(render_signature("{agent} {x} {", &vars()), "Claude {x} {");
+    }
+
+    #[test]
+    fn rejects_unknown_variables() {
+        assert!(check_template("{agent} on {date}").is_ok());
+        assert!(check_template("fn() {}").is_ok());
+        assert!(matches!(
+            check_template("by {author}"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn plain_signature_goes_before_the_footer_block() {
+        assert_eq!(
+            append_signature("fix: x\n\n- Body", "🤖 Generated with Claude"),
+            "fix: x\n\n- Body\n\n🤖 Generated with Claude"
+        );
+
+        let signed = append_signature(
+            "feat: x\n\nBREAKING CHANGE: new api",
+            "🤖 Generated with Claude",
+        );
+        assert_eq!(
+            signed,
+            "feat: x\n\n🤖 Generated with Claude\n\nBREAKING CHANGE: new api"
+        );
+        let commit = ConventionalCommit::validate(&signed).unwrap();
+        assert_eq!(commit.footers().len(), 1);
+    }
+
+    #[test]
+    fn trailer_signature_joins_the_trailer_block() {
+        let trailer = "Co-authored-by: Claude <noreply@anthropic.com>";
+
+        assert_eq!(
+            append_signature("fix: x", trailer),
+            format!("fix: x\n\n{}", trailer)
+        );
+        let signed = append_signature("fix: x\n\n- Body\n\nRefs: #7", trailer);
+        assert_eq!(signed, format!("fix: x\n\n- Body\n\nRefs: #7\n{}", trailer));
+        assert_eq!(
+            ConventionalCommit::validate(&signed)
+                .unwrap()
+                .footers()
+                .len(),
+            2
+        );
+
+        // Already present: not repeated
+        assert_eq!(append_signature(&signed, trailer), signed);
+    }
+
+    #[test]
+    fn mixed_signature_splits_text_and_trailers() {
+        assert_eq!(
+            append_signature(
+                "fix: x\n\nRefs: #7",
+                "🤖 Generated with Claude\n\nAssisted-by: Claude"
+            ),
+            "fix: x\n\n🤖 Generated with Claude\n\nRefs: #7\nAssisted-by: Claude"
+        );
+    }
+
+    #[test]
+    fn converts_days_to_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_844), (2024, 5, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(today().len(), 10);
+    }
+}
@@ -0,0 +1,331 @@
+use crate::error::ConfigError;
+use crate::signature::append_trailers;
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Footer token used for ticket references
+pub const TICKET_FOOTER: &str = "Refs";
+
+/// Capture group that limits the ticket ID to part of a match
+const TICKET_GROUP: &str = "ticket";
+
+/// Whether `key` can be a Jira or Linear project key (`PROJ`, `ENG2`)
+pub fn is_valid_key(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Pattern for issue keys of the configured projects (`ticket_keys`)
+///
+/// Matches `PROJ-1234` for a `PROJ` key, in any case, so Linear's lowercase
+/// `eng-42-fix-sync` branches work too. Only listed keys match, so names like
+/// `UTF-8` or `SHA-256` aren't taken for tickets. `None` without keys.
+pub fn key_pattern(keys: &[String]) -> Option<String> {
+    if keys.is_empty() {
+        return None;
+    }
+    let keys: Vec<String> = keys.iter().map(|key| regex::escape(key)).collect();
+    Some(format!(
+        r"(?i)\b(?P<{}>(?:{})-[0-9]+)\b",
+        TICKET_GROUP,
+        keys.join("|")
+    ))
+}
+
+/// Where ticket references from the branch name go in the message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketPlacement {
+    /// A `Refs: PROJ-1234` footer
+    #[default]
+    Footer,
+    /// Start of the description: `feat(auth): PROJ-1234 add login`
+    Prefix,
+    /// Don't add ticket references
+    Off,
+}
+
+impl FromStr for TicketPlacement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "footer" => Ok(Self::Footer),
+            "prefix" => Ok(Self::Prefix),
+            "off" => Ok(Self::Off),
+            _ => Err(format!(
+                "unknown ticket placement `{}` (expected footer, prefix or off)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TicketPlacement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Footer => write!(f, "footer"),
+            Self::Prefix => write!(f, "prefix"),
+            Self::Off => write!(f, "off"),
+        }
+    }
+}
+
+/// Compile a `ticket_patterns` entry
+pub(crate) fn compile_pattern(pattern: &str) -> Result<Regex, ConfigError> {
+    Regex::new(pattern).map_err(|e| ConfigError::InvalidValue {
+        key: "ticket_patterns".to_string(),
+        value: pattern.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Ticket IDs in a branch name, in order of appearance and without duplicates
+///
+/// Each pattern's `ticket` group is the ID if present, otherwise the whole
+/// match. Numeric IDs become GitHub references (`#123`); others are
+/// uppercased (`eng-42` → `ENG-42`). Invalid patterns are skipped (config
+/// loading already rejects them).
+pub fn extract_tickets(branch: &str, patterns: &[String]) -> Vec<String> {
+    let mut found: Vec<(usize, String)> = Vec::new();
+    for pattern in patterns.iter().filter_map(|p| compile_pattern(p).ok()) {
+        for captures in pattern.captures_iter(branch) {
+            let id = captures
+                .name(TICKET_GROUP)
+                .unwrap_or_else(|| captures.get(0).expect("group 0 always matches"));
+            found.push((id.start(), normalize(id.as_str())));
+        }
+    }
+
+    found.sort_by_key(|(start, _)| *start);
+    let mut tickets: Vec<String> = Vec::new();
+    for (_, id) in found {
+        if !id.is_empty() && !tickets.contains(&id) {
+            tickets.push(id);
+        }
+    }
+    tickets
+}
+
+fn normalize(id: &str) -> String {
+    let id = id.trim_start_matches('#');
+    if id.chars().all(|c| c.is_ascii_digit()) {
+        format!("#{}", id)
+    } else {
+        id.to_uppercase()
+    }
+}
+
+/// Add ticket references to a (signed) commit message
+///
+/// IDs the message already mentions as a whole word are left out. With
+/// [`TicketPlacement::Footer`] the references join the message's footer
+/// block, or start one, so they parse as a [`crate::Footer`]. With
+/// [`TicketPlacement::Prefix`] they go in front of the description.
+pub fn apply_tickets(message: &str, tickets: &[String], placement: TicketPlacement) -> String {
+    let missing: Vec<&str> = tickets
+        .iter()
+        .map(String::as_str)
+        .filter(|id| !mentions(message, id))
+        .collect();
+    if missing.is_empty() {
+        return message.to_string();
+    }
+    let ids = missing.join(", ");
+
+    match placement {
+        TicketPlacement::Off => message.to_string(),
+        TicketPlacement::Footer => {
+            append_trailers(message, &[format!("{}: {}", TICKET_FOOTER, ids)])
+        }
+        TicketPlacement::Prefix => {
+            let (header, rest) = match message.split_once('\n') {
+                Some((header, rest)) => (header, Some(rest)),
+                None => (message, None),
+            };
+            let Some((kind, description)) = header.split_once(": ") else {
+                return message.to_string();
+            };
+            let header = format!("{}: {} {}", kind, ids, description);
+            match rest {
+                Some(rest) => format!("{}\n{}", header, rest),
+                None => header,
+            }
+        }
+    }
+}
+
+/// Whether `id` appears in `message` on its own (`#7` isn't in `#70`)
+fn mentions(message: &str, id: &str) -> bool {
+    let is_id_char = |c: char| c.is_alphanumeric() || c == '_';
+    message.match_indices(id).any(|(start, _)| {
+        let before = message[..start].chars().next_back();
+        let after = message[start + id.len()..].chars().next();
+        !before.is_some_and(is_id_char) && !after.is_some_and(is_id_char)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ConventionalCommit;
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        let keys: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+        key_pattern(&keys).into_iter().collect()
+    }
+
+    #[test]
+    fn extracts_keys_of_configured_projects() {
+        let patterns = keys(&["PROJ", "ENG"]);
+
+        assert_eq!(
+            extract_tickets("feature/PROJ-1234-add-login", &patterns),
+            ["PROJ-1234"]
+        );
+        assert_eq!(
+            extract_tickets("fix/ENG-1-and-eng-2", &patterns),
+            ["ENG-1", "ENG-2"]
+        );
+        assert!(extract_tickets("feature/add-login", &patterns).is_empty());
+        assert!(extract_tickets("main", &patterns).is_empty());
+        assert!(key_pattern(&[]).is_none());
+    }
+
+    #[test]
+    fn ignores_names_that_only_look_like_tickets() {
+        let patterns = keys(&["PROJ"]);
+
+        assert!(extract_tickets("fix/UTF-8-decoding", &patterns).is_empty());
+        assert!(extract_tickets("feat/SHA-256", &patterns).is_empty());
+        assert!(extract_tickets("2024-roadmap", &patterns).is_empty());
+        assert!(extract_tickets("feat/XPROJ-12", &patterns).is_empty());
+    }
+
+    #[test]
+    fn github_numbers_need_a_pattern() {
+        let patterns = vec![r"(?i)(?:^|/)(?:gh|issue)-(?P<ticket>[0-9]+)\b".to_string()];
+
+        assert_eq!(extract_tickets("fix/gh-42-crash", &patterns), ["#42"]);
+        assert!(extract_tickets("2024-roadmap", &patterns).is_empty());
+    }
+
+    #[test]
+    fn custom_patterns_use_the_ticket_group() {
+        // Linear's default branch format is lowercase
+        let patterns = vec![r"(?:^|/)(?P<ticket>eng-[0-9]+)".to_string()];
+
+        assert_eq!(
+            extract_tickets("alice/eng-42-fix-sync", &patterns),
+            ["ENG-42"]
+        );
+    }
+
+    #[test]
+    fn parses_placement() {
+        assert_eq!("Prefix".parse(), Ok(TicketPlacement::Prefix));
+        assert_eq!(TicketPlacement::default().to_string(), "footer");
+        assert!("trailer".parse::<TicketPlacement>().is_err());
+    }
+
+    #[test]
+    fn footer_starts_or_joins_the_footer_block() {
+        let tickets = ["PROJ-1".to_string()];
+
+        let message = apply_tickets(
+            "feat: add login\n\n- Body",
+            &tickets,
+            TicketPlacement::Footer,
+        );
+        assert_eq!(message, "feat: add login\n\n- Body\n\nRefs: PROJ-1");
+
+        let message = apply_tickets(
+            "feat: add login\n\nBREAKING CHANGE: new flow",
+            &tickets,
+            TicketPlacement::Footer,
+        );
+        assert_eq!(
+            message,
+            "feat: add login\n\nBREAKING CHANGE: new flow\nRefs: PROJ-1"
+        );
+
+        // Footers survive validation
+        let commit = ConventionalCommit::validate(&message).unwrap();
+        assert_eq!(commit.footers().len(), 2);
+        assert_eq!(commit.footers()[1].token(), "Refs");
+        assert_eq!(commit.footers()[1].value(), "PROJ-1");
+    }
+
+    #[test]
+    fn footer_follows_the_signature() {
+        let message = apply_tickets(
+            "fix: handle eof\n\n🤖 Generated with Claude via commitment",
+            &["#7".to_string(), "ABC-2".to_string()],
+            TicketPlacement::Footer,
+        );
+
+        let commit = ConventionalCommit::validate(&message).unwrap();
+        assert_eq!(commit.footers()[0].to_string(), "Refs: #7, ABC-2");
+        assert_eq!(
+            commit.body(),
+            Some("🤖 Generated with Claude via commitment")
+        );
+    }
+
+    #[test]
+    fn prefix_goes_before_the_description() {
+        let tickets = ["PROJ-1".to_string()];
+
+        assert_eq!(
+            apply_tickets("feat(auth): add login", &tickets, TicketPlacement::Prefix),
+            "feat(auth): PROJ-1 add login"
+        );
+        assert_eq!(
+            apply_tickets("fix: x\n\n- Body", &tickets, TicketPlacement::Prefix),
+            "fix: PROJ-1 x\n\n- Body"
+        );
+        // Not a header: left alone for validation to reject
+        assert_eq!(
+            apply_tickets("oops", &tickets, TicketPlacement::Prefix),
+            "oops"
+        );
+    }
+
+    #[test]
+    fn skips_ids_already_mentioned_and_off() {
+        let tickets = ["PROJ-1".to_string()];
+
+        assert_eq!(
+            apply_tickets("fix: x\n\nRefs: PROJ-1", &tickets, TicketPlacement::Footer),
+            "fix: x\n\nRefs: PROJ-1"
+        );
+        assert_eq!(
+            apply_tickets("fix: x", &tickets, TicketPlacement::Off),
+            "fix: x"
+        );
+        assert_eq!(
+            apply_tickets("fix: x", &[], TicketPlacement::Footer),
+            "fix: x"
+        );
+    }
+
+    #[test]
+    fn longer_ids_dont_count_as_mentions() {
+        let tickets = ["#7".to_string(), "ENG-1".to_string()];
+
+        assert_eq!(
+            apply_tickets(
+                "fix: x\n\nRefs: #70, ENG-12",
+                &tickets,
+                TicketPlacement::Footer
+            ),
+            "fix: x\n\nRefs: #70, ENG-12\nRefs: #7, ENG-1"
+        );
+        assert_eq!(
+            apply_tickets("fix: x (#7, ENG-1)", &tickets, TicketPlacement::Footer),
+            "fix: x (#7, ENG-1)"
+        );
+    }
+}
@@ -1,39 +1,111 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Deserialize, Deserializer};
 use std::fmt;
 use std::str::FromStr;
 
-/// Valid conventional commit types
+/// Standard conventional commit types
 ///
-/// These are the only allowed prefixes for commit messages.
-/// Used to build the validation regex and display in error messages.
-pub const CONVENTIONAL_COMMIT_TYPES: &[&str] = &[
+/// The default type set only: projects can add or remove types in their
+/// config, so validation takes its types from [`CommitRules`].
+#[deprecated(note = "use `default_commit_types` or the configured `Config::commit_types`")]
+pub const CONVENTIONAL_COMMIT_TYPES: &[&str] = DEFAULT_TYPE_NAMES;
+
+/// Names of the standard types, in prompt order
+const DEFAULT_TYPE_NAMES: &[&str] = &[
     "feat", "fix", "docs", "style", "refactor", "test", "chore", "perf", "build", "ci", "revert",
 ];
 
+/// When to use each of the [`DEFAULT_TYPE_NAMES`], in the same order
+const CONVENTIONAL_COMMIT_DESCRIPTIONS: &[&str] = &[
+    "A new feature",
+    "A bug fix",
+    "Documentation only changes",
+    "Formatting and whitespace changes that don't affect meaning",
+    "A code change that neither fixes a bug nor adds a feature",
+    "Adding or correcting tests",
+    "Maintenance that doesn't touch source or tests",
+    "A code change that improves performance",
+    "Changes to the build system or external dependencies",
+    "Changes to CI configuration and scripts",
+    "Reverts a previous commit",
+];
+
+/// A commit type and when to use it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitType {
+    /// Lowercase name used in the header (`feat`)
+    pub name: String,
+    /// Shown to the agent to steer classification
+    pub description: String,
+}
+
+impl CommitType {
+    pub fn new(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+        }
+    }
+
+    /// Whether `name` can be used as a type (lowercase ASCII letters and digits)
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    }
+}
+
+/// The standard types with their descriptions
+///
+/// # Examples
+/// ```
+/// # use commitment_rs::types::default_commit_types;
+/// let types = default_commit_types();
+/// assert_eq!(types[0].name, "feat");
+/// assert_eq!(types[0].description, "A new feature");
+/// ```
+pub fn default_commit_types() -> Vec<CommitType> {
+    DEFAULT_TYPE_NAMES
+        .iter()
+        .zip(CONVENTIONAL_COMMIT_DESCRIPTIONS)
+        .map(|(name, description)| CommitType::new(*name, *description))
+        .collect()
+}
+
 /// Format commit types as a comma-separated list for display
-pub fn commit_types_display() -> String {
-    CONVENTIONAL_COMMIT_TYPES.join(", ")
+pub fn commit_types_display(types: &[CommitType]) -> String {
+    types
+        .iter()
+        .map(|commit_type| commit_type.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
-static CONVENTIONAL_COMMIT_PATTERN: Lazy<Regex> = Lazy::new(|| {
-    let types = CONVENTIONAL_COMMIT_TYPES.join("|");
-    Regex::new(&format!(r"^({})(\([a-z0-9\-]+\))?: .+", types)).expect("valid regex pattern")
-});
+static CUSTOM_AGENT_NAME_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[a-z0-9][a-z0-9_-]*$").expect("valid regex pattern"));
 
-/// Agent names - closed set of supported AI CLIs
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Agent names - built-in AI CLIs plus user-defined custom agents
+///
+/// Custom agents are any other lowercase identifier (e.g. `ollama`). Their
+/// command line is configured in an `[agents.<name>]` config section.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AgentName {
     Claude,
     Codex,
     Gemini,
+    Custom(String),
 }
 
 impl AgentName {
+    /// Names of the built-in agents, in display order
+    pub const BUILTIN: [AgentName; 3] = [Self::Claude, Self::Codex, Self::Gemini];
+
     /// Get human-readable display name for the agent
     ///
     /// Returns the capitalized name suitable for display in commit signatures
-    /// and user-facing messages.
+    /// and user-facing messages. Custom agents display as their configured name.
     ///
     /// # Examples
     /// ```
@@ -42,18 +114,20 @@ impl AgentName {
     /// assert_eq!(AgentName::Codex.display_name(), "Codex");
     /// assert_eq!(AgentName::Gemini.display_name(), "Gemini");
     /// ```
-    pub fn display_name(&self) -> &'static str {
+    pub fn display_name(&self) -> &str {
         match self {
             Self::Claude => "Claude",
             Self::Codex => "Codex",
             Self::Gemini => "Gemini",
+            Self::Custom(name) => name,
         }
     }
 
     /// Get installation URL for the agent
     ///
     /// Returns the official installation documentation URL for the agent,
-    /// useful for error messages when the agent is not found.
+    /// useful for error messages when the agent is not found. For custom
+    /// agents this points at the custom agent configuration docs.
     ///
     /// # Examples
     /// ```
@@ -67,6 +141,7 @@ impl AgentName {
             Self::Claude => "https://code.claude.com/docs",
             Self::Codex => "https://developers.openai.com/codex/cli",
             Self::Gemini => "https://geminicli.com/docs/get-started/",
+            Self::Custom(_) => "https://github.com/arittr/commitment-rs#custom-agents",
         }
     }
 
@@ -74,7 +149,8 @@ impl AgentName {
     ///
     /// Returns the executable name to invoke this agent from the command line.
     /// This is the canonical source for command names - use this instead of
-    /// hardcoding strings like `"claude"`.
+    /// hardcoding strings like `"claude"`. Custom agents default to a command
+    /// with the same name unless their config section overrides it.
     ///
     /// # Examples
     /// ```
@@ -83,11 +159,61 @@ impl AgentName {
     /// assert_eq!(AgentName::Codex.command_name(), "codex");
     /// assert_eq!(AgentName::Gemini.command_name(), "gemini");
     /// ```
-    pub fn command_name(&self) -> &'static str {
+    pub fn command_name(&self) -> &str {
         match self {
             Self::Claude => "claude",
             Self::Codex => "codex",
             Self::Gemini => "gemini",
+            Self::Custom(name) => name,
+        }
+    }
+
+    /// Check whether this is one of the built-in agents
+    pub fn is_builtin(&self) -> bool {
+        !matches!(self, Self::Custom(_))
+    }
+
+    /// Built-in agent a custom name is probably a typo of (`claud` → `claude`)
+    ///
+    /// Returns `None` for built-in agents and for names more than two edits
+    /// away from every built-in one.
+    ///
+    /// # Examples
+    /// ```
+    /// # use commitment_rs::types::AgentName;
+    /// let typo: AgentName = "gemeni".parse().unwrap();
+    /// assert_eq!(typo.closest_builtin(), Some(AgentName::Gemini));
+    /// ```
+    pub fn closest_builtin(&self) -> Option<AgentName> {
+        let Self::Custom(name) = self else {
+            return None;
+        };
+        Self::BUILTIN
+            .into_iter()
+            .map(|builtin| (edit_distance(name, builtin.command_name()), builtin))
+            .filter(|(distance, _)| *distance <= 2)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, builtin)| builtin)
+    }
+
+    /// Default token budget for the diff section of the prompt
+    ///
+    /// Sized well inside each agent's context window. Custom agents are often
+    /// small local models, so they get the conservative budget that the old
+    /// 8000-byte limit amounted to.
+    ///
+    /// # Examples
+    /// ```
+    /// # use commitment_rs::types::AgentName;
+    /// assert_eq!(AgentName::Claude.diff_token_budget(), 16_000);
+    /// assert_eq!(AgentName::Custom("ollama".into()).diff_token_budget(), 2_000);
+    /// ```
+    pub fn diff_token_budget(&self) -> usize {
+        match self {
+            Self::Claude => 16_000,
+            Self::Codex => 12_000,
+            Self::Gemini => 24_000,
+            Self::Custom(_) => 2_000,
         }
     }
 
@@ -121,10 +247,12 @@ impl FromStr for AgentName {
     type Err = AgentNameParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
+        let lower = s.to_lowercase();
+        match lower.as_str() {
             "claude" => Ok(Self::Claude),
             "codex" => Ok(Self::Codex),
             "gemini" => Ok(Self::Gemini),
+            _ if CUSTOM_AGENT_NAME_PATTERN.is_match(&lower) => Ok(Self::Custom(lower)),
             _ => Err(AgentNameParseError {
                 invalid: s.to_string(),
             }),
@@ -138,6 +266,29 @@ impl fmt::Display for AgentName {
     }
 }
 
+/// Deserializes from the same strings accepted by `FromStr` (used by config files)
+impl<'de> Deserialize<'de> for AgentName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Levenshtein distance between two short ASCII names
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != *cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
 /// Error when parsing agent name from string
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AgentNameParseError {
@@ -148,7 +299,7 @@ impl fmt::Display for AgentNameParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "invalid agent name '{}' (expected: claude, codex, gemini)",
+            "invalid agent name '{}' (expected: claude, codex, gemini, or a custom agent name)",
             self.invalid
         )
     }
@@ -159,36 +310,127 @@ impl std::error::Error for AgentNameParseError {}
 /// Validated conventional commit message
 ///
 /// Can only be constructed via validate(). Once you have this type,
-/// it's guaranteed to be a valid conventional commit format.
+/// it's guaranteed to follow the Conventional Commits 1.0 grammar:
+///
+/// ```text
+/// <type>[(<scope>)][!]: <description>
+///
+/// [body]
+///
+/// [footers]
+/// ```
+///
+/// The parsed parts are available through accessors, and [`render`] puts
+/// them back together byte-for-byte.
+///
+/// [`render`]: ConventionalCommit::render
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ConventionalCommit {
+    kind: String,
+    scope: Option<String>,
+    breaking: bool,
+    description: String,
+    body: Option<String>,
+    footers: Vec<Footer>,
     raw: String, // Private - enforces validation on construction
 }
 
 impl ConventionalCommit {
     /// Validate and construct a conventional commit message
     ///
-    /// Format: `<type>(<scope>): <description>`
+    /// Format: `<type>(<scope>)!: <description>`, optionally followed by a
+    /// blank line and a body, and a final paragraph of footers / git trailers
+    /// (`Token: value` or `Token #value`).
     /// - type: feat, fix, docs, style, refactor, test, chore, perf, build, ci, revert
-    /// - scope: optional, e.g., (api), (cli)
+    ///   (the standard set; see [`validate_with`](Self::validate_with) for others)
+    /// - scope: optional, e.g., (api), (UI), (core/parser)
+    /// - `!`: optional breaking change marker
     /// - description: required
+    ///
+    /// Errors report the line and column of the first problem found.
     #[must_use = "validation result should be checked"]
     pub fn validate(msg: &str) -> Result<Self, CommitValidationError> {
+        Self::validate_with(msg, &CommitRules::default())
+    }
+
+    /// Validate against the grammar plus project-specific [`CommitRules`]
+    ///
+    /// Types come from `rules.types`; a scope outside `rules.scopes` is
+    /// reported as [`SyntaxError::UnknownScope`]. `(core,cli)` names several scopes, each
+    /// of which must be allowed. A header over `rules.max_header_length` is
+    /// reported as [`SyntaxError::HeaderTooLong`], and with
+    /// `rules.require_breaking` a message that isn't marked as breaking as
+    /// [`SyntaxError::UnmarkedBreakingChange`].
+    #[must_use = "validation result should be checked"]
+    pub fn validate_with(msg: &str, rules: &CommitRules) -> Result<Self, CommitValidationError> {
         let msg = msg.trim();
 
         if msg.is_empty() {
             return Err(CommitValidationError::Empty);
         }
 
-        // Use static compiled regex pattern for performance
-        if !CONVENTIONAL_COMMIT_PATTERN.is_match(msg) {
-            return Err(CommitValidationError::InvalidFormat {
-                message: msg.to_string(),
-            });
+        let (header, rest) = match msg.split_once('\n') {
+            Some((header, rest)) => (header, Some(rest)),
+            None => (msg, None),
+        };
+
+        let parsed = parse_header(header, rules).map_err(|(offset, error)| {
+            CommitValidationError::syntax(1, column(header, offset), error, header)
+        })?;
+        let length = header.chars().count();
+        if let Some(max) = rules.max_header_length
+            && length > max
+        {
+            return Err(CommitValidationError::syntax(
+                1,
+                max + 1,
+                SyntaxError::HeaderTooLong { length, max },
+                header,
+            ));
         }
 
+        // Body and footers must be separated from the header by a blank line
+        let (body, footers) = match rest {
+            None => (None, Vec::new()),
+            Some(rest) => {
+                let Some(rest) = rest.strip_prefix('\n') else {
+                    let line = rest.lines().next().unwrap_or_default();
+                    return Err(CommitValidationError::syntax(
+                        2,
+                        1,
+                        SyntaxError::MissingBlankLine,
+                        line,
+                    ));
+                };
+                split_body_and_footers(rest)
+            }
+        };
+
+        if rules.require_breaking
+            && !parsed.breaking
+            && !footers.iter().any(Footer::is_breaking_change)
+        {
+            // Point at the colon, where the `!` belongs
+            let offset = header.find(':').unwrap_or_default();
+            return Err(CommitValidationError::syntax(
+                1,
+                column(header, offset),
+                SyntaxError::UnmarkedBreakingChange,
+                header,
+            ));
+        }
+
+        // `Feat:` is stored as `feat:`
+        let kind = parsed.kind.to_lowercase();
+        let raw = format!("{}{}", kind, &msg[parsed.kind.len()..]);
         Ok(Self {
-            raw: msg.to_string(),
+            kind,
+            scope: parsed.scope.map(str::to_string),
+            breaking: parsed.breaking,
+            description: parsed.description.to_string(),
+            body: body.map(str::to_string),
+            footers,
+            raw,
         })
     }
 
@@ -196,6 +438,292 @@ impl ConventionalCommit {
     pub fn as_str(&self) -> &str {
         &self.raw
     }
+
+    /// Commit type (`feat`, `fix`, ...)
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Scope without parentheses, if present
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+
+    /// Description after `: ` on the header line
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Body text between the header and the footers, if present
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    /// Footers and git trailers from the final paragraph
+    pub fn footers(&self) -> &[Footer] {
+        &self.footers
+    }
+
+    /// Whether the header carries the `!` breaking change marker
+    pub fn has_breaking_marker(&self) -> bool {
+        self.breaking
+    }
+
+    /// Whether this is a breaking change (`!` marker or `BREAKING CHANGE` footer)
+    pub fn is_breaking(&self) -> bool {
+        self.breaking || self.breaking_change().is_some()
+    }
+
+    /// Value of the `BREAKING CHANGE` footer, if present
+    pub fn breaking_change(&self) -> Option<&str> {
+        self.footers
+            .iter()
+            .find(|footer| footer.is_breaking_change())
+            .map(Footer::value)
+    }
+
+    /// First line of the message
+    pub fn header(&self) -> &str {
+        self.raw.lines().next().unwrap_or_default()
+    }
+
+    /// Reassemble the message from its parsed parts
+    ///
+    /// Always equal to [`as_str`](Self::as_str) for a validated commit.
+    pub fn render(&self) -> String {
+        let mut out = self.kind.clone();
+        if let Some(scope) = &self.scope {
+            out.push('(');
+            out.push_str(scope);
+            out.push(')');
+        }
+        if self.breaking {
+            out.push('!');
+        }
+        out.push_str(": ");
+        out.push_str(&self.description);
+
+        if let Some(body) = &self.body {
+            out.push_str("\n\n");
+            out.push_str(body);
+        }
+        if !self.footers.is_empty() {
+            out.push_str("\n\n");
+            let footers: Vec<String> = self.footers.iter().map(Footer::to_string).collect();
+            out.push_str(&footers.join("\n"));
+        }
+        out
+    }
+}
+
+/// A footer or git trailer (`Refs: #123`, `Closes #42`, `BREAKING CHANGE: ...`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footer {
+    token: String,
+    separator: FooterSeparator,
+    value: String,
+}
+
+/// Separator between a footer token and its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FooterSeparator {
+    /// `Token: value`
+    Colon,
+    /// `Token #value`
+    Hash,
+}
+
+impl FooterSeparator {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Colon => ": ",
+            Self::Hash => " #",
+        }
+    }
+}
+
+impl Footer {
+    /// Footer token (`Refs`, `Co-Authored-By`, `BREAKING CHANGE`)
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Separator between token and value
+    pub fn separator(&self) -> FooterSeparator {
+        self.separator
+    }
+
+    /// Footer value, including any continuation lines
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Whether this is a `BREAKING CHANGE` (or `BREAKING-CHANGE`) footer
+    pub fn is_breaking_change(&self) -> bool {
+        self.token == "BREAKING CHANGE" || self.token == "BREAKING-CHANGE"
+    }
+}
+
+impl fmt::Display for Footer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.token, self.separator.as_str(), self.value)
+    }
+}
+
+/// Footer line: `Token: value` or `Token #value`
+static FOOTER_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(BREAKING CHANGE|[A-Za-z0-9][A-Za-z0-9-]*)(: | #)(.*)$")
+        .expect("valid regex pattern")
+});
+
+/// Whether `line` starts a footer (`Token: value` or `Token #value`)
+pub(crate) fn is_footer_line(line: &str) -> bool {
+    FOOTER_PATTERN.is_match(line)
+}
+
+/// Parsed header parts, borrowing from the header line
+struct Header<'a> {
+    kind: &'a str,
+    scope: Option<&'a str>,
+    breaking: bool,
+    description: &'a str,
+}
+
+/// Parse `<type>(<scope>)!: <description>`
+///
+/// Errors carry the byte offset of the problem within the header.
+fn parse_header<'a>(
+    header: &'a str,
+    rules: &CommitRules,
+) -> Result<Header<'a>, (usize, SyntaxError)> {
+    let type_end = header
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(header.len());
+    let kind = &header[..type_end];
+    if kind.is_empty() {
+        return Err((0, SyntaxError::MissingType));
+    }
+    // Types aren't case-sensitive (Conventional Commits 1.0, rule 15)
+    if !rules
+        .types
+        .iter()
+        .any(|t| t.name.eq_ignore_ascii_case(kind))
+    {
+        return Err((
+            0,
+            SyntaxError::UnknownType {
+                found: kind.to_string(),
+                allowed: rules.types.iter().map(|t| t.name.clone()).collect(),
+            },
+        ));
+    }
+
+    let mut pos = type_end;
+    let mut scope = None;
+    if header[pos..].starts_with('(') {
+        let open = pos;
+        let close = header[open..]
+            .find(')')
+            .map(|i| open + i)
+            .ok_or((open, SyntaxError::UnclosedScope))?;
+        let inner = &header[open + 1..close];
+        if inner.is_empty() {
+            return Err((open + 1, SyntaxError::EmptyScope));
+        }
+        if let Some(i) = inner.find(|c: char| c.is_whitespace() || c == '(') {
+            return Err((open + 1 + i, SyntaxError::InvalidScope));
+        }
+        if !rules.scopes.is_empty() {
+            let mut offset = open + 1;
+            for part in inner.split(',') {
+                if !rules.scopes.iter().any(|allowed| allowed == part) {
+                    return Err((
+                        offset,
+                        SyntaxError::UnknownScope {
+                            found: part.to_string(),
+                            allowed: rules.scopes.clone(),
+                        },
+                    ));
+                }
+                offset += part.len() + 1;
+            }
+        }
+        scope = Some(inner);
+        pos = close + 1;
+    }
+
+    let breaking = header[pos..].starts_with('!');
+    if breaking {
+        pos += 1;
+    }
+
+    if !header[pos..].starts_with(':') {
+        return Err((pos, SyntaxError::MissingColon));
+    }
+    pos += 1;
+
+    if header[pos..].trim().is_empty() {
+        return Err((pos, SyntaxError::EmptyDescription));
+    }
+    if !header[pos..].starts_with(' ') {
+        return Err((pos, SyntaxError::MissingSpace));
+    }
+    pos += 1;
+
+    let description = &header[pos..];
+    if description.starts_with(char::is_whitespace) {
+        return Err((pos, SyntaxError::LeadingWhitespace));
+    }
+
+    Ok(Header {
+        kind,
+        scope,
+        breaking,
+        description,
+    })
+}
+
+/// Split everything after the header's blank line into body and footers
+///
+/// The final paragraph is a footer block if its first line is a footer;
+/// lines that don't start a new footer continue the previous footer's value.
+fn split_body_and_footers(rest: &str) -> (Option<&str>, Vec<Footer>) {
+    let (body, last) = match rest.rfind("\n\n") {
+        Some(i) => (Some(&rest[..i]), &rest[i + 2..]),
+        None => (None, rest),
+    };
+
+    let first_line = last.lines().next().unwrap_or_default();
+    if !FOOTER_PATTERN.is_match(first_line) {
+        return (Some(rest), Vec::new());
+    }
+
+    let mut footers: Vec<Footer> = Vec::new();
+    for line in last.split('\n') {
+        match FOOTER_PATTERN.captures(line) {
+            Some(caps) => footers.push(Footer {
+                token: caps[1].to_string(),
+                separator: if &caps[2] == ": " {
+                    FooterSeparator::Colon
+                } else {
+                    FooterSeparator::Hash
+                },
+                value: caps[3].to_string(),
+            }),
+            None => {
+                let footer = footers.last_mut().expect("first line is a footer");
+                footer.value.push('\n');
+                footer.value.push_str(line);
+            }
+        }
+    }
+
+    (body, footers)
+}
+
+/// 1-based character column of a byte offset within a line
+fn column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
 }
 
 /// Enables `&commit` to be used where `&str` is expected
@@ -230,17 +758,47 @@ impl std::ops::Deref for ConventionalCommit {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CommitValidationError {
     Empty,
-    InvalidFormat { message: String },
+    /// The message breaks the Conventional Commits grammar
+    Syntax {
+        /// 1-based line number
+        line: usize,
+        /// 1-based character column within the line
+        column: usize,
+        /// What is wrong at that position
+        error: SyntaxError,
+        /// The offending line, for display
+        source_line: String,
+    },
+}
+
+impl CommitValidationError {
+    fn syntax(line: usize, column: usize, error: SyntaxError, source_line: &str) -> Self {
+        Self::Syntax {
+            line,
+            column,
+            error,
+            source_line: source_line.to_string(),
+        }
+    }
 }
 
 impl fmt::Display for CommitValidationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Empty => write!(f, "commit message is empty"),
-            Self::InvalidFormat { message } => write!(
+            Self::Syntax {
+                line,
+                column,
+                error,
+                source_line,
+            } => write!(
                 f,
-                "invalid conventional commit format: '{}'\nExpected: <type>(<scope>): <description>",
-                message
+                "invalid conventional commit at line {}, column {}: {}\n  {}\n  {}^\nExpected: <type>(<scope>): <description>",
+                line,
+                column,
+                error,
+                source_line,
+                " ".repeat(column - 1)
             ),
         }
     }
@@ -248,6 +806,107 @@ impl fmt::Display for CommitValidationError {
 
 impl std::error::Error for CommitValidationError {}
 
+/// Specific grammar violation inside a commit message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxError {
+    /// Header doesn't start with a type
+    MissingType,
+    /// Type isn't one of the allowed types ([`CommitRules`])
+    UnknownType { found: String, allowed: Vec<String> },
+    /// `(` without a matching `)`
+    UnclosedScope,
+    /// `()`
+    EmptyScope,
+    /// Scope contains whitespace or a nested `(`
+    InvalidScope,
+    /// Scope isn't one of the project's allowed scopes ([`CommitRules`])
+    UnknownScope { found: String, allowed: Vec<String> },
+    /// No `:` after the type, scope and `!`
+    MissingColon,
+    /// `:` not followed by a space
+    MissingSpace,
+    /// Nothing after `: `
+    EmptyDescription,
+    /// More than one space after `:`
+    LeadingWhitespace,
+    /// Body or footers not separated from the header by a blank line
+    MissingBlankLine,
+    /// Header longer than the project allows ([`CommitRules`])
+    HeaderTooLong { length: usize, max: usize },
+    /// Breaking change without a `!` marker or `BREAKING CHANGE` footer ([`CommitRules`])
+    UnmarkedBreakingChange,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingType => write!(f, "expected a commit type"),
+            Self::UnknownType { found, allowed } => write!(
+                f,
+                "unknown type '{}' (expected one of: {})",
+                found,
+                allowed.join(", ")
+            ),
+            Self::UnclosedScope => write!(f, "scope is missing a closing ')'"),
+            Self::EmptyScope => write!(f, "scope must not be empty"),
+            Self::InvalidScope => write!(f, "scope must not contain whitespace or '('"),
+            Self::UnknownScope { found, allowed } => write!(
+                f,
+                "unknown scope '{}' (expected one of: {})",
+                found,
+                allowed.join(", ")
+            ),
+            Self::MissingColon => write!(f, "expected ':' after the type or scope"),
+            Self::MissingSpace => write!(f, "expected a space after ':'"),
+            Self::EmptyDescription => write!(f, "description must not be empty"),
+            Self::LeadingWhitespace => write!(f, "expected exactly one space after ':'"),
+            Self::MissingBlankLine => {
+                write!(f, "expected a blank line between the header and the body")
+            }
+            Self::HeaderTooLong { length, max } => {
+                write!(f, "header is {} characters (max {})", length, max)
+            }
+            Self::UnmarkedBreakingChange => write!(
+                f,
+                "breaking change must be marked with '!' before ':' or a BREAKING CHANGE footer"
+            ),
+        }
+    }
+}
+
+/// Project-specific restrictions on top of the Conventional Commits grammar
+///
+/// The default allows the standard types and any scope. See
+/// [`ConventionalCommit::validate_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitRules {
+    /// Allowed types
+    pub types: Vec<CommitType>,
+    /// Allowed scopes (empty: any scope)
+    pub scopes: Vec<String>,
+    /// Longest allowed header in characters (`None`: any length)
+    pub max_header_length: Option<usize>,
+    /// Require a `!` marker or `BREAKING CHANGE` footer
+    pub require_breaking: bool,
+}
+
+impl CommitRules {
+    pub fn new(types: Vec<CommitType>, scopes: Vec<String>) -> Self {
+        Self {
+            types,
+            scopes,
+            max_header_length: None,
+            require_breaking: false,
+        }
+    }
+}
+
+impl Default for CommitRules {
+    fn default() -> Self {
+        Self::new(default_commit_types(), Vec::new())
+    }
+}
+
 /// Staged git diff data carrier
 ///
 /// Plain struct - no validation needed, just holds git output
@@ -259,6 +918,21 @@ pub struct StagedDiff {
     pub name_status: String,
     /// Output of `git diff --cached`
     pub diff: String,
+    /// Paths whose content was left out of `diff` (see [`crate::ignore`])
+    ///
+    /// They keep their `name_status` line; the prompt marks them there.
+    pub omitted: Vec<String>,
+}
+
+/// A commit from the repository's history
+///
+/// Plain struct like [`StagedDiff`]; used to pick few-shot examples.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryCommit {
+    pub author_name: String,
+    pub author_email: String,
+    /// Full message (subject, body and trailers)
+    pub message: String,
 }
 
 #[cfg(test)]
@@ -286,13 +960,55 @@ mod tests {
 
     #[test]
     fn rejects_invalid_agent_name() {
-        let result = "invalid".parse::<AgentName>();
+        let result = "not an agent!".parse::<AgentName>();
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.to_string().contains("invalid"));
         assert!(err.to_string().contains("claude, codex, gemini"));
     }
 
+    #[test]
+    fn parses_custom_agent_name() {
+        assert_eq!(
+            "ollama".parse::<AgentName>().unwrap(),
+            AgentName::Custom("ollama".to_string())
+        );
+        assert_eq!(
+            "My-LLM".parse::<AgentName>().unwrap(),
+            AgentName::Custom("my-llm".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_custom_agent_name_with_shell_characters() {
+        assert!("llm;rm".parse::<AgentName>().is_err());
+        assert!("-flag".parse::<AgentName>().is_err());
+    }
+
+    #[test]
+    fn custom_agent_name_accessors() {
+        let agent = AgentName::Custom("ollama".to_string());
+        assert_eq!(agent.display_name(), "ollama");
+        assert_eq!(agent.command_name(), "ollama");
+        assert_eq!(agent.to_string(), "ollama");
+        assert!(!agent.is_builtin());
+        assert!(AgentName::Claude.is_builtin());
+        assert_eq!(
+            agent.commit_signature(),
+            "🤖 Generated with ollama via commitment"
+        );
+    }
+
+    #[test]
+    fn suggests_builtin_agent_for_typos() {
+        let closest = |name: &str| name.parse::<AgentName>().unwrap().closest_builtin();
+
+        assert_eq!(closest("claud"), Some(AgentName::Claude));
+        assert_eq!(closest("codx"), Some(AgentName::Codex));
+        assert_eq!(closest("ollama"), None);
+        assert_eq!(closest("claude"), None);
+    }
+
     #[test]
     fn rejects_empty_agent_name() {
         assert!("".parse::<AgentName>().is_err());
@@ -362,55 +1078,37 @@ mod tests {
     #[test]
     fn rejects_commit_without_type() {
         let result = ConventionalCommit::validate("just a description");
-        assert!(matches!(
-            result,
-            Err(CommitValidationError::InvalidFormat { .. })
-        ));
+        assert!(matches!(result, Err(CommitValidationError::Syntax { .. })));
     }
 
     #[test]
     fn rejects_commit_without_colon() {
         let result = ConventionalCommit::validate("feat add feature");
-        assert!(matches!(
-            result,
-            Err(CommitValidationError::InvalidFormat { .. })
-        ));
+        assert!(matches!(result, Err(CommitValidationError::Syntax { .. })));
     }
 
     #[test]
     fn rejects_commit_without_description() {
         let result = ConventionalCommit::validate("feat:");
-        assert!(matches!(
-            result,
-            Err(CommitValidationError::InvalidFormat { .. })
-        ));
+        assert!(matches!(result, Err(CommitValidationError::Syntax { .. })));
     }
 
     #[test]
     fn rejects_invalid_type() {
         let result = ConventionalCommit::validate("invalid: description");
-        assert!(matches!(
-            result,
-            Err(CommitValidationError::InvalidFormat { .. })
-        ));
+        assert!(matches!(result, Err(CommitValidationError::Syntax { .. })));
     }
 
     #[test]
-    fn rejects_uppercase_type() {
-        let result = ConventionalCommit::validate("FEAT: description");
-        assert!(matches!(
-            result,
-            Err(CommitValidationError::InvalidFormat { .. })
-        ));
+    fn lowercases_uppercase_type() {
+        let commit = ConventionalCommit::validate("FEAT: description").unwrap();
+        assert_eq!(commit.as_str(), "feat: description");
     }
 
     #[test]
-    fn rejects_uppercase_scope() {
-        let result = ConventionalCommit::validate("feat(API): description");
-        assert!(matches!(
-            result,
-            Err(CommitValidationError::InvalidFormat { .. })
-        ));
+    fn accepts_uppercase_scope() {
+        let commit = ConventionalCommit::validate("fix(UI): description").unwrap();
+        assert_eq!(commit.scope(), Some("UI"));
     }
 
     #[test]
@@ -420,6 +1118,263 @@ mod tests {
         assert_eq!(result.unwrap().as_str(), "feat: test");
     }
 
+    fn syntax_error(msg: &str) -> (usize, usize, SyntaxError) {
+        match ConventionalCommit::validate(msg) {
+            Err(CommitValidationError::Syntax {
+                line,
+                column,
+                error,
+                ..
+            }) => (line, column, error),
+            other => panic!("expected Syntax error for {:?}, got {:?}", msg, other),
+        }
+    }
+
+    #[test]
+    fn parses_header_parts() {
+        let commit = ConventionalCommit::validate("feat(api)!: drop v1 endpoints").unwrap();
+        assert_eq!(commit.kind(), "feat");
+        assert_eq!(commit.scope(), Some("api"));
+        assert!(commit.has_breaking_marker());
+        assert!(commit.is_breaking());
+        assert_eq!(commit.description(), "drop v1 endpoints");
+        assert_eq!(commit.body(), None);
+        assert!(commit.footers().is_empty());
+    }
+
+    #[test]
+    fn accepts_scope_with_slash() {
+        let commit = ConventionalCommit::validate("feat(core/parser): add parser").unwrap();
+        assert_eq!(commit.scope(), Some("core/parser"));
+    }
+
+    #[test]
+    fn accepts_breaking_marker_without_scope() {
+        let commit = ConventionalCommit::validate("refactor!: rename config keys").unwrap();
+        assert_eq!(commit.scope(), None);
+        assert!(commit.is_breaking());
+    }
+
+    #[test]
+    fn parses_body_and_footers() {
+        let msg = "fix(auth): refresh expired tokens\n\n- Retry once on 401\n- Log refresh failures\n\nRefs: #123\nCloses #45\nCo-Authored-By: Jane <jane@example.com>";
+        let commit = ConventionalCommit::validate(msg).unwrap();
+
+        assert_eq!(
+            commit.body(),
+            Some("- Retry once on 401\n- Log refresh failures")
+        );
+        let footers = commit.footers();
+        assert_eq!(footers.len(), 3);
+        assert_eq!(footers[0].token(), "Refs");
+        assert_eq!(footers[0].value(), "#123");
+        assert_eq!(footers[1].token(), "Closes");
+        assert_eq!(footers[1].separator(), FooterSeparator::Hash);
+        assert_eq!(footers[1].value(), "45");
+        assert_eq!(footers[2].token(), "Co-Authored-By");
+        assert!(!commit.is_breaking());
+    }
+
+    #[test]
+    fn parses_breaking_change_footer() {
+        let msg = "feat: allow config extends\n\nBREAKING CHANGE: `extends` now resolves\n  relative to the config file";
+        let commit = ConventionalCommit::validate(msg).unwrap();
+        assert_eq!(commit.body(), None);
+        assert!(!commit.has_breaking_marker());
+        assert!(commit.is_breaking());
+        assert_eq!(
+            commit.breaking_change(),
+            Some("`extends` now resolves\n  relative to the config file")
+        );
+    }
+
+    #[test]
+    fn hyphenated_breaking_change_token_is_breaking() {
+        let commit = ConventionalCommit::validate("fix: x\n\nBREAKING-CHANGE: y").unwrap();
+        assert!(commit.is_breaking());
+    }
+
+    #[test]
+    fn body_only_last_paragraph_is_not_footer() {
+        let msg = "docs: update readme\n\nFirst paragraph.\n\n- bullet one\n- bullet two";
+        let commit = ConventionalCommit::validate(msg).unwrap();
+        assert_eq!(
+            commit.body(),
+            Some("First paragraph.\n\n- bullet one\n- bullet two")
+        );
+        assert!(commit.footers().is_empty());
+    }
+
+    #[test]
+    fn render_round_trips_byte_for_byte() {
+        let messages = [
+            "feat: add new feature",
+            "feat(api)!: drop v1 endpoints",
+            "fix(UI): align buttons\n\nBody text here.",
+            "feat: x\n\n\nbody after extra blank line",
+            "chore: bump deps\n\nRefs: #1\nCloses #2",
+            "feat: a\n\nbody\n\nwith paragraphs\n\nBREAKING CHANGE: y\n  continued\nRefs: #9",
+            "perf(core/parser): faster 世界 🦀\n\n- émojis\n\n🤖 Generated with Claude via commitment",
+        ];
+        for msg in messages {
+            let commit = ConventionalCommit::validate(msg).unwrap();
+            assert_eq!(commit.render(), msg);
+            assert_eq!(commit.render(), commit.as_str());
+        }
+    }
+
+    #[test]
+    fn header_accessor_returns_first_line() {
+        let commit = ConventionalCommit::validate("feat: a\n\nbody").unwrap();
+        assert_eq!(commit.header(), "feat: a");
+    }
+
+    #[test]
+    fn error_positions_in_header() {
+        assert_eq!(
+            syntax_error("feat add feature"),
+            (1, 5, SyntaxError::MissingColon)
+        );
+        assert_eq!(
+            syntax_error("feat:add feature"),
+            (1, 6, SyntaxError::MissingSpace)
+        );
+        assert_eq!(syntax_error("feat:"), (1, 6, SyntaxError::EmptyDescription));
+        assert_eq!(
+            syntax_error("feat: "),
+            (1, 6, SyntaxError::EmptyDescription)
+        );
+        assert_eq!(
+            syntax_error("feat:  two spaces"),
+            (1, 7, SyntaxError::LeadingWhitespace)
+        );
+        assert_eq!(syntax_error("(api): x"), (1, 1, SyntaxError::MissingType));
+        assert_eq!(syntax_error("feat(): x"), (1, 6, SyntaxError::EmptyScope));
+        assert_eq!(
+            syntax_error("feat(api: x"),
+            (1, 5, SyntaxError::UnclosedScope)
+        );
+        assert_eq!(
+            syntax_error("feat(my api): x"),
+            (1, 8, SyntaxError::InvalidScope)
+        );
+    }
+
+    #[test]
+    fn error_names_unknown_and_uppercase_types() {
+        assert_eq!(
+            syntax_error("feature: x"),
+            (
+                1,
+                1,
+                SyntaxError::UnknownType {
+                    found: "feature".to_string(),
+                    allowed: DEFAULT_TYPE_NAMES.iter().map(|t| t.to_string()).collect()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn types_are_case_insensitive() {
+        for message in ["FEAT: x", "Feat: x"] {
+            let commit = ConventionalCommit::validate(message).unwrap();
+            assert_eq!(commit.kind(), "feat");
+            assert_eq!(commit.as_str(), "feat: x");
+        }
+        let commit = ConventionalCommit::validate("Fix(API)!: x\n\n- Body").unwrap();
+        assert_eq!(commit.as_str(), "fix(API)!: x\n\n- Body");
+    }
+
+    #[test]
+    fn rules_set_the_allowed_types() {
+        let mut types = default_commit_types();
+        types.retain(|t| t.name != "chore");
+        types.push(CommitType::new("deps", "Dependency updates"));
+        let rules = CommitRules::new(types, Vec::new());
+
+        assert!(ConventionalCommit::validate_with("deps: bump serde", &rules).is_ok());
+        assert!(ConventionalCommit::validate("deps: bump serde").is_err());
+
+        let Err(CommitValidationError::Syntax { error, .. }) =
+            ConventionalCommit::validate_with("chore: tidy", &rules)
+        else {
+            panic!("expected a syntax error");
+        };
+        assert!(error.to_string().ends_with("revert, deps)"));
+        assert_eq!(commit_types_display(&rules.types[..3]), "feat, fix, docs");
+    }
+
+    #[test]
+    fn rules_restrict_scopes() {
+        let rules = CommitRules {
+            scopes: vec!["core".to_string(), "cli".to_string()],
+            ..CommitRules::default()
+        };
+
+        assert!(ConventionalCommit::validate_with("feat(core): x", &rules).is_ok());
+        assert!(ConventionalCommit::validate_with("feat(core,cli): x", &rules).is_ok());
+        assert!(ConventionalCommit::validate_with("feat: x", &rules).is_ok());
+        assert!(ConventionalCommit::validate("feat(web): x").is_ok());
+
+        let Err(CommitValidationError::Syntax { column, error, .. }) =
+            ConventionalCommit::validate_with("feat(core,web): x", &rules)
+        else {
+            panic!("expected a syntax error");
+        };
+        assert_eq!(column, 11);
+        assert_eq!(
+            error.to_string(),
+            "unknown scope 'web' (expected one of: core, cli)"
+        );
+    }
+
+    #[test]
+    fn rules_limit_header_length() {
+        let rules = CommitRules {
+            max_header_length: Some(12),
+            ..CommitRules::default()
+        };
+
+        assert!(ConventionalCommit::validate_with("fix: twelve", &rules).is_ok());
+        assert_eq!(
+            ConventionalCommit::validate_with("fix: thirteen", &rules),
+            Err(CommitValidationError::syntax(
+                1,
+                13,
+                SyntaxError::HeaderTooLong {
+                    length: 13,
+                    max: 12
+                },
+                "fix: thirteen"
+            ))
+        );
+    }
+
+    #[test]
+    fn error_requires_blank_line_after_header() {
+        assert_eq!(
+            syntax_error("feat: x\n- bullet"),
+            (2, 1, SyntaxError::MissingBlankLine)
+        );
+    }
+
+    #[test]
+    fn error_columns_count_characters_not_bytes() {
+        // Scope error after a multi-byte character
+        let (_, column, _) = syntax_error("feat(é x): y");
+        assert_eq!(column, 7);
+    }
+
+    #[test]
+    fn syntax_error_display_points_at_column() {
+        let err = ConventionalCommit::validate("feat add feature").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid conventional commit at line 1, column 5: expected ':' after the type or scope\n  feat add feature\n      ^\nExpected: <type>(<scope>): <description>"
+        );
+    }
+
     #[test]
     fn staged_diff_default() {
         let diff = StagedDiff::default();
@@ -434,6 +1389,7 @@ mod tests {
             stat: "1 file changed, 10 insertions(+)".to_string(),
             name_status: "A\tsrc/test.rs".to_string(),
             diff: "@@ -0,0 +1,10 @@".to_string(),
+            omitted: Vec::new(),
         };
         assert_eq!(diff.stat, "1 file changed, 10 insertions(+)");
         assert_eq!(diff.name_status, "A\tsrc/test.rs");
@@ -446,6 +1402,7 @@ mod tests {
             stat: "test".to_string(),
             name_status: "test".to_string(),
             diff: "test".to_string(),
+            omitted: Vec::new(),
         };
         let diff2 = diff1.clone();
         assert_eq!(diff1, diff2);
@@ -548,6 +1505,7 @@ mod tests {
         assert_eq!(s2, "chore: update deps");
 
         // deref - Deref trait (implicit via &*)
+        #[allow(clippy::explicit_auto_deref)]
         let s3: &str = &*commit;
         assert_eq!(s3, "chore: update deps");
 
@@ -39,6 +39,7 @@ pub mod diffs {
             stat: " src/lib.rs | 5 +++++\n 1 file changed, 5 insertions(+)".into(),
             name_status: "M\tsrc/lib.rs".into(),
             diff: include_str!("diffs/simple_addition.diff").into(),
+            omitted: Vec::new(),
         }
     }
 
@@ -48,6 +49,7 @@ pub mod diffs {
             stat: " src/lib.rs  | 10 ++++++++++\n src/main.rs |  3 +++\n 2 files changed, 13 insertions(+)".into(),
             name_status: "M\tsrc/lib.rs\nM\tsrc/main.rs".into(),
             diff: include_str!("diffs/multi_file_feature.diff").into(),
+            omitted: Vec::new(),
         }
     }
 
@@ -58,6 +60,7 @@ pub mod diffs {
                 .into(),
             name_status: "M\tsrc/parser.rs".into(),
             diff: include_str!("diffs/bug_fix.diff").into(),
+            omitted: Vec::new(),
         }
     }
 
@@ -67,6 +70,7 @@ pub mod diffs {
             stat: " src/handlers.rs | 20 +++++++++-----------\n 1 file changed, 9 insertions(+), 11 deletions(-)".into(),
             name_status: "M\tsrc/handlers.rs".into(),
             diff: include_str!("diffs/refactor_handlers.diff").into(),
+            omitted: Vec::new(),
         }
     }
 
@@ -76,6 +80,7 @@ pub mod diffs {
             stat: " src/lib.rs | 8 ++++++++\n 1 file changed, 8 insertions(+)".into(),
             name_status: "M\tsrc/lib.rs".into(),
             diff: include_str!("diffs/documentation.diff").into(),
+            omitted: Vec::new(),
         }
     }
 
@@ -85,6 +90,7 @@ pub mod diffs {
             stat: " src/lib.rs | 15 +++++++++++++++\n 1 file changed, 15 insertions(+)".into(),
             name_status: "M\tsrc/lib.rs".into(),
             diff: include_str!("diffs/add_tests.diff").into(),
+            omitted: Vec::new(),
         }
     }
 }
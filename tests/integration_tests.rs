@@ -13,6 +13,7 @@ impl MockGitProvider {
                 stat: "1 file changed, 10 insertions(+)".to_string(),
                 name_status: "A\tsrc/test.rs".to_string(),
                 diff: "@@ -0,0 +1,10 @@\n+fn test() {}".to_string(),
+                omitted: Vec::new(),
             }),
             has_changes: true,
         }
@@ -106,11 +107,16 @@ async fn truncation_message_appears_for_large_diffs() {
         stat: "1 file changed, 1000 insertions(+)".to_string(),
         name_status: "M\tsrc/large_file.rs".to_string(),
         diff: large_diff,
+        omitted: Vec::new(),
     });
 
-    // Build prompt
+    // Build prompt with a 2000-token (~8000 byte) diff budget
     let diff = git.get_staged_diff().unwrap();
-    let prompt = build_prompt(&diff);
+    let config = Config {
+        max_diff_tokens: Some(2000),
+        ..Config::default()
+    };
+    let prompt = build_prompt_with_config(&diff, &config);
 
     // Verify truncation message appears
     assert!(
@@ -120,8 +126,9 @@ async fn truncation_message_appears_for_large_diffs() {
 
     // Verify the prompt is not excessively large
     // Should be around 8000 chars for diff + overhead for other sections
+    // (instructions and type descriptions)
     assert!(
-        prompt.len() < 10000,
+        prompt.len() < 11000,
         "Prompt should be truncated to reasonable size"
     );
 }
@@ -132,6 +139,7 @@ async fn change_summary_appears_in_prompt() {
         stat: "3 files changed, 50 insertions(+), 20 deletions(-)".to_string(),
         name_status: "M\tsrc/a.rs\nA\tsrc/b.rs\nD\tsrc/c.rs".to_string(),
         diff: "@@ -1,3 +1,4 @@\n+new code".to_string(),
+        omitted: Vec::new(),
     });
 
     let diff = git.get_staged_diff().unwrap();
@@ -172,6 +180,7 @@ async fn staged_files_display_formatting() {
         stat: "3 files changed, 25 insertions(+), 5 deletions(-)".to_string(),
         name_status: "M\tsrc/main.rs\nA\tsrc/new.rs\nD\tsrc/old.rs".to_string(),
         diff: "@@ diff content".to_string(),
+        omitted: Vec::new(),
     });
 
     let diff = git.get_staged_diff().unwrap();
@@ -218,6 +227,7 @@ async fn git_diff_flags_produce_expected_output() {
         stat: "1 file changed, 5 insertions(+), 2 deletions(-)".to_string(),
         name_status: "M\tsrc/lib.rs".to_string(),
         diff: "@@ -10,7 +10,10 @@ fn example() {\n-old line 1\n-old line 2\n+new line 1\n+new line 2\n+new line 3\n+new line 4\n+new line 5\n context line 1\n context line 2\n context line 3".to_string(),
+        omitted: Vec::new(),
     });
 
     let diff = git.get_staged_diff().unwrap();
@@ -366,6 +376,7 @@ async fn realistic_flow_with_multiple_files() {
 @@ -1,5 +1,30 @@ src/lib.rs
 +pub use agents::generate;
 "#.to_string(),
+        omitted: Vec::new(),
     });
 
     let diff = git.get_staged_diff().unwrap();
@@ -427,7 +438,6 @@ async fn validation_errors_propagate() {
     let invalid_responses = vec![
         "",                     // Empty
         "just some text",       // No type
-        "FEAT: description",    // Uppercase type
         "feat description",     // Missing colon
         "invalid: description", // Invalid type
     ];
@@ -456,6 +466,26 @@ async fn validation_errors_propagate() {
     }
 }
 
+#[tokio::test]
+async fn candidates_from_custom_command_agent() {
+    // Ignores the prompt and prints three candidates, one a duplicate
+    let script = "cat >/dev/null; printf '<<<COMMIT_MESSAGE_START>>>\\nfeat: add parser\\n<<<COMMIT_MESSAGE_SEPARATOR>>>\\nfeat: add parser\\n<<<COMMIT_MESSAGE_SEPARATOR>>>\\nrefactor(parser): extract module\\n<<<COMMIT_MESSAGE_END>>>\\n'";
+    let mut agent = agents::custom::CustomAgent::new("multi");
+    agent.config.command = Some("sh".to_string());
+    agent.config.args = vec!["-c".to_string(), script.to_string()];
+    let agent = Agent::Custom(agent);
+    let git = MockGitProvider::new();
+
+    let commits = generate_candidates(&git, &agent, None, &Config::default(), 3)
+        .await
+        .unwrap();
+    let headers: Vec<&str> = commits.iter().map(|c| c.header()).collect();
+    assert_eq!(
+        headers,
+        ["feat: add parser", "refactor(parser): extract module"]
+    );
+}
+
 #[tokio::test]
 async fn utf8_handling_in_diffs() {
     // Test that UTF-8 characters in diffs are handled correctly
@@ -463,6 +493,7 @@ async fn utf8_handling_in_diffs() {
         stat: "1 file changed, 3 insertions(+)".to_string(),
         name_status: "M\tsrc/main.rs".to_string(),
         diff: "@@ -1,2 +1,5 @@\n+println!(\"Hello 世界 🦀\");\n+// Comment with émojis".to_string(),
+        omitted: Vec::new(),
     });
 
     let diff = git.get_staged_diff().unwrap();
@@ -481,17 +512,22 @@ async fn utf8_handling_in_diffs() {
 async fn truncation_respects_utf8_boundaries() {
     // Create a diff with UTF-8 characters near the truncation boundary
     let mut large_diff = "a".repeat(7998);
-    large_diff.push_str("🦀"); // 4-byte UTF-8 char
+    large_diff.push('🦀'); // 4-byte UTF-8 char
     large_diff.push_str(&"b".repeat(100));
 
     let git = MockGitProvider::with_diff(StagedDiff {
         stat: "1 file changed, 1 insertion(+)".to_string(),
         name_status: "M\tsrc/test.rs".to_string(),
         diff: large_diff,
+        omitted: Vec::new(),
     });
 
     let diff = git.get_staged_diff().unwrap();
-    let prompt = build_prompt(&diff);
+    let config = Config {
+        max_diff_tokens: Some(2000),
+        ..Config::default()
+    };
+    let prompt = build_prompt_with_config(&diff, &config);
 
     // Should not panic on UTF-8 boundary
     assert!(prompt.contains("... (diff truncated)"));
@@ -499,3 +535,385 @@ async fn truncation_respects_utf8_boundaries() {
     // Should be valid UTF-8
     assert!(std::str::from_utf8(prompt.as_bytes()).is_ok());
 }
+
+/// Serve a single canned HTTP response on a random local port
+///
+/// Returns the base URL and a handle resolving to the request head and body
+/// the server received.
+async fn stub_http_server(
+    status: &'static str,
+    body: &'static str,
+) -> (String, tokio::task::JoinHandle<(String, String)>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+
+        // Read headers, then Content-Length bytes of body
+        let (head, body_start) = loop {
+            let n = socket.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break (
+                    String::from_utf8_lossy(&request[..pos]).to_string(),
+                    pos + 4,
+                );
+            }
+        };
+        let content_length: usize = head
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse().ok())?
+            })
+            .unwrap_or(0);
+        while request.len() < body_start + content_length {
+            let n = socket.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        let request_body = String::from_utf8_lossy(&request[body_start..]).to_string();
+
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        socket.shutdown().await.unwrap();
+
+        (head, request_body)
+    });
+
+    (base_url, handle)
+}
+
+fn http_agent(api: agents::http::HttpApi, base_url: String) -> Agent {
+    Agent::Http(agents::http::HttpAgent::new(
+        "local",
+        agents::http::HttpAgentConfig {
+            api,
+            base_url: Some(base_url),
+            model: Some("test-model".to_string()),
+            temperature: Some(0.2),
+            max_tokens: Some(256),
+            api_key_env: None,
+            timeout_secs: None,
+        },
+    ))
+}
+
+#[tokio::test]
+async fn http_agent_openai_generation_flow() {
+    let (base_url, server) = stub_http_server(
+        "200 OK",
+        r#"{"choices":[{"message":{"role":"assistant","content":"<<<COMMIT_MESSAGE_START>>>feat(http): add local agent<<<COMMIT_MESSAGE_END>>>"}}]}"#,
+    )
+    .await;
+    let agent = http_agent(agents::http::HttpApi::Openai, format!("{}/v1", base_url));
+    let git = MockGitProvider::new();
+
+    let message = generate_commit_message(&git, &agent, None, &Config::default())
+        .await
+        .unwrap();
+    assert_eq!(message.as_str(), "feat(http): add local agent");
+
+    let (head, body) = server.await.unwrap();
+    assert!(head.starts_with("POST /v1/chat/completions "));
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["model"], "test-model");
+    assert_eq!(body["max_tokens"], 256);
+    assert_eq!(body["messages"][0]["role"], "user");
+    assert!(
+        body["messages"][0]["content"]
+            .as_str()
+            .unwrap()
+            .contains("fn test()")
+    );
+}
+
+#[tokio::test]
+async fn http_agent_ollama_generation_flow() {
+    let (base_url, server) =
+        stub_http_server("200 OK", r#"{"response":"fix: handle ollama","done":true}"#).await;
+    let agent = http_agent(agents::http::HttpApi::Ollama, base_url);
+    let git = MockGitProvider::new();
+
+    let message = generate_commit_message(&git, &agent, None, &Config::default())
+        .await
+        .unwrap();
+    assert_eq!(message.as_str(), "fix: handle ollama");
+
+    let (head, body) = server.await.unwrap();
+    assert!(head.starts_with("POST /api/generate "));
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    // Generation asks for a stream; a server answering in one piece still works
+    assert_eq!(body["stream"], true);
+    assert_eq!(body["options"]["num_predict"], 256);
+}
+
+#[tokio::test]
+async fn http_agent_streams_openai_events() {
+    let (base_url, _server) = stub_http_server(
+        "200 OK",
+        concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"<<<COMMIT_MESSAGE_START>>>feat: \"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"stream<<<COMMIT_MESSAGE_END>>>\"}}]}\n\n",
+            "data: [DONE]\n\n",
+        ),
+    )
+    .await;
+    let agent = http_agent(agents::http::HttpApi::Openai, base_url);
+
+    let mut seen = Vec::new();
+    let response = agent
+        .execute_streaming("prompt", &mut |text| seen.push(text.to_string()))
+        .await
+        .unwrap();
+    assert_eq!(
+        response,
+        "<<<COMMIT_MESSAGE_START>>>feat: stream<<<COMMIT_MESSAGE_END>>>"
+    );
+    assert_eq!(seen.last(), Some(&response));
+    assert_eq!(clean_ai_response(&response), "feat: stream");
+}
+
+#[tokio::test]
+async fn http_agent_error_status_maps_to_execution_failed() {
+    let (base_url, _server) = stub_http_server(
+        "500 Internal Server Error",
+        r#"{"error":"model not loaded"}"#,
+    )
+    .await;
+    let agent = http_agent(agents::http::HttpApi::Openai, base_url);
+
+    match agent.execute("prompt").await {
+        Err(AgentError::ExecutionFailed { agent, stderr, .. }) => {
+            assert_eq!(agent, AgentName::Custom("local".to_string()));
+            assert!(stderr.contains("500"));
+            assert!(stderr.contains("model not loaded"));
+        }
+        other => panic!("expected ExecutionFailed error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn http_agent_malformed_body_maps_to_invalid_response() {
+    let (base_url, _server) = stub_http_server("200 OK", r#"{"choices":[]}"#).await;
+    let agent = http_agent(agents::http::HttpApi::Openai, base_url);
+
+    let result = agent.execute("prompt").await;
+    assert!(matches!(result, Err(AgentError::InvalidResponse { .. })));
+}
+
+#[tokio::test]
+async fn http_agent_timeout_maps_to_timeout() {
+    // Accept the connection but never respond
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let _server = tokio::spawn(async move {
+        let (_socket, _) = listener.accept().await.unwrap();
+        std::future::pending::<()>().await;
+    });
+
+    let agent = Agent::Http(agents::http::HttpAgent {
+        timeout: std::time::Duration::from_millis(200),
+        ..agents::http::HttpAgent::new(
+            "local",
+            agents::http::HttpAgentConfig {
+                base_url: Some(base_url),
+                ..Default::default()
+            },
+        )
+    });
+
+    let result = agent.execute("prompt").await;
+    assert!(matches!(result, Err(AgentError::Timeout { .. })));
+}
+
+/// Fake agent CLI: records its arguments, stdin and any `@file` it is given
+#[cfg(unix)]
+const FAKE_AGENT_SCRIPT: &str = r#"#!/bin/sh
+log="$(dirname "$0")/../log"
+printf '%s\n' "$@" > "$log/args"
+printf '%s\n' "$FAKE_AGENT_ENV" > "$log/env"
+pwd > "$log/cwd"
+cat > "$log/stdin"
+for arg in "$@"; do
+  case "$arg" in
+    @*)
+      file="${arg#@}"
+      printf '%s\n' "$file" > "$log/file_path"
+      ls -l "$file" | cut -c1-10 > "$log/file_mode"
+      cat "$file" > "$log/file"
+      ;;
+  esac
+done
+printf '<<<COMMIT_MESSAGE_START>>>\nfeat: add answer\n<<<COMMIT_MESSAGE_END>>>\n'
+"#;
+
+/// Run `commitment --message-only` in a repository with a staged change,
+/// with fake `claude`, `codex` and `gemini` binaries first on `PATH`
+///
+/// Returns the temp directory (the fake agents log to `log/`) and stdout.
+#[cfg(unix)]
+fn run_with_fake_agents(config: &str, args: &[&str]) -> (tempfile::TempDir, String) {
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Command;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let bin = dir.path().join("bin");
+    let repo = dir.path().join("repo");
+    for sub in [&bin, &repo, &dir.path().join("log")] {
+        std::fs::create_dir(sub).unwrap();
+    }
+    for agent in ["claude", "codex", "gemini"] {
+        let path = bin.join(agent);
+        std::fs::write(&path, FAKE_AGENT_SCRIPT).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(&repo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    std::fs::write(
+        repo.join("answer.rs"),
+        "pub fn answer() -> u32 {\n    42\n}\n",
+    )
+    .unwrap();
+    git(&["add", "answer.rs"]);
+    std::fs::write(repo.join(".commitment.toml"), config).unwrap();
+
+    let path = format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_commitment"))
+        .args(["--message-only", "--no-signature"])
+        .args(args)
+        .current_dir(&repo)
+        .env("PATH", path)
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "commitment failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    (dir, String::from_utf8(output.stdout).unwrap())
+}
+
+#[cfg(unix)]
+fn fake_agent_log(dir: &tempfile::TempDir, name: &str) -> String {
+    std::fs::read_to_string(dir.path().join("log").join(name)).unwrap_or_default()
+}
+
+#[cfg(unix)]
+#[test]
+fn builtin_agents_default_to_stdin_prompts() {
+    for agent in ["claude", "codex", "gemini"] {
+        let (dir, stdout) = run_with_fake_agents("", &["--agent", agent]);
+        assert_eq!(stdout, "feat: add answer\n");
+
+        assert!(fake_agent_log(&dir, "stdin").contains("pub fn answer()"));
+        let args = fake_agent_log(&dir, "args");
+        assert!(!args.contains("answer"), "{} got the prompt in argv", agent);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn builtin_agent_argv_prompt_is_configurable() {
+    let (dir, _) = run_with_fake_agents("[agents.claude]\nprompt = \"argv\"\n", &[]);
+
+    let args = fake_agent_log(&dir, "args");
+    assert!(args.starts_with("--print\n"));
+    assert!(args.contains("pub fn answer()"));
+    assert_eq!(fake_agent_log(&dir, "stdin"), "");
+}
+
+#[cfg(unix)]
+#[test]
+fn builtin_agent_file_prompt_is_private_and_removed() {
+    let (dir, stdout) = run_with_fake_agents(
+        "[agents.gemini]\nprompt = \"file\"\n",
+        &["--agent", "gemini"],
+    );
+    assert_eq!(stdout, "feat: add answer\n");
+
+    assert!(fake_agent_log(&dir, "args").starts_with("-p\n@"));
+    assert!(fake_agent_log(&dir, "file").contains("pub fn answer()"));
+    assert_eq!(fake_agent_log(&dir, "file_mode").trim(), "-rw-------");
+    // Gemini only reads `@` paths inside the directory it runs in
+    let file_path = fake_agent_log(&dir, "file_path");
+    assert!(!file_path.trim().contains('/'), "{}", file_path);
+    assert!(!dir.path().join("repo").join(file_path.trim()).exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn builtin_agent_settings_reach_the_process() {
+    let work = tempfile::TempDir::new().unwrap();
+    let config = format!(
+        "[agents.codex]\nmodel = \"o4-mini\"\nargs = [\"--color\", \"never\"]\nenv = {{ FAKE_AGENT_ENV = \"from config\" }}\ncwd = \"{}\"\n",
+        work.path().display()
+    );
+    let (dir, stdout) =
+        run_with_fake_agents(&config, &["--agent", "codex", "--agent-arg", "--full-auto"]);
+    assert_eq!(stdout, "feat: add answer\n");
+
+    assert_eq!(
+        fake_agent_log(&dir, "args"),
+        "exec\n--skip-git-repo-check\n--model\no4-mini\n--color\nnever\n--full-auto\n"
+    );
+    assert_eq!(fake_agent_log(&dir, "env"), "from config\n");
+    let cwd = std::fs::canonicalize(work.path()).unwrap();
+    assert_eq!(fake_agent_log(&dir, "cwd").trim(), cwd.to_str().unwrap());
+}
+
+#[test]
+fn config_errors_are_printed() {
+    use std::process::Command;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join(".git")).unwrap();
+    std::fs::write(dir.path().join(".commitment.toml"), "agent = \"claud\"\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_commitment"))
+        .args(["config", "show"])
+        .current_dir(dir.path())
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .env_remove("COMMITMENT_AGENT")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Failed to load config"),
+        "stderr: {}",
+        stderr
+    );
+    assert!(
+        stderr.contains("did you mean `claude`?"),
+        "stderr: {}",
+        stderr
+    );
+}
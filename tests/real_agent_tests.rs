@@ -28,7 +28,7 @@ async fn claude_generates_valid_commit_for_simple_change() {
     let git = MockGitProvider::new(diffs::simple_addition());
     let agent = Agent::from(AgentName::Claude);
 
-    let result = generate_commit_message(&git, &agent, None).await;
+    let result = generate_commit_message(&git, &agent, None, &Config::default()).await;
 
     assert!(
         result.is_ok(),
@@ -51,7 +51,7 @@ async fn claude_generates_valid_commit_for_multi_file_change() {
     let git = MockGitProvider::new(diffs::multi_file_feature());
     let agent = Agent::from(AgentName::Claude);
 
-    let result = generate_commit_message(&git, &agent, None).await;
+    let result = generate_commit_message(&git, &agent, None, &Config::default()).await;
 
     assert!(
         result.is_ok(),
@@ -66,7 +66,7 @@ async fn claude_generates_fix_type_for_bug_fix() {
     let git = MockGitProvider::new(diffs::bug_fix());
     let agent = Agent::from(AgentName::Claude);
 
-    let result = generate_commit_message(&git, &agent, None).await;
+    let result = generate_commit_message(&git, &agent, None, &Config::default()).await;
 
     assert!(
         result.is_ok(),
@@ -84,7 +84,7 @@ async fn claude_handles_signature_appending() {
     let agent = Agent::from(AgentName::Claude);
     let signature = agent.name().commit_signature();
 
-    let result = generate_commit_message(&git, &agent, Some(&signature)).await;
+    let result = generate_commit_message(&git, &agent, Some(&signature), &Config::default()).await;
 
     assert!(
         result.is_ok(),
@@ -106,7 +106,7 @@ async fn claude_generates_docs_type_for_documentation() {
     let git = MockGitProvider::new(diffs::documentation());
     let agent = Agent::from(AgentName::Claude);
 
-    let result = generate_commit_message(&git, &agent, None).await;
+    let result = generate_commit_message(&git, &agent, None, &Config::default()).await;
 
     assert!(
         result.is_ok(),
@@ -123,7 +123,7 @@ async fn claude_generates_test_type_for_tests() {
     let git = MockGitProvider::new(diffs::add_tests());
     let agent = Agent::from(AgentName::Claude);
 
-    let result = generate_commit_message(&git, &agent, None).await;
+    let result = generate_commit_message(&git, &agent, None, &Config::default()).await;
 
     assert!(
         result.is_ok(),
@@ -144,7 +144,7 @@ async fn codex_generates_valid_commit_for_simple_change() {
     let git = MockGitProvider::new(diffs::simple_addition());
     let agent = Agent::from(AgentName::Codex);
 
-    let result = generate_commit_message(&git, &agent, None).await;
+    let result = generate_commit_message(&git, &agent, None, &Config::default()).await;
 
     assert!(
         result.is_ok(),
@@ -159,7 +159,7 @@ async fn codex_generates_valid_commit_for_refactor() {
     let git = MockGitProvider::new(diffs::refactor_handlers());
     let agent = Agent::from(AgentName::Codex);
 
-    let result = generate_commit_message(&git, &agent, None).await;
+    let result = generate_commit_message(&git, &agent, None, &Config::default()).await;
 
     assert!(
         result.is_ok(),
@@ -180,7 +180,7 @@ async fn gemini_generates_valid_commit_for_simple_change() {
     let git = MockGitProvider::new(diffs::simple_addition());
     let agent = Agent::from(AgentName::Gemini);
 
-    let result = generate_commit_message(&git, &agent, None).await;
+    let result = generate_commit_message(&git, &agent, None, &Config::default()).await;
 
     assert!(
         result.is_ok(),
@@ -195,7 +195,7 @@ async fn gemini_generates_valid_commit_for_multi_file_change() {
     let git = MockGitProvider::new(diffs::multi_file_feature());
     let agent = Agent::from(AgentName::Gemini);
 
-    let result = generate_commit_message(&git, &agent, None).await;
+    let result = generate_commit_message(&git, &agent, None, &Config::default()).await;
 
     assert!(
         result.is_ok(),
@@ -220,7 +220,7 @@ async fn all_agents_produce_valid_commits_for_same_diff() {
     ];
 
     for agent in &agents {
-        let result = generate_commit_message(&git, agent, None).await;
+        let result = generate_commit_message(&git, agent, None, &Config::default()).await;
 
         assert!(
             result.is_ok(),